@@ -0,0 +1,176 @@
+use std::time::Duration;
+
+// YouTube only shows chapters when the description lists at least this many
+static MIN_CHAPTERS: usize = 3;
+
+// A part of a video, running until the next chapter starts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+  pub title: String,
+  pub start: Duration,
+}
+
+// Parse `h:mm:ss` or `m:ss`
+pub fn parse_timestamp(value: &str) -> Option<Duration> {
+  let parts: Vec<u64> = value.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+  let seconds = match parts.as_slice() {
+    [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => hours * 3600 + minutes * 60 + seconds,
+    [minutes, seconds] if *seconds < 60 => minutes * 60 + seconds,
+    _ => return None,
+  };
+  Some(Duration::from_secs(seconds))
+}
+
+// A line like `0:00 Intro`, `[1:02:03] - Outro` or `Intro (0:00)`: its first timestamp,
+// and the rest of the line as the title
+fn parse_line(line: &str) -> Option<Chapter> {
+  let separators: &[char] = &['-', '–', '—', '|', ':', '•', '·', '(', ')', '[', ']', '▶', '►'];
+  let words: Vec<&str> = line.split_whitespace().collect();
+  let (index, start) = words.iter().enumerate().find_map(|(index, word)| {
+    Some((index, parse_timestamp(word.trim_matches(|c: char| !c.is_ascii_digit()))?))
+  })?;
+
+  let title: Vec<&str> = words.iter().enumerate().filter(|(i, _)| *i != index).map(|(_, word)| *word).collect();
+  let title = title.join(" ").trim_matches(|c: char| c.is_whitespace() || separators.contains(&c)).to_string();
+  Some(Chapter { title, start })
+}
+
+// The chapters a description lists, one timestamp per line. Like YouTube, the list has
+// to start at 0:00 with increasing times, otherwise the timestamps are not chapters.
+pub fn parse_description(description: &str) -> Vec<Chapter> {
+  let mut chapters: Vec<Chapter> = vec![];
+  for chapter in description.lines().filter_map(parse_line) {
+    match chapters.last() {
+      Some(last) if chapter.start > last.start => chapters.push(chapter),
+      // A timestamp out of order ends the list, e.g. a later comment on a moment of the video
+      Some(_) if chapters.len() >= MIN_CHAPTERS => break,
+      // Start over from the next 0:00
+      _ => {
+        chapters.clear();
+        if chapter.start.is_zero() {
+          chapters.push(chapter);
+        }
+      },
+    }
+  }
+  if chapters.len() < MIN_CHAPTERS {
+    return vec![];
+  }
+  chapters
+}
+
+// The chapters of a `descriptionHtml`, its lines being split by `<br>` and the
+// timestamps being links
+pub fn parse_description_html(html: &str) -> Vec<Chapter> {
+  let mut text = String::new();
+  let mut rest = html;
+  while let Some(open) = rest.find('<') {
+    text.push_str(&rest[..open]);
+    let Some(close) = rest[open..].find('>') else {
+      break;
+    };
+    let tag = rest[open + 1..open + close].trim_start_matches('/').to_ascii_lowercase();
+    if tag.starts_with("br") || tag.starts_with("p") || tag.starts_with("div") {
+      text.push('\n');
+    }
+    rest = &rest[open + close + 1..];
+  }
+  text.push_str(rest);
+
+  parse_description(&decode_entities(&text))
+}
+
+// Replace the HTML entities Invidious sends, e.g. `&amp;` or `&#39;`
+pub fn decode_entities(text: &str) -> String {
+  let mut decoded = String::with_capacity(text.len());
+  let mut rest = text;
+  while let Some(start) = rest.find('&') {
+    decoded.push_str(&rest[..start]);
+    rest = &rest[start..];
+    let entity = rest.find(';').filter(|end| *end <= 10).map(|end| &rest[1..end]);
+    let character = entity.and_then(|entity| match entity {
+      "amp" => Some('&'),
+      "lt" => Some('<'),
+      "gt" => Some('>'),
+      "quot" => Some('"'),
+      "apos" => Some('\''),
+      "nbsp" => Some(' '),
+      _ => match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+        None => entity.strip_prefix('#').and_then(|decimal| decimal.parse().ok()).and_then(char::from_u32),
+      },
+    });
+    match (entity, character) {
+      (Some(entity), Some(character)) => {
+        decoded.push(character);
+        rest = &rest[entity.len() + 2..];
+      },
+      _ => {
+        decoded.push('&');
+        rest = &rest[1..];
+      },
+    }
+  }
+  decoded.push_str(rest);
+  decoded
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn chapter(title: &str, seconds: u64) -> Chapter {
+    Chapter { title: title.to_string(), start: Duration::from_secs(seconds) }
+  }
+
+  #[test]
+  fn parses_timestamps() {
+    assert_eq!(parse_timestamp("0:00"), Some(Duration::ZERO));
+    assert_eq!(parse_timestamp("12:34"), Some(Duration::from_secs(754)));
+    assert_eq!(parse_timestamp("1:02:03"), Some(Duration::from_secs(3723)));
+    assert_eq!(parse_timestamp("1:60"), None);
+    assert_eq!(parse_timestamp("1:2:3:4"), None);
+    assert_eq!(parse_timestamp("soon"), None);
+  }
+
+  #[test]
+  fn parses_the_chapters_of_a_description() {
+    let description = "My video\n\n0:00 Intro\n[1:30] - The middle part\nOutro (1:02:03)\n\nThanks for watching";
+    assert_eq!(parse_description(description), vec![
+      chapter("Intro", 0),
+      chapter("The middle part", 90),
+      chapter("Outro", 3723),
+    ]);
+  }
+
+  #[test]
+  fn needs_three_chapters_from_zero() {
+    assert!(parse_description("0:00 Intro\n1:00 Outro").is_empty());
+    assert!(parse_description("0:10 Intro\n1:00 Middle\n2:00 Outro").is_empty());
+  }
+
+  #[test]
+  fn stops_at_a_timestamp_out_of_order() {
+    let description = "0:00 A\n1:00 B\n2:00 C\n\nMy favourite part is 1:30";
+    assert_eq!(parse_description(description).len(), 3);
+    // Before three chapters, the list starts over from the next 0:00
+    let description = "Tracks 5:00 and 3:00\n0:00 A\n1:00 B\n2:00 C";
+    assert_eq!(parse_description(description)[0], chapter("A", 0));
+  }
+
+  #[test]
+  fn parses_the_chapters_of_an_html_description() {
+    let html = "Intro &amp; more<br><a href=\"#\">0:00</a> Start<br><a href=\"#\">1:00</a> Rock &#39;n&#x27; roll<br>2:00 End";
+    assert_eq!(parse_description_html(html), vec![
+      chapter("Start", 0),
+      chapter("Rock 'n' roll", 60),
+      chapter("End", 120),
+    ]);
+  }
+
+  #[test]
+  fn decodes_entities() {
+    assert_eq!(decode_entities("a &lt;b&gt; &quot;c&quot; &#233; &#xE9;"), "a <b> \"c\" é é");
+    assert_eq!(decode_entities("AT&T &unknown; &"), "AT&T &unknown; &");
+  }
+}
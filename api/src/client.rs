@@ -2,8 +2,10 @@ use std::io::Error;
 
 use crate::get_logger;
 
-use crate::fetcher::{SearchParams, VideoParams, SearchRequest, GetRequest, VideoRequest};
+use crate::fetcher::{get_bytes, SearchParams, VideoParams, PlaylistParams, CaptionsParams, StoryboardsParams, ChannelParams, ChannelTab, ChannelTabParams, CommentsParams, SuggestionsParams, SearchRequest, SuggestionsRequest, GetRequest, VideoRequest, PlaylistRequest, CaptionsRequest, StoryboardsRequest, ChannelRequest, ChannelTabRequest, CommentsRequest};
+use crate::models::{CaptionObject, CaptionsObject, ChannelObject, ChannelPlaylistsObject, ChannelVideosObject, CommentsObject, CommunityObject, PlaylistObject, SearchItem, StoryboardObject, SuggestionsObject, StoryboardsObject, VideoObject};
 
+#[derive(Debug, Clone)]
 pub struct APIClient {
   api_url: String,
 }
@@ -14,18 +16,100 @@ impl APIClient {
       api_url: url,
     }
   }
-  pub fn search(&self, params: SearchParams) -> Result<(), Error> {
+  // The instance the requests go to
+  pub fn api_url(&self) -> &str {
+    &self.api_url
+  }
+  pub fn search(&self, params: SearchParams) -> Result<Vec<SearchItem>, Error> {
     get_logger().info(format!("Searching with params: {:?}", params));
     let request = SearchRequest::new(self.api_url.clone(), params);
-    let response = request.execute();
-    get_logger().info(format!("Response: {}", response.expect("Failed to execute request")));
-    return Ok(());
+    let response = request.execute().map_err(Error::other)?;
+    serde_json::from_str(&response).map_err(|e| Error::other(format!("Invalid search response: {}", e)))
+  }
+  // Queries starting like `query`
+  pub fn get_suggestions(&self, query: String) -> Result<Vec<String>, Error> {
+    let request = SuggestionsRequest::new(self.api_url.clone(), SuggestionsParams { q: query });
+    let response = request.execute().map_err(Error::other)?;
+    let suggestions: SuggestionsObject = serde_json::from_str(&response)
+      .map_err(|e| Error::other(format!("Invalid suggestions response: {}", e)))?;
+    Ok(suggestions.suggestions)
   }
-  pub fn get_video(&self, video_id: String, params: VideoParams) -> Result<(), Error> {
+  pub fn get_video(&self, video_id: String, params: VideoParams) -> Result<VideoObject, Error> {
     get_logger().info(format!("Getting video with id: {}", video_id));
     let request = VideoRequest::new(self.api_url.clone(), video_id, params);
-    let response = request.execute();
-    get_logger().info(format!("Response: {}", response.expect("Failed to execute request")));
-    return Ok(());
+    let response = request.execute().map_err(Error::other)?;
+    serde_json::from_str(&response).map_err(|e| Error::other(format!("Invalid video response: {}", e)))
+  }
+  pub fn get_channel(&self, channel_id: String, params: ChannelParams) -> Result<ChannelObject, Error> {
+    get_logger().info(format!("Getting channel with id: {}", channel_id));
+    let request = ChannelRequest::new(self.api_url.clone(), channel_id, params);
+    let response = request.execute().map_err(Error::other)?;
+    serde_json::from_str(&response).map_err(|e| Error::other(format!("Invalid channel response: {}", e)))
+  }
+  // A page of the videos, shorts or streams of a channel, `tab` being one of them
+  pub fn get_channel_videos(&self, channel_id: String, tab: ChannelTab, params: ChannelTabParams) -> Result<ChannelVideosObject, Error> {
+    get_logger().info(format!("Getting {:?} of channel with id: {}", tab, channel_id));
+    let request = ChannelTabRequest::new(self.api_url.clone(), channel_id, tab, params);
+    let response = request.execute().map_err(Error::other)?;
+    serde_json::from_str(&response).map_err(|e| Error::other(format!("Invalid channel videos response: {}", e)))
+  }
+  pub fn get_channel_playlists(&self, channel_id: String, params: ChannelTabParams) -> Result<ChannelPlaylistsObject, Error> {
+    get_logger().info(format!("Getting playlists of channel with id: {}", channel_id));
+    let request = ChannelTabRequest::new(self.api_url.clone(), channel_id, ChannelTab::Playlists, params);
+    let response = request.execute().map_err(Error::other)?;
+    serde_json::from_str(&response).map_err(|e| Error::other(format!("Invalid channel playlists response: {}", e)))
+  }
+  pub fn get_channel_community(&self, channel_id: String, params: ChannelTabParams) -> Result<CommunityObject, Error> {
+    get_logger().info(format!("Getting community posts of channel with id: {}", channel_id));
+    let request = ChannelTabRequest::new(self.api_url.clone(), channel_id, ChannelTab::Community, params);
+    let response = request.execute().map_err(Error::other)?;
+    serde_json::from_str(&response).map_err(|e| Error::other(format!("Invalid community response: {}", e)))
+  }
+  // A page of the comments of a video, or of the replies of a comment with their continuation
+  pub fn get_comments(&self, video_id: String, params: CommentsParams) -> Result<CommentsObject, Error> {
+    get_logger().info(format!("Getting comments of video with id: {}", video_id));
+    let request = CommentsRequest::new(self.api_url.clone(), video_id, params);
+    let response = request.execute().map_err(Error::other)?;
+    serde_json::from_str(&response).map_err(|e| Error::other(format!("Invalid comments response: {}", e)))
   }
-}
\ No newline at end of file
+  pub fn get_playlist(&self, playlist_id: String, params: PlaylistParams) -> Result<PlaylistObject, Error> {
+    get_logger().info(format!("Getting playlist with id: {}", playlist_id));
+    let request = PlaylistRequest::new(self.api_url.clone(), playlist_id, params);
+    let response = request.execute().map_err(Error::other)?;
+    serde_json::from_str(&response).map_err(|e| Error::other(format!("Invalid playlist response: {}", e)))
+  }
+  // The caption tracks of a video
+  pub fn get_captions(&self, video_id: String) -> Result<Vec<CaptionObject>, Error> {
+    get_logger().info(format!("Getting captions of video with id: {}", video_id));
+    let request = CaptionsRequest::new(self.api_url.clone(), video_id, CaptionsParams { label: None, lang: None });
+    let response = request.execute().map_err(Error::other)?;
+    let captions: CaptionsObject = serde_json::from_str(&response)
+      .map_err(|e| Error::other(format!("Invalid captions response: {}", e)))?;
+    Ok(captions.captions)
+  }
+  // The WebVTT text of a caption track
+  pub fn get_caption_track(&self, video_id: String, caption: &CaptionObject) -> Result<String, Error> {
+    get_logger().info(format!("Getting the {} captions of video with id: {}", caption.label, video_id));
+    let params = CaptionsParams { label: Some(caption.label.clone()), lang: None };
+    let request = CaptionsRequest::new(self.api_url.clone(), video_id, params);
+    request.execute().map_err(Error::other)
+  }
+  // The storyboards of a video, from the smallest thumbnails to the largest
+  pub fn get_storyboards(&self, video_id: String) -> Result<Vec<StoryboardObject>, Error> {
+    get_logger().info(format!("Getting storyboards of video with id: {}", video_id));
+    let request = StoryboardsRequest::new(self.api_url.clone(), video_id, StoryboardsParams { width: None, height: None });
+    let response = request.execute().map_err(Error::other)?;
+    let storyboards: StoryboardsObject = serde_json::from_str(&response)
+      .map_err(|e| Error::other(format!("Invalid storyboards response: {}", e)))?;
+    Ok(storyboards.storyboards)
+  }
+  // Download a file linked by the API, the relative urls being on the instance
+  pub fn get_file(&self, url: &str) -> Result<Vec<u8>, Error> {
+    let url = match url {
+      url if url.starts_with("//") => format!("https:{}", url),
+      url if url.starts_with('/') => format!("{}{}", self.api_url, url),
+      url => url.to_string(),
+    };
+    get_bytes(&url).map_err(Error::other)
+  }
+}
@@ -6,9 +6,16 @@ use crate::get_logger;
 
 // API endpoints
 static SEARCH_ENDPOINT: &str = "/api/v1/search";
+static SUGGESTIONS_ENDPOINT: &str = "/api/v1/search/suggestions";
 static VIDEO_ENDPOINT: &str = "/api/v1/videos/{}";
+static PLAYLIST_ENDPOINT: &str = "/api/v1/playlists/{}";
+static CAPTIONS_ENDPOINT: &str = "/api/v1/captions/{}";
+static STORYBOARDS_ENDPOINT: &str = "/api/v1/storyboards/{}";
+static CHANNEL_ENDPOINT: &str = "/api/v1/channels/{}";
+static CHANNEL_TAB_ENDPOINT: &str = "/api/v1/channels/{}/{}";
+static COMMENTS_ENDPOINT: &str = "/api/v1/comments/{}";
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub struct SearchParams {
   pub q: String, // Query
   pub page: Option<u32>,
@@ -20,23 +27,93 @@ pub struct SearchParams {
   pub region: Option<String>, // Default: US
 }
 
+pub struct SuggestionsParams {
+  pub q: String, // The start of a query
+}
+
 pub struct VideoParams {
   pub region: Option<String>, // Default: US
 }
 
+pub struct PlaylistParams {
+  pub page: Option<u32>, // Pages of 100 videos, from 1
+}
+
+pub struct ChannelParams {
+  pub sort_by: Option<String>, // newest, popular, oldest
+}
+
+// The lists of a channel, each one has its endpoint
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChannelTab {
+  Videos,
+  Shorts,
+  Streams,
+  Playlists,
+  Community,
+}
+
+impl ChannelTab {
+  fn path(&self) -> &'static str {
+    match self {
+      ChannelTab::Videos => "videos",
+      ChannelTab::Shorts => "shorts",
+      ChannelTab::Streams => "streams",
+      ChannelTab::Playlists => "playlists",
+      ChannelTab::Community => "community",
+    }
+  }
+}
+
+pub struct ChannelTabParams {
+  pub sort_by: Option<String>, // newest, popular, oldest for the videos, newest, oldest, last for the playlists
+  pub continuation: Option<String>, // The next page
+}
+
+pub struct CommentsParams {
+  pub sort_by: Option<String>, // top, new
+  pub continuation: Option<String>, // The next page, or the replies of a comment
+}
+
+pub struct StoryboardsParams {
+  pub width: Option<u32>, // The storyboard with thumbnails this wide, every storyboard when not set
+  pub height: Option<u32>,
+}
+
+pub struct CaptionsParams {
+  pub label: Option<String>, // The track to download, the list of tracks when not set
+  pub lang: Option<String>, // Language code of the track to download
+}
+
 pub enum ParamsType {
   Search(SearchParams),
+  Suggestions(SuggestionsParams),
   Video(VideoParams),
+  Playlist(PlaylistParams),
+  Captions(CaptionsParams),
+  Storyboards(StoryboardsParams),
+  Comments(CommentsParams),
+  ChannelTab(ChannelTabParams),
+  Channel(ChannelParams),
 }
 
 trait Params {
   fn build(&self) -> String;
 }
 
+// Percent-encode a query parameter, keeping the characters that need no escaping
+fn encode(value: &str) -> String {
+  value.bytes().map(|byte| match byte {
+    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+    b' ' => "+".to_string(),
+    _ => format!("%{:02X}", byte),
+  }).collect()
+}
+
 impl Params for SearchParams {
   fn build(&self) -> String {
     let mut params = vec![];
-    params.push(format!("q={}", self.q));
+    params.push(format!("q={}", encode(&self.q)));
     if let Some(page) = self.page {
       params.push(format!("page={}", page));
     }
@@ -62,6 +139,12 @@ impl Params for SearchParams {
   }
 }
 
+impl Params for SuggestionsParams {
+  fn build(&self) -> String {
+    format!("q={}", encode(&self.q))
+  }
+}
+
 impl Params for VideoParams {
   fn build(&self) -> String {
     let mut params = vec![];
@@ -72,6 +155,78 @@ impl Params for VideoParams {
   }
 }
 
+impl Params for PlaylistParams {
+  fn build(&self) -> String {
+    let mut params = vec![];
+    if let Some(page) = self.page {
+      params.push(format!("page={}", page));
+    }
+    params.join("&")
+  }
+}
+
+impl Params for CaptionsParams {
+  fn build(&self) -> String {
+    let mut params = vec![];
+    if let Some(label) = &self.label {
+      params.push(format!("label={}", encode(label)));
+    }
+    if let Some(lang) = &self.lang {
+      params.push(format!("lang={}", encode(lang)));
+    }
+    params.join("&")
+  }
+}
+
+impl Params for ChannelParams {
+  fn build(&self) -> String {
+    let mut params = vec![];
+    if let Some(sort_by) = &self.sort_by {
+      params.push(format!("sort_by={}", sort_by));
+    }
+    params.join("&")
+  }
+}
+
+impl Params for ChannelTabParams {
+  fn build(&self) -> String {
+    let mut params = vec![];
+    if let Some(sort_by) = &self.sort_by {
+      params.push(format!("sort_by={}", sort_by));
+    }
+    if let Some(continuation) = &self.continuation {
+      params.push(format!("continuation={}", encode(continuation)));
+    }
+    params.join("&")
+  }
+}
+
+impl Params for CommentsParams {
+  fn build(&self) -> String {
+    let mut params = vec![];
+    if let Some(sort_by) = &self.sort_by {
+      params.push(format!("sort_by={}", sort_by));
+    }
+    if let Some(continuation) = &self.continuation {
+      params.push(format!("continuation={}", encode(continuation)));
+    }
+    params.join("&")
+  }
+}
+
+impl Params for StoryboardsParams {
+  fn build(&self) -> String {
+    let mut params = vec![];
+    if let Some(width) = &self.width {
+      params.push(format!("width={}", width));
+    }
+    if let Some(height) = &self.height {
+      params.push(format!("height={}", height));
+    }
+    params.join("&")
+  }
+}
+
 pub trait GetRequest {
   fn build_with_params(&self) -> String;
   fn execute(&self) -> Result<String, String>;
@@ -82,12 +237,54 @@ pub struct SearchRequest {
   params: ParamsType,
 }
 
+pub struct SuggestionsRequest {
+  url: String,
+  params: ParamsType,
+}
+
 pub struct VideoRequest {
   url: String,
   video_id: String,
   params: ParamsType,
 }
 
+pub struct PlaylistRequest {
+  url: String,
+  playlist_id: String,
+  params: ParamsType,
+}
+
+pub struct CaptionsRequest {
+  url: String,
+  video_id: String,
+  params: ParamsType,
+}
+
+pub struct ChannelRequest {
+  url: String,
+  channel_id: String,
+  params: ParamsType,
+}
+
+pub struct ChannelTabRequest {
+  url: String,
+  channel_id: String,
+  tab: ChannelTab,
+  params: ParamsType,
+}
+
+pub struct CommentsRequest {
+  url: String,
+  video_id: String,
+  params: ParamsType,
+}
+
+pub struct StoryboardsRequest {
+  url: String,
+  video_id: String,
+  params: ParamsType,
+}
+
 impl SearchRequest {
   pub fn new(api_url: String, params: SearchParams) -> Self {
     Self {
@@ -97,6 +294,36 @@ impl SearchRequest {
   }
 }
 
+impl SuggestionsRequest {
+  pub fn new(api_url: String, params: SuggestionsParams) -> Self {
+    Self {
+      url: format!("{}{}", api_url, SUGGESTIONS_ENDPOINT),
+      params: ParamsType::Suggestions(params),
+    }
+  }
+}
+
+impl ChannelTabRequest {
+  pub fn new(api_url: String, channel_id: String, tab: ChannelTab, params: ChannelTabParams) -> Self {
+    Self {
+      url: format!("{}{}", api_url, CHANNEL_TAB_ENDPOINT),
+      channel_id,
+      tab,
+      params: ParamsType::ChannelTab(params),
+    }
+  }
+}
+
+impl CommentsRequest {
+  pub fn new(api_url: String, video_id: String, params: CommentsParams) -> Self {
+    Self {
+      url: format!("{}{}", api_url, COMMENTS_ENDPOINT),
+      video_id,
+      params: ParamsType::Comments(params),
+    }
+  }
+}
+
 impl VideoRequest {
   pub fn new(api_url: String, video_id: String, params: VideoParams) -> Self {
     Self {
@@ -107,6 +334,46 @@ impl VideoRequest {
   }
 }
 
+impl PlaylistRequest {
+  pub fn new(api_url: String, playlist_id: String, params: PlaylistParams) -> Self {
+    Self {
+      url: format!("{}{}", api_url, PLAYLIST_ENDPOINT),
+      playlist_id,
+      params: ParamsType::Playlist(params),
+    }
+  }
+}
+
+impl CaptionsRequest {
+  pub fn new(api_url: String, video_id: String, params: CaptionsParams) -> Self {
+    Self {
+      url: format!("{}{}", api_url, CAPTIONS_ENDPOINT),
+      video_id,
+      params: ParamsType::Captions(params),
+    }
+  }
+}
+
+impl ChannelRequest {
+  pub fn new(api_url: String, channel_id: String, params: ChannelParams) -> Self {
+    Self {
+      url: format!("{}{}", api_url, CHANNEL_ENDPOINT),
+      channel_id,
+      params: ParamsType::Channel(params),
+    }
+  }
+}
+
+impl StoryboardsRequest {
+  pub fn new(api_url: String, video_id: String, params: StoryboardsParams) -> Self {
+    Self {
+      url: format!("{}{}", api_url, STORYBOARDS_ENDPOINT),
+      video_id,
+      params: ParamsType::Storyboards(params),
+    }
+  }
+}
+
 impl GetRequest for SearchRequest {
   fn build_with_params(&self) -> String {
     match &self.params {
@@ -114,7 +381,7 @@ impl GetRequest for SearchRequest {
         let mut url = self.url.clone();
         let params = params.build();
         if !params.is_empty() {
-          url.push_str("?");
+          url.push('?');
           url.push_str(&params);
         }
         url
@@ -127,7 +394,28 @@ impl GetRequest for SearchRequest {
     get_logger().debug(format!("Sending request to {}", url));
     let client = reqwest::Client::new();
 
-    let response = block_on(client.get(url).send()).unwrap();
+    let response = block_on(client.get(url).send()).map_err(|e| e.to_string())?;
+    let response = response.error_for_status().map_err(|e| e.to_string())?;
+
+    let body = block_on(response.text()).map_err(|e| e.to_string())?;
+    Ok(body)
+  }
+}
+
+impl GetRequest for SuggestionsRequest {
+  fn build_with_params(&self) -> String {
+    match &self.params {
+      ParamsType::Suggestions(params) => format!("{}?{}", self.url, params.build()),
+      _ => panic!("Invalid params type"),
+    }
+  }
+  fn execute(&self) -> Result<String, String> {
+    let url = self.build_with_params();
+    get_logger().debug(format!("Sending request to {}", url));
+    let client = reqwest::Client::new();
+
+    let response = block_on(client.get(url).send()).map_err(|e| e.to_string())?;
+    let response = response.error_for_status().map_err(|e| e.to_string())?;
 
     let body = block_on(response.text()).map_err(|e| e.to_string())?;
     Ok(body)
@@ -142,7 +430,7 @@ impl GetRequest for VideoRequest {
         url = url.replace("{}", &self.video_id);
         let params = params.build();
         if !params.is_empty() {
-          url.push_str("?");
+          url.push('?');
           url.push_str(&params);
         }
         url
@@ -155,9 +443,201 @@ impl GetRequest for VideoRequest {
     get_logger().debug(format!("Sending request to {}", url));
     let client = reqwest::Client::new();
 
-    let response = block_on(client.get(url).send()).unwrap();
+    let response = block_on(client.get(url).send()).map_err(|e| e.to_string())?;
+    let response = response.error_for_status().map_err(|e| e.to_string())?;
 
-    let body = block_on(response.text()).unwrap();
+    let body = block_on(response.text()).map_err(|e| e.to_string())?;
     Ok(body)
   }
-}
\ No newline at end of file
+}
+
+impl GetRequest for PlaylistRequest {
+  fn build_with_params(&self) -> String {
+    match &self.params {
+      ParamsType::Playlist(params) => {
+        let mut url = self.url.replace("{}", &self.playlist_id);
+        let params = params.build();
+        if !params.is_empty() {
+          url.push('?');
+          url.push_str(&params);
+        }
+        url
+      },
+      _ => panic!("Invalid params type"),
+    }
+  }
+  fn execute(&self) -> Result<String, String> {
+    let url = self.build_with_params();
+    get_logger().debug(format!("Sending request to {}", url));
+    let client = reqwest::Client::new();
+
+    let response = block_on(client.get(url).send()).map_err(|e| e.to_string())?;
+    let response = response.error_for_status().map_err(|e| e.to_string())?;
+
+    let body = block_on(response.text()).map_err(|e| e.to_string())?;
+    Ok(body)
+  }
+}
+
+impl GetRequest for CaptionsRequest {
+  fn build_with_params(&self) -> String {
+    match &self.params {
+      ParamsType::Captions(params) => {
+        let mut url = self.url.replace("{}", &self.video_id);
+        let params = params.build();
+        if !params.is_empty() {
+          url.push('?');
+          url.push_str(&params);
+        }
+        url
+      },
+      _ => panic!("Invalid params type"),
+    }
+  }
+  fn execute(&self) -> Result<String, String> {
+    let url = self.build_with_params();
+    get_logger().debug(format!("Sending request to {}", url));
+    let client = reqwest::Client::new();
+
+    let response = block_on(client.get(url).send()).map_err(|e| e.to_string())?;
+    let response = response.error_for_status().map_err(|e| e.to_string())?;
+
+    let body = block_on(response.text()).map_err(|e| e.to_string())?;
+    Ok(body)
+  }
+}
+
+impl GetRequest for ChannelTabRequest {
+  fn build_with_params(&self) -> String {
+    match &self.params {
+      ParamsType::ChannelTab(params) => {
+        let mut url = self.url.replacen("{}", &self.channel_id, 1).replacen("{}", self.tab.path(), 1);
+        let params = params.build();
+        if !params.is_empty() {
+          url.push('?');
+          url.push_str(&params);
+        }
+        url
+      },
+      _ => panic!("Invalid params type"),
+    }
+  }
+  fn execute(&self) -> Result<String, String> {
+    let url = self.build_with_params();
+    get_logger().debug(format!("Sending request to {}", url));
+    let client = reqwest::Client::new();
+
+    let response = block_on(client.get(url).send()).map_err(|e| e.to_string())?;
+    let response = response.error_for_status().map_err(|e| e.to_string())?;
+
+    let body = block_on(response.text()).map_err(|e| e.to_string())?;
+    Ok(body)
+  }
+}
+
+impl GetRequest for CommentsRequest {
+  fn build_with_params(&self) -> String {
+    match &self.params {
+      ParamsType::Comments(params) => {
+        let mut url = self.url.replace("{}", &self.video_id);
+        let params = params.build();
+        if !params.is_empty() {
+          url.push('?');
+          url.push_str(&params);
+        }
+        url
+      },
+      _ => panic!("Invalid params type"),
+    }
+  }
+  fn execute(&self) -> Result<String, String> {
+    let url = self.build_with_params();
+    get_logger().debug(format!("Sending request to {}", url));
+    let client = reqwest::Client::new();
+
+    let response = block_on(client.get(url).send()).map_err(|e| e.to_string())?;
+    let response = response.error_for_status().map_err(|e| e.to_string())?;
+
+    let body = block_on(response.text()).map_err(|e| e.to_string())?;
+    Ok(body)
+  }
+}
+
+impl GetRequest for ChannelRequest {
+  fn build_with_params(&self) -> String {
+    match &self.params {
+      ParamsType::Channel(params) => {
+        let mut url = self.url.replace("{}", &self.channel_id);
+        let params = params.build();
+        if !params.is_empty() {
+          url.push('?');
+          url.push_str(&params);
+        }
+        url
+      },
+      _ => panic!("Invalid params type"),
+    }
+  }
+  fn execute(&self) -> Result<String, String> {
+    let url = self.build_with_params();
+    get_logger().debug(format!("Sending request to {}", url));
+    let client = reqwest::Client::new();
+
+    let response = block_on(client.get(url).send()).map_err(|e| e.to_string())?;
+    let response = response.error_for_status().map_err(|e| e.to_string())?;
+
+    let body = block_on(response.text()).map_err(|e| e.to_string())?;
+    Ok(body)
+  }
+}
+
+impl GetRequest for StoryboardsRequest {
+  fn build_with_params(&self) -> String {
+    match &self.params {
+      ParamsType::Storyboards(params) => {
+        let mut url = self.url.replace("{}", &self.video_id);
+        let params = params.build();
+        if !params.is_empty() {
+          url.push('?');
+          url.push_str(&params);
+        }
+        url
+      },
+      _ => panic!("Invalid params type"),
+    }
+  }
+  fn execute(&self) -> Result<String, String> {
+    let url = self.build_with_params();
+    get_logger().debug(format!("Sending request to {}", url));
+    let client = reqwest::Client::new();
+
+    let response = block_on(client.get(url).send()).map_err(|e| e.to_string())?;
+    let response = response.error_for_status().map_err(|e| e.to_string())?;
+
+    let body = block_on(response.text()).map_err(|e| e.to_string())?;
+    Ok(body)
+  }
+}
+
+// The body of a file the API links to, e.g. an image
+pub fn get_bytes(url: &str) -> Result<Vec<u8>, String> {
+  get_logger().debug(format!("Sending request to {}", url));
+  let client = reqwest::Client::new();
+
+  let response = block_on(client.get(url).send()).map_err(|e| e.to_string())?;
+  let response = response.error_for_status().map_err(|e| e.to_string())?;
+
+  let body = block_on(response.bytes()).map_err(|e| e.to_string())?;
+  Ok(body.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn encodes_the_caption_track() {
+    let params = CaptionsParams { label: Some("English (auto-generated)".to_string()), lang: Some("en-US".to_string()) };
+    assert_eq!(params.build(), "label=English+%28auto-generated%29&lang=en-US");
+  }
+}
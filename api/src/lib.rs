@@ -1,5 +1,7 @@
+pub mod chapters;
 pub mod client;
 pub mod fetcher;
+pub mod models;
 
 use lazy_static::lazy_static;
 use std::sync::Mutex;
@@ -1,110 +1,296 @@
-struct ImageObject {
-  url: String,
-  width: i32, // Integer
-  height: i32, // Integer
-}
-
-struct ThumbnailObject {
-  quality: String,
-  url: String,
-  width: i32, // Integer
-  height: i32, // Integer
-}
-
-struct FormatObject {
-  index: String,
-  bitrate: String,
-  init: String,
-  url: String,
-  itag: String,
-  ctype: String,
-  clen: String,
-  lmt: String,
-  projectionType: i32, // Integer
-  container: String,
-  encoding: String,
-  qualityLabel: String,
-  resolution: String,
-}
-
-struct VideoObject {
-  r#type: String, // Constant
-  title: String,
-  videoId: String,
-  author: String,
-  authorId: String,
-  authorUrl: String,
-  authorVerified: bool,
-  videoThumbnails: Vec<ThumbnailObject>,
-  description: String,
-  descriptionHtml: String,
-  viewCount: i32, // Integer
-  viewCountText: String,
-  lengthSeconds: i32, // Integer
-  published: i64, // Unix timestamp
-  publishedText: String,
-  premiereTimestamp: Option<i64>, // Unix timestamp, Only available on premiered videos
-  liveNow: bool,
-  premium: bool,
-  isUpcoming: bool,
-
-  hlsUrl: Option<String>,
-  adaptiveFormats: Option<Vec<FormatObject>>,
-  recommendedVideos: Option<Vec<VideoObject>>,
-}
-
-struct ChannelObject {
-  r#type: String, // Constant
-  author: String,
-  authorId: String,
-  authorUrl: String,
-  authorVerified: bool,
-  authorThumbnails: Vec<ThumbnailObject>,
-  autoGenerated: bool,
-  subCount: i32, // Integer
-  videoCount: i32, // Integer
-  description: String,
-  descriptionHtml: String,
-}
-
-struct PlaylistVideo {
-  title: String,
-  videoId: String,
-  lengthSeconds: i32, // Integer
-  videoThumbnails: Vec<ThumbnailObject>,
-}
-
-struct PlaylistObject {
-  r#type: String, // Constant
-  title: String,
-  playlistId: String,
-  playlistThumbnail: String,
-  author: String,
-  authorId: String,
-  authorUrl: String,
-  authorVerified: bool,
-  videoCount: i32, // Integer
-  videos: Vec<PlaylistVideo>,
-}
-
-struct CommentObject {
-  author: String,
-  authorThumbnails: Vec<ThumbnailObject>,
-  authorId: String,
-  authorUrl: String,
-  isEdited: bool,
-  isPinned: bool,
-  content: String,
-  contentHtml: String,
-  published: i64, // Unix timestamp
-  publishedText: String,
-  likeCount: i32, // Integer
-  commentId: String,
-  authorIsChannelOwner: bool,
-  replies: Option<CommentReplyObject>,
-}
-
-struct CommentReplyObject {
-  repliesCount: i32, // Integer
-  continuation: String,
+// Objects returned by the Invidious API, the fields keep the names of its json
+#![allow(non_snake_case)]
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::chapters::{parse_description, parse_description_html, Chapter};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ImageObject {
+  pub url: String,
+  pub width: i32, // Integer
+  pub height: i32, // Integer
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ThumbnailObject {
+  pub quality: String,
+  pub url: String,
+  pub width: i32, // Integer
+  pub height: i32, // Integer
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FormatObject {
+  pub index: String,
+  pub bitrate: String,
+  pub init: String,
+  pub url: String,
+  pub itag: String,
+  pub ctype: String,
+  pub clen: String,
+  pub lmt: String,
+  pub projectionType: i32, // Integer
+  pub container: String,
+  pub encoding: String,
+  pub qualityLabel: String,
+  pub resolution: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct VideoObject {
+  pub r#type: String, // Constant
+  pub title: String,
+  pub videoId: String,
+  pub author: String,
+  pub authorId: String,
+  pub authorUrl: String,
+  pub authorVerified: bool,
+  pub videoThumbnails: Vec<ThumbnailObject>,
+  pub description: String,
+  pub descriptionHtml: String,
+  pub viewCount: i64, // Integer
+  pub viewCountText: String,
+  pub likeCount: i64, // Integer, only in the video endpoint
+  pub keywords: Vec<String>, // Only in the video endpoint
+  pub subCountText: String, // Subscribers of the author, only in the video endpoint
+  pub lengthSeconds: i32, // Integer
+  pub published: i64, // Unix timestamp
+  pub publishedText: String,
+  pub premiereTimestamp: Option<i64>, // Unix timestamp, Only available on premiered videos
+  pub liveNow: bool,
+  pub premium: bool,
+  pub isUpcoming: bool,
+
+  pub hlsUrl: Option<String>,
+  pub adaptiveFormats: Option<Vec<FormatObject>>,
+  pub captions: Option<Vec<CaptionObject>>,
+  pub chapters: Option<Vec<ChapterObject>>, // Only sent by the instances extracting them
+  pub recommendedVideos: Option<Vec<VideoObject>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ChapterObject {
+  pub title: String,
+  pub startTime: f64, // Seconds
+}
+
+impl StoryboardObject {
+  // Where the thumbnail shown at `position` is: the sheet index, and the pixel
+  // offset of the thumbnail in the sheet
+  pub fn locate(&self, position: Duration) -> Option<(u32, u32, u32)> {
+    let per_sheet = self.storyboardWidth * self.storyboardHeight;
+    if self.interval == 0 || self.count == 0 || per_sheet == 0 {
+      return None;
+    }
+    let index = ((position.as_millis() / self.interval as u128) as u32).min(self.count - 1);
+    let cell = index % per_sheet;
+    Some((index / per_sheet, cell % self.storyboardWidth * self.width, cell / self.storyboardWidth * self.height))
+  }
+
+  // The url of a sheet
+  pub fn sheet_url(&self, sheet: u32) -> String {
+    self.templateUrl.replace("$M", &sheet.to_string())
+  }
+}
+
+impl VideoObject {
+  // The chapters sent by the instance, or the ones listed in the description
+  pub fn chapters(&self) -> Vec<Chapter> {
+    if let Some(chapters) = self.chapters.as_ref().filter(|chapters| !chapters.is_empty()) {
+      return chapters.iter().map(|chapter| Chapter {
+        title: chapter.title.clone(),
+        start: Duration::from_secs_f64(chapter.startTime.max(0.0)),
+      }).collect();
+    }
+    match self.description.is_empty() {
+      true => parse_description_html(&self.descriptionHtml),
+      false => parse_description(&self.description),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CaptionObject {
+  pub label: String,
+  pub languageCode: String,
+  pub url: String, // Relative to the api url, serves the WebVTT track
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CaptionsObject {
+  pub captions: Vec<CaptionObject>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct StoryboardObject {
+  pub url: String, // Relative to the api url, serves the storyboard as WebVTT
+  pub templateUrl: String, // The url of a sheet, `$M` being replaced by its index
+  pub width: u32, // Width of a thumbnail
+  pub height: u32, // Height of a thumbnail
+  pub count: u32, // Number of thumbnails
+  pub interval: u64, // Milliseconds between two thumbnails
+  pub storyboardWidth: u32, // Thumbnails per row of a sheet
+  pub storyboardHeight: u32, // Thumbnails per column of a sheet
+  pub storyboardCount: u32, // Number of sheets
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct StoryboardsObject {
+  pub storyboards: Vec<StoryboardObject>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ChannelObject {
+  pub r#type: String, // Constant
+  pub author: String,
+  pub authorId: String,
+  pub authorUrl: String,
+  pub authorVerified: bool,
+  pub authorThumbnails: Vec<ThumbnailObject>,
+  pub autoGenerated: bool,
+  pub subCount: i64, // Integer
+  pub videoCount: i32, // Integer
+  pub description: String,
+  pub descriptionHtml: String,
+  pub authorBanners: Vec<ImageObject>,
+  pub latestVideos: Vec<VideoObject>, // Only in the channel endpoint
+}
+
+// A page of the videos, shorts or streams of a channel
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ChannelVideosObject {
+  pub videos: Vec<VideoObject>,
+  pub continuation: Option<String>, // The next page, none after the last one
+}
+
+// A page of the playlists of a channel
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ChannelPlaylistsObject {
+  pub playlists: Vec<PlaylistObject>,
+  pub continuation: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AttachmentObject {
+  pub r#type: String, // image, video, poll, multiImage, playlist
+  pub title: String, // Only on the video and playlist attachments
+}
+
+// A post of the community tab of a channel
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CommunityPostObject {
+  pub author: String,
+  pub authorId: String,
+  pub content: String,
+  pub contentHtml: String,
+  pub published: i64, // Unix timestamp
+  pub publishedText: String,
+  pub likeCount: i32, // Integer
+  pub replyCount: i32, // Integer
+  pub commentId: String,
+  pub attachment: Option<AttachmentObject>,
+}
+
+// A page of the community tab of a channel
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CommunityObject {
+  pub authorId: String,
+  pub comments: Vec<CommunityPostObject>,
+  pub continuation: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PlaylistVideo {
+  pub title: String,
+  pub videoId: String,
+  pub lengthSeconds: i32, // Integer
+  pub videoThumbnails: Vec<ThumbnailObject>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PlaylistObject {
+  pub r#type: String, // Constant
+  pub title: String,
+  pub playlistId: String,
+  pub playlistThumbnail: String,
+  pub author: String,
+  pub authorId: String,
+  pub authorUrl: String,
+  pub authorVerified: bool,
+  pub videoCount: i32, // Integer
+  pub videos: Vec<PlaylistVideo>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SuggestionsObject {
+  pub query: String,
+  pub suggestions: Vec<String>,
+}
+
+// An item of the search results, told apart by its `type`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SearchItem {
+  Video(Box<VideoObject>),
+  Channel(ChannelObject),
+  Playlist(PlaylistObject),
+  // The kinds not handled, e.g. hashtags
+  #[serde(other)]
+  Other,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CommentObject {
+  pub author: String,
+  pub authorThumbnails: Vec<ThumbnailObject>,
+  pub authorId: String,
+  pub authorUrl: String,
+  pub isEdited: bool,
+  pub isPinned: bool,
+  pub content: String,
+  pub contentHtml: String,
+  pub published: i64, // Unix timestamp
+  pub publishedText: String,
+  pub likeCount: i32, // Integer
+  pub commentId: String,
+  pub authorIsChannelOwner: bool,
+  pub replies: Option<CommentReplyObject>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CommentReplyObject {
+  pub repliesCount: i32, // Integer
+  pub continuation: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CommentsObject {
+  pub commentCount: Option<i32>, // Integer, only on the first page
+  pub videoId: String,
+  pub comments: Vec<CommentObject>,
+  pub continuation: Option<String>, // The next page, none after the last one
 }
\ No newline at end of file
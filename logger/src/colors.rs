@@ -1,3 +1,7 @@
+use std::sync::Mutex;
+
+use crate::LogLevel;
+
 pub static BLACK: &str = "\x1b[30m";
 pub static BLUE: &str = "\x1b[34m";
 pub static CYAN: &str = "\x1b[36m";
@@ -13,4 +17,153 @@ pub static UNDERLINE: &str = "\x1b[4m";
 pub static BLINK: &str = "\x1b[5m";
 pub static REVERSE: &str = "\x1b[7m";
 pub static HIDDEN: &str = "\x1b[8m";
-pub static STRIKETHROUGH: &str = "\x1b[9m";
\ No newline at end of file
+pub static STRIKETHROUGH: &str = "\x1b[9m";
+
+// How many colors the terminal shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    // `NO_COLOR` is set, or the terminal is dumb
+    None,
+}
+
+impl ColorDepth {
+    // What the environment tells of the terminal, see https://no-color.org
+    pub fn detect() -> ColorDepth {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        let term = var("TERM");
+        if !var("NO_COLOR").is_empty() || term == "dumb" {
+            ColorDepth::None
+        } else if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+// A color, with the ones standing for it on terminals with fewer colors
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shade {
+    pub rgb: (u8, u8, u8),
+    // Index in the xterm palette
+    pub ansi256: u8,
+    // Index in the 16 basic colors, 8 to 15 being the bright ones
+    pub ansi16: u8,
+}
+
+// The basic colors as xterm draws them, to find the closest one
+static ANSI16: [(u8, u8, u8); 16] = [
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0), (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0), (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+];
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+impl Shade {
+    pub const fn new(rgb: (u8, u8, u8), ansi256: u8, ansi16: u8) -> Shade {
+        Shade { rgb, ansi256, ansi16 }
+    }
+
+    // A color with the closest ones of the smaller palettes
+    pub fn from_rgb(rgb: (u8, u8, u8)) -> Shade {
+        // The 6x6x6 cube, or the gray ramp when it is closer
+        let level = |c: u8| if c < 48 { 0 } else if c < 115 { 1 } else { (c as u16 - 35) / 40 } as u8;
+        let value = |l: u8| if l == 0 { 0 } else { 55 + l * 40 };
+        let cube = (level(rgb.0), level(rgb.1), level(rgb.2));
+        let cube_rgb = (value(cube.0), value(cube.1), value(cube.2));
+        let average = (rgb.0 as u16 + rgb.1 as u16 + rgb.2 as u16) / 3;
+        let gray = (average.saturating_sub(3) / 10).min(23) as u8;
+        let gray_value = 8 + gray * 10;
+        let ansi256 = match distance(rgb, (gray_value, gray_value, gray_value)) < distance(rgb, cube_rgb) {
+            true => 232 + gray,
+            false => 16 + 36 * cube.0 + 6 * cube.1 + cube.2,
+        };
+        let ansi16 = (0..16).min_by_key(|index| distance(rgb, ANSI16[*index as usize])).unwrap_or(7);
+        Shade { rgb, ansi256, ansi16 }
+    }
+
+    // `#ff8700`
+    pub fn parse(hex: &str) -> Option<Shade> {
+        let hex = hex.strip_prefix('#')?;
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+        Some(Shade::from_rgb((channel(0)?, channel(2)?, channel(4)?)))
+    }
+
+    // The escape code drawing text in this color, nothing without colors
+    pub fn escape(&self, depth: ColorDepth) -> String {
+        match depth {
+            ColorDepth::TrueColor => format!("\x1b[38;2;{};{};{}m", self.rgb.0, self.rgb.1, self.rgb.2),
+            ColorDepth::Ansi256 => format!("\x1b[38;5;{}m", self.ansi256),
+            ColorDepth::Ansi16 if self.ansi16 < 8 => format!("\x1b[{}m", 30 + self.ansi16),
+            ColorDepth::Ansi16 => format!("\x1b[{}m", 90 + self.ansi16 - 8),
+            ColorDepth::None => String::new(),
+        }
+    }
+}
+
+// The colors of the log levels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelColors {
+    pub success: Shade,
+    pub info: Shade,
+    pub warn: Shade,
+    pub debug: Shade,
+    pub error: Shade,
+}
+
+// Set by the theme, the basic colors are used until then
+static LEVELS: Mutex<Option<(ColorDepth, LevelColors)>> = Mutex::new(None);
+
+// Draw the log levels with `colors`, in as many colors as `depth`
+pub fn set_level_colors(depth: ColorDepth, colors: LevelColors) {
+    *LEVELS.lock().unwrap_or_else(|e| e.into_inner()) = Some((depth, colors));
+}
+
+// The depth of the theme, or else of the terminal
+pub fn depth() -> ColorDepth {
+    match *LEVELS.lock().unwrap_or_else(|e| e.into_inner()) {
+        Some((depth, _)) => depth,
+        None => ColorDepth::detect(),
+    }
+}
+
+// `code` unless the colors are off, for the codes of this module
+pub fn paint(code: &'static str) -> &'static str {
+    match depth() {
+        ColorDepth::None => "",
+        _ => code,
+    }
+}
+
+// The escape code of the color of `level`
+pub fn level(level: LogLevel) -> String {
+    let Some((depth, colors)) = *LEVELS.lock().unwrap_or_else(|e| e.into_inner()) else {
+        let code = match level {
+            LogLevel::Success => GREEN,
+            LogLevel::Info => CYAN,
+            LogLevel::Warn => YELLOW,
+            LogLevel::Debug => PURPLE,
+            LogLevel::Error => RED,
+        };
+        return paint(code).to_string();
+    };
+    let shade = match level {
+        LogLevel::Success => colors.success,
+        LogLevel::Info => colors.info,
+        LogLevel::Warn => colors.warn,
+        LogLevel::Debug => colors.debug,
+        LogLevel::Error => colors.error,
+    };
+    shade.escape(depth)
+}
@@ -1,8 +1,29 @@
 pub mod colors;
 
-use colors::{YELLOW, CYAN, GREEN, RESET, PURPLE, RED, ITALIC};
+use std::sync::Mutex;
+
+use colors::{paint, RESET, ITALIC};
+
+// Receives the messages instead of stdout, e.g. while a full screen interface is shown
+pub type Sink = Box<dyn Fn(&str, LogLevel, &str) + Send>;
+
+static SINK: Mutex<Option<Sink>> = Mutex::new(None);
+
+// Send the messages of every module to `sink`, or back to stdout with `None`
+pub fn set_sink(sink: Option<Sink>) {
+    *SINK.lock().unwrap_or_else(|e| e.into_inner()) = sink;
+}
+
+// Print a message of `module`, or hand it to the sink when one is set
+fn emit(module: &str, level: LogLevel, message: &str) {
+    match SINK.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(sink) => sink(module, level, message),
+        None => println!("[{}{}{}] [{}] {}", paint(ITALIC), module, paint(RESET), level, message),
+    }
+}
 
 // Define a Level enum
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum LogLevel {
     Success,
     Info,
@@ -12,14 +33,21 @@ pub enum LogLevel {
 }
 
 // Implement Level
+impl std::fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}{}", colors::level(*self), self.name(), paint(RESET))
+    }
+}
+
 impl LogLevel {
-    pub fn to_string(&self) -> String {
+    // The level without its color
+    pub fn name(&self) -> &'static str {
         match self {
-            LogLevel::Success => format!("{}{}{}", GREEN, "Success".to_string(), RESET),
-            LogLevel::Info => format!("{}{}{}", CYAN, "Info".to_string(), RESET),
-            LogLevel::Warn => format!("{}{}{}", YELLOW, "Warn".to_string(), RESET),
-            LogLevel::Debug => format!("{}{}{}", PURPLE, "Debug".to_string(), RESET),
-            LogLevel::Error => format!("{}{}{}", RED, "Error".to_string(), RESET),
+            LogLevel::Success => "Success",
+            LogLevel::Info => "Info",
+            LogLevel::Warn => "Warn",
+            LogLevel::Debug => "Debug",
+            LogLevel::Error => "Error",
         }
     }
 }
@@ -49,7 +77,7 @@ pub struct APILogger;
 
 impl Logger for APILogger {
     fn log(&self, message: &str, level: LogLevel) {
-        println!("[{}API{}] [{}] {}", ITALIC, RESET, level.to_string(), message);
+        emit("API", level, message);
     }
 }
 
@@ -58,7 +86,7 @@ pub struct UiLogger;
 
 impl Logger for UiLogger {
     fn log(&self, message: &str, level: LogLevel) {
-        println!("[{}UI{}] [{}] {}", ITALIC, RESET, level.to_string(), message);
+        emit("UI", level, message);
     }
 }
 
@@ -67,7 +95,16 @@ pub struct CoreLogger;
 
 impl Logger for CoreLogger {
     fn log(&self, message: &str, level: LogLevel) {
-        println!("[{}Core{}] [{}] {}", ITALIC, RESET, level.to_string(), message);
+        emit("Core", level, message);
+    }
+}
+
+// Implement Logger for Youtube module
+pub struct YoutubeLogger;
+
+impl Logger for YoutubeLogger {
+    fn log(&self, message: &str, level: LogLevel) {
+        emit("Youtube", level, message);
     }
 }
 
@@ -76,6 +113,7 @@ pub enum ModuleType {
     API,
     Ui,
     Core,
+    Youtube,
 }
 
 // Function to create a logger for a specific module
@@ -84,5 +122,6 @@ pub fn create_logger(module_type: ModuleType) -> Box<dyn Logger> {
         ModuleType::API => Box::new(APILogger),
         ModuleType::Ui => Box::new(UiLogger),
         ModuleType::Core => Box::new(CoreLogger),
+        ModuleType::Youtube => Box::new(YoutubeLogger),
     }
 }
\ No newline at end of file
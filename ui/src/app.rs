@@ -0,0 +1,497 @@
+use std::sync::mpsc::{RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use logger::colors::ColorDepth;
+use logger::LogLevel;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::event::{Event, EventLoop, RequestId, Response};
+use crate::{format, get_logger};
+use crate::keymap::{Action, Keymap, Scope};
+use crate::library::Library;
+use crate::palette::{Command, CommandPalette, Outcome};
+use crate::provider::{MiniPlayer, Provider};
+use crate::screens::channel::ChannelScreen;
+use crate::screens::home::HomeScreen;
+use crate::screens::player::PlayerScreen;
+use crate::screens::search::SearchScreen;
+use crate::screens::video::VideoScreen;
+use crate::screens::{Screen, Transition};
+use crate::terminal::{self, Tui};
+use crate::theme;
+
+// How long a logged message stays on the status line
+static MESSAGE_DURATION: Duration = Duration::from_secs(4);
+
+// Columns of the mini player, its rows keep a 16:9 frame of cells twice as tall as wide
+static MINI_COLS: u16 = 32;
+
+// How often the mini player is drawn again
+static MINI_FRAME: Duration = Duration::from_millis(66);
+
+// What the screens share: the provider, and a way to reach it without blocking
+pub struct Context {
+  provider: Arc<dyn Provider>,
+  events: Sender<Event>,
+  next_request: u64,
+  // The provider may use reqwest, which needs the tokio runtime of the caller
+  runtime: Option<tokio::runtime::Handle>,
+  // Counts the ticks, for the loading spinners
+  pub ticks: u64,
+  // The videos to watch next, in order
+  pub queue: Vec<QueuedVideo>,
+  pub keymap: Keymap,
+  // The searches, watched videos and subscriptions kept between runs
+  pub library: Library,
+}
+
+// A video added to the queue
+#[derive(Debug, Clone)]
+pub struct QueuedVideo {
+  pub id: String,
+  pub title: String,
+}
+
+impl Context {
+  pub fn provider(&self) -> &dyn Provider {
+    self.provider.as_ref()
+  }
+
+  // Run `job` on another thread, its response comes back to the screens with the returned id
+  pub fn request<F>(&mut self, job: F) -> RequestId
+  where
+    F: FnOnce(&dyn Provider) -> std::io::Result<Response> + Send + 'static,
+  {
+    self.next_request += 1;
+    let request = RequestId(self.next_request);
+    let (provider, events, runtime) = (self.provider.clone(), self.events.clone(), self.runtime.clone());
+    std::thread::spawn(move || {
+      let _guard = runtime.as_ref().map(|runtime| runtime.enter());
+      let _ = events.send(Event::Response(request, job(provider.as_ref())));
+    });
+    request
+  }
+
+  // What `key` does in `scope`
+  pub fn action(&self, scope: Scope, key: &KeyEvent) -> Option<Action> {
+    self.keymap.key_action(scope, key)
+  }
+
+  // A spinner frame, for what is loading
+  pub fn spinner(&self) -> char {
+    ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'][self.ticks as usize % 10]
+  }
+}
+
+// A video playing in a corner while the user browses
+struct Mini {
+  player: Box<dyn MiniPlayer>,
+  title: String,
+  // Where it was last drawn, a click there plays it full screen
+  area: Rect,
+}
+
+struct App {
+  // The screens opened, the last one is shown
+  screens: Vec<Box<dyn Screen>>,
+  context: Context,
+  events: EventLoop,
+  // The last message worth showing, and when it was logged
+  message: Option<(LogLevel, String, Instant)>,
+  // Lines of the help scrolled past, while it is shown
+  help: Option<u16>,
+  palette: Option<CommandPalette>,
+  mini: Option<Mini>,
+  // Set until the mini player is played full screen
+  expanding: bool,
+  running: bool,
+}
+
+impl App {
+  fn screen(&mut self) -> &mut Box<dyn Screen> {
+    self.screens.last_mut().expect("The home screen is never closed")
+  }
+
+  fn apply(&mut self, transition: Transition) {
+    match transition {
+      Transition::Stay => {},
+      Transition::Push(screen) => self.screens.push(screen),
+      Transition::Pop if self.screens.len() > 1 => {
+        self.screens.pop();
+      },
+      Transition::Pop => {},
+      Transition::Quit => self.running = false,
+    }
+  }
+
+  fn handle_key(&mut self, key: KeyEvent) {
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+      self.running = false;
+      return;
+    }
+    let scope = self.screen().scope();
+    // The help takes the keys while it is shown, any other closes it
+    if let Some(scroll) = self.help {
+      self.help = match self.context.action(scope, &key) {
+        Some(Action::NextResult) => Some(scroll.saturating_add(1)),
+        Some(Action::PreviousResult) => Some(scroll.saturating_sub(1)),
+        _ => None,
+      };
+      return;
+    }
+    // So does the palette, until a command is picked
+    if let Some(palette) = self.palette.as_mut() {
+      match palette.handle_key(key) {
+        Outcome::Stay => {},
+        Outcome::Close => self.palette = None,
+        Outcome::Run(command) => {
+          self.palette = None;
+          self.run_command(command);
+        },
+      }
+      return;
+    }
+    let mut screen = self.screens.pop().expect("The home screen is never closed");
+    let transition = screen.handle_key(key, &mut self.context);
+    self.screens.push(screen);
+    // The keys a screen leaves go everywhere the same
+    let transition = match (transition, self.context.action(scope, &key)) {
+      (Some(transition), _) => transition,
+      (None, Some(action)) => self.perform_global(action),
+      (None, None) => Transition::Stay,
+    };
+    self.apply(transition);
+  }
+
+  fn handle_mouse(&mut self, mouse: MouseEvent) {
+    // The help and the palette take the wheel while shown, a click closes them
+    if self.help.is_some() || self.palette.is_some() {
+      let down = mouse.kind == MouseEventKind::ScrollDown;
+      match (mouse.kind, self.help, self.palette.as_mut()) {
+        (MouseEventKind::ScrollDown | MouseEventKind::ScrollUp, Some(scroll), _) => {
+          self.help = Some(if down { scroll.saturating_add(1) } else { scroll.saturating_sub(1) });
+        },
+        (MouseEventKind::ScrollDown | MouseEventKind::ScrollUp, None, Some(palette)) => palette.scroll(down),
+        (MouseEventKind::Down(MouseButton::Left), _, _) => {
+          self.help = None;
+          self.palette = None;
+        },
+        _ => {},
+      }
+      return;
+    }
+    let on_mini = self.mini.as_ref().is_some_and(|mini| mini.area.contains(Position::new(mouse.column, mouse.row)));
+    if on_mini && mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+      self.expanding = true;
+      return;
+    }
+    let mut screen = self.screens.pop().expect("The home screen is never closed");
+    let transition = screen.handle_mouse(mouse, &mut self.context);
+    self.screens.push(screen);
+    self.apply(transition.unwrap_or(Transition::Stay));
+  }
+
+  // What the actions a screen leaves do everywhere
+  fn perform_global(&mut self, action: Action) -> Transition {
+    match action {
+      Action::Back => Transition::Pop,
+      Action::Quit => Transition::Quit,
+      Action::Help => {
+        self.help = Some(0);
+        Transition::Stay
+      },
+      Action::Palette => {
+        let scope = self.screen().scope();
+        self.palette = Some(CommandPalette::new(scope, &self.context));
+        Transition::Stay
+      },
+      Action::FullScreen => {
+        self.expanding = self.mini.is_some();
+        Transition::Stay
+      },
+      Action::MiniPlayPause => {
+        if let Some(mini) = self.mini.as_mut() {
+          mini.player.toggle_pause();
+        }
+        Transition::Stay
+      },
+      Action::CloseMiniPlayer => {
+        self.mini = None;
+        Transition::Stay
+      },
+      _ => Transition::Stay,
+    }
+  }
+
+  // Do what was picked in the palette
+  fn run_command(&mut self, command: Command) {
+    let context = &mut self.context;
+    let transition = match command {
+      Command::Action(action) => {
+        let mut screen = self.screens.pop().expect("The home screen is never closed");
+        let transition = screen.perform(action, context);
+        self.screens.push(screen);
+        transition.unwrap_or_else(|| self.perform_global(action))
+      },
+      Command::Search(query) => Transition::Push(Box::new(SearchScreen::with_query(query, context))),
+      Command::Video(id) => Transition::Push(Box::new(VideoScreen::new(id, context))),
+      Command::Channel(id) => Transition::Push(Box::new(ChannelScreen::new(id, context))),
+      Command::Queued(index) => Transition::Push(Box::new(PlayerScreen::queue(context.queue[index..].to_vec()))),
+    };
+    self.apply(transition);
+  }
+
+  fn handle_event(&mut self, event: Event) {
+    match event {
+      Event::Key(key) => self.handle_key(key),
+      Event::Mouse(mouse) => self.handle_mouse(mouse),
+      // The next draw uses the new size
+      Event::Resize(_, _) => {},
+      Event::Response(request, response) => {
+        for screen in self.screens.iter_mut() {
+          screen.handle_response(request, &response, &mut self.context);
+        }
+      },
+      Event::Log(level, message) => self.message = Some((level, message, Instant::now())),
+      Event::Tick => self.context.ticks += 1,
+    }
+  }
+
+  // Play what the shown screen asks for, with the terminal handed over to the player
+  fn play(&mut self, terminal: &mut Tui) -> std::io::Result<()> {
+    if std::mem::take(&mut self.expanding) {
+      return self.expand(terminal);
+    }
+    let Some(playback) = self.screen().pending_playback() else {
+      return Ok(());
+    };
+    // A single video plays at a time
+    self.mini = None;
+    self.events.pause();
+    self.context.library.add_watched(&playback.id, &playback.title);
+    let provider = self.context.provider.clone();
+    let result = terminal::suspend(terminal, || provider.play(&playback.id, playback.start));
+    self.events.resume();
+    match result? {
+      Ok(Some(player)) => {
+        self.mini = Some(Mini { player, title: playback.title, area: Rect::default() });
+        let transition = self.screen().playback_minimized();
+        self.apply(transition);
+      },
+      result => self.screen().playback_ended(result.map(|_| ())),
+    }
+    Ok(())
+  }
+
+  // Play the mini player full screen, it comes back when shrunk again
+  fn expand(&mut self, terminal: &mut Tui) -> std::io::Result<()> {
+    let Some(Mini { player, title, .. }) = self.mini.take() else {
+      return Ok(());
+    };
+    self.events.pause();
+    let result = terminal::suspend(terminal, || player.expand());
+    self.events.resume();
+    match result? {
+      Ok(player) => self.mini = player.map(|player| Mini { player, title, area: Rect::default() }),
+      Err(e) => get_logger().error(format!("Failed to play {}: {}", title, e)),
+    }
+    Ok(())
+  }
+
+  fn draw(&mut self, frame: &mut Frame) {
+    let [header, body, status] = Layout::vertical([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+
+    // Where the user is, e.g. `ytbascii › Search › Results for "lofi"`
+    let theme = theme::current();
+    let mut path = vec![Span::styled(" ytbascii ", theme.inverse().add_modifier(Modifier::BOLD))];
+    for screen in self.screens.iter().skip(1) {
+      path.push(Span::raw(" › ").fg(theme.muted));
+      path.push(Span::raw(screen.title()));
+    }
+    frame.render_widget(Line::from(path), header);
+
+    let screen = self.screens.last_mut().expect("The home screen is never closed");
+    screen.draw(frame, body, &self.context);
+    self.draw_mini(frame, body);
+
+    self.draw_status(frame, status);
+    if let Some(scroll) = self.help {
+      self.draw_help(frame, body, scroll);
+    }
+    if let Some(palette) = self.palette.as_mut() {
+      palette.draw(frame, body);
+    }
+  }
+
+  // The mini player in the bottom right corner, over the screen, until the video is over
+  fn draw_mini(&mut self, frame: &mut Frame, area: Rect) {
+    if self.mini.as_ref().is_some_and(|mini| mini.player.is_over()) {
+      self.mini = None;
+    }
+    let Some(mini) = self.mini.as_mut() else {
+      return;
+    };
+    let theme = theme::current();
+    let cols = MINI_COLS.min(area.width.saturating_sub(2));
+    let rows = (cols * 9 / 32).min(area.height.saturating_sub(2));
+    if rows == 0 {
+      mini.area = Rect::default();
+      return;
+    }
+    let colored = theme.depth != ColorDepth::None;
+    let lines = match mini.player.frame(cols, rows, colored) {
+      Ok(Some(cells)) => cells.into_iter().map(|row| {
+        Line::from(row.into_iter().map(|cell| {
+          let style = match (colored, cell.bg) {
+            (false, _) => Style::new(),
+            (true, Some(bg)) => Style::new().fg(theme.rgb(cell.fg)).bg(theme.rgb(bg)),
+            (true, None) => Style::new().fg(theme.rgb(cell.fg)),
+          };
+          Span::styled(cell.ch.to_string(), style)
+        }).collect::<Vec<Span>>())
+      }).collect(),
+      Ok(None) => vec![Line::from(format!(" {} Loading...", self.context.spinner())).fg(theme.muted)],
+      Err(e) => {
+        get_logger().error(format!("Failed to play {}: {}", mini.title, e));
+        self.mini = None;
+        return;
+      },
+    };
+    let state = if mini.player.is_paused() { "❚❚" } else { "▶" };
+    let position = format::duration(mini.player.position().as_secs() as i64);
+    let time = match mini.player.duration() {
+      Some(duration) => format!(" {} / {} ", position, format::duration(duration.as_secs() as i64)),
+      None => format!(" {} ", position),
+    };
+    let block = Block::bordered()
+      .title(format!(" {} {} ", state, mini.title))
+      .title_bottom(Line::from(time).right_aligned())
+      .border_style(Style::new().fg(theme.accent));
+    mini.area = Rect::new(area.right() - cols - 2, area.bottom() - rows - 2, cols + 2, rows + 2);
+    frame.render_widget(Clear, mini.area);
+    frame.render_widget(Paragraph::new(Text::from(lines)).block(block), mini.area);
+  }
+
+  // The keys of the shown screen, then the global ones, over the screen
+  fn draw_help(&self, frame: &mut Frame, area: Rect, scroll: u16) {
+    let scope = self.screens.last().map_or(Scope::Global, |screen| screen.scope());
+    let sections = self.context.keymap.help(scope);
+    let theme = theme::current();
+    let width = sections.iter().flat_map(|(_, lines)| lines.iter().map(|(keys, _)| keys.chars().count())).max().unwrap_or(0);
+    let mut lines = vec![];
+    for (scope, bindings) in sections.into_iter().filter(|(_, bindings)| !bindings.is_empty()) {
+      if !lines.is_empty() {
+        lines.push(Line::default());
+      }
+      lines.push(Line::from(scope.name()).bold().fg(theme.accent));
+      for (keys, description) in bindings {
+        lines.push(Line::from(vec![Span::raw(format!("{:<width$}  ", keys, width = width)).bold(), Span::raw(description)]));
+      }
+    }
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4).min(area.width);
+    let [_, area, _] = Layout::vertical([Constraint::Fill(1), Constraint::Length(height), Constraint::Fill(1)]).areas(area);
+    let [_, area, _] = Layout::horizontal([Constraint::Fill(1), Constraint::Length(width), Constraint::Fill(1)]).areas(area);
+    let block = Block::bordered().title(" Keys ").border_style(Style::new().fg(theme.accent));
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(Text::from(lines)).block(block).scroll((scroll, 0)), area);
+  }
+
+  // The last message while it is recent, otherwise the keys of the screen
+  fn draw_status(&mut self, frame: &mut Frame, area: Rect) {
+    if self.message.as_ref().is_some_and(|(_, _, at)| at.elapsed() > MESSAGE_DURATION) {
+      self.message = None;
+    }
+    let line = match &self.message {
+      Some((level, message, _)) => {
+        let theme = theme::current();
+        let color = match level {
+          LogLevel::Error => theme.error,
+          LogLevel::Warn => theme.warn,
+          _ => theme.success,
+        };
+        Line::from(vec![Span::raw(format!(" {} ", level.name())).fg(color).bold(), Span::raw(message.clone())])
+      },
+      None => {
+        let mut spans = vec![];
+        let (scope, mut hints) = self.screens.last().map(|screen| (screen.scope(), screen.hints())).unwrap_or((Scope::Global, vec![]));
+        if self.mini.is_some() {
+          hints.push((Action::FullScreen, "full screen"));
+        }
+        hints.push((Action::Help, "keys"));
+        for (action, hint) in hints {
+          // The actions without a key are left out
+          let Some(chord) = self.context.keymap.keys(scope, action).into_iter().next() else {
+            continue;
+          };
+          spans.push(Span::raw(format!(" {} ", chord)).reversed());
+          spans.push(Span::raw(format!(" {}  ", hint)));
+        }
+        Line::from(spans)
+      },
+    };
+    frame.render_widget(line, area);
+  }
+}
+
+// Run the interface until the user quits. The log messages go to the status line meanwhile.
+pub fn run(provider: Arc<dyn Provider>, keymap: Keymap, library: Library) -> std::io::Result<()> {
+  let events = EventLoop::new();
+  let log = events.sender();
+  logger::set_sink(Some(Box::new(move |module, level, message| {
+    // The steps of the requests would hide what matters
+    if matches!(level, LogLevel::Success | LogLevel::Warn | LogLevel::Error) {
+      let _ = log.send(Event::Log(level, format!("{}: {}", module, message)));
+    }
+  })));
+
+  let mut terminal = terminal::init(keymap.mouse)?;
+  let mut context = Context {
+    provider,
+    events: events.sender(),
+    next_request: 0,
+    runtime: tokio::runtime::Handle::try_current().ok(),
+    ticks: 0,
+    queue: vec![],
+    keymap,
+    library,
+  };
+  let home = Box::new(HomeScreen::new(&mut context));
+  let mut app = App { screens: vec![home], context, events, message: None, help: None, palette: None, mini: None, expanding: false, running: true };
+
+  let result = (|| {
+    while app.running {
+      terminal.draw(|frame| app.draw(frame))?;
+      app.play(&mut terminal)?;
+      // The mini player is drawn again between the events
+      let event = match app.mini.is_some() {
+        true => match app.events.next_within(MINI_FRAME) {
+          Ok(event) => event,
+          Err(RecvTimeoutError::Timeout) => continue,
+          Err(RecvTimeoutError::Disconnected) => break,
+        },
+        false => match app.events.next() {
+          Some(event) => event,
+          None => break,
+        },
+      };
+      app.handle_event(event);
+      // Handle what piled up before drawing again
+      while let Ok(event) = app.events.try_next() {
+        app.handle_event(event);
+      }
+    }
+    Ok(())
+  })();
+
+  terminal::restore()?;
+  logger::set_sink(None);
+  get_logger().info("Closed the interface".to_string());
+  result
+}
@@ -0,0 +1,146 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use api::models::{ChannelObject, ChannelPlaylistsObject, ChannelVideosObject, CommentsObject, CommunityObject, PlaylistObject, SearchItem, VideoObject};
+use logger::LogLevel;
+use ratatui::crossterm::event::{self, KeyEvent, KeyEventKind, MouseEvent, MouseEventKind};
+use youtube::models::video::VideoInfo;
+
+use crate::get_logger;
+
+// How often the screens are redrawn without any event, e.g. for the loading spinners
+static TICK_RATE: Duration = Duration::from_millis(250);
+
+// How long the input thread waits for a key before checking for a pause or a tick
+static INPUT_POLL: Duration = Duration::from_millis(50);
+
+// Identifies a request, so its response reaches the screen that made it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RequestId(pub u64);
+
+// The data a request fetched
+#[derive(Debug)]
+pub enum Response {
+  Search(Vec<SearchItem>),
+  Suggestions(Vec<String>),
+  Video(Box<VideoObject>),
+  Info(VideoInfo),
+  Comments(CommentsObject),
+  Channel(Box<ChannelObject>),
+  ChannelVideos(ChannelVideosObject),
+  ChannelPlaylists(ChannelPlaylistsObject),
+  Community(CommunityObject),
+  Playlist(PlaylistObject),
+  // A request that returns nothing went through
+  Done,
+}
+
+// Everything the event loop reacts to
+#[derive(Debug)]
+pub enum Event {
+  Key(KeyEvent),
+  // Clicks, drags and the wheel, when the mouse is captured
+  Mouse(MouseEvent),
+  Resize(u16, u16),
+  // A request made by a screen is over
+  Response(RequestId, std::io::Result<Response>),
+  // A message logged by any module, shown on the status line
+  Log(LogLevel, String),
+  Tick,
+}
+
+// Merges the terminal input, the responses and the log messages into one channel
+pub struct EventLoop {
+  sender: Sender<Event>,
+  receiver: Receiver<Event>,
+  // Set while another program reads the terminal, e.g. the player
+  paused: Arc<AtomicBool>,
+}
+
+impl EventLoop {
+  pub fn new() -> EventLoop {
+    let (sender, receiver) = mpsc::channel();
+    let paused = Arc::new(AtomicBool::new(false));
+
+    let (input, input_paused) = (sender.clone(), paused.clone());
+    std::thread::spawn(move || {
+      let mut last_tick = Instant::now();
+      loop {
+        if input_paused.load(Ordering::SeqCst) {
+          std::thread::sleep(INPUT_POLL);
+          continue;
+        }
+        let event = match event::poll(INPUT_POLL) {
+          Ok(true) => match event::read() {
+            Ok(event::Event::Key(key)) if key.kind == KeyEventKind::Press => Some(Event::Key(key)),
+            // The moves without a button held would flood the loop
+            Ok(event::Event::Mouse(mouse)) if mouse.kind != MouseEventKind::Moved => Some(Event::Mouse(mouse)),
+            Ok(event::Event::Resize(cols, rows)) => Some(Event::Resize(cols, rows)),
+            Ok(_) => None,
+            Err(e) => {
+              get_logger().error(format!("Failed to read the terminal: {}", e));
+              return;
+            },
+          },
+          Ok(false) => None,
+          Err(e) => {
+            get_logger().error(format!("Failed to read the terminal: {}", e));
+            return;
+          },
+        };
+        if let Some(event) = event {
+          if input.send(event).is_err() {
+            return;
+          }
+        }
+        if last_tick.elapsed() >= TICK_RATE {
+          last_tick = Instant::now();
+          if input.send(Event::Tick).is_err() {
+            return;
+          }
+        }
+      }
+    });
+
+    EventLoop { sender, receiver, paused }
+  }
+
+  // Where the other threads send their events
+  pub fn sender(&self) -> Sender<Event> {
+    self.sender.clone()
+  }
+
+  // Wait for the next event
+  pub fn next(&self) -> Option<Event> {
+    self.receiver.recv().ok()
+  }
+
+  // Wait for the next event at most `timeout`, e.g. to draw a video meanwhile
+  pub fn next_within(&self, timeout: Duration) -> Result<Event, RecvTimeoutError> {
+    self.receiver.recv_timeout(timeout)
+  }
+
+  // The next event if there is one already
+  pub fn try_next(&self) -> Result<Event, mpsc::TryRecvError> {
+    self.receiver.try_recv()
+  }
+
+  // Stop reading the terminal, so another program gets the keys
+  pub fn pause(&self) {
+    self.paused.store(true, Ordering::SeqCst);
+    // Let a read in progress finish
+    std::thread::sleep(INPUT_POLL * 2);
+  }
+
+  pub fn resume(&self) {
+    self.paused.store(false, Ordering::SeqCst);
+  }
+}
+
+impl Default for EventLoop {
+  fn default() -> Self {
+    EventLoop::new()
+  }
+}
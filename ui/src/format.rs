@@ -0,0 +1,60 @@
+// How the screens write durations, counts and dates, the way YouTube does
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// `m:ss`, or `h:mm:ss` past an hour
+pub fn duration(seconds: i64) -> String {
+  let seconds = seconds.max(0);
+  if seconds >= 3600 {
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+  } else {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+  }
+}
+
+// `950`, `12K` or `1.2M`
+pub fn count(count: i64) -> String {
+  let count = count.max(0) as f64;
+  let (value, suffix) = match count {
+    count if count >= 1e9 => (count / 1e9, "B"),
+    count if count >= 1e6 => (count / 1e6, "M"),
+    count if count >= 1e3 => (count / 1e3, "K"),
+    count => return format!("{}", count),
+  };
+  // One decimal under 10, dropped when it is 0
+  match value < 10.0 {
+    true => format!("{}{}", (value * 10.0).floor() / 10.0, suffix),
+    false => format!("{}{}", value.floor(), suffix),
+  }
+}
+
+// `2009-10-25`, the UTC day of a unix timestamp
+pub fn date(timestamp: i64) -> String {
+  // Days to a civil date, from Howard Hinnant's algorithm
+  let days = timestamp.div_euclid(86400) + 719468;
+  let era = days.div_euclid(146097);
+  let day_of_era = days - era * 146097;
+  let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let month_index = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+  let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+  let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+  format!("{}-{:02}-{:02}", year, month, day)
+}
+
+// `3 days ago`, how long before now a unix timestamp is
+pub fn ago(timestamp: i64) -> String {
+  let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_secs() as i64);
+  let seconds = (now - timestamp).max(0);
+  let (value, unit) = match seconds {
+    seconds if seconds >= 365 * 86400 => (seconds / (365 * 86400), "year"),
+    seconds if seconds >= 30 * 86400 => (seconds / (30 * 86400), "month"),
+    seconds if seconds >= 7 * 86400 => (seconds / (7 * 86400), "week"),
+    seconds if seconds >= 86400 => (seconds / 86400, "day"),
+    seconds if seconds >= 3600 => (seconds / 3600, "hour"),
+    seconds if seconds >= 60 => (seconds / 60, "minute"),
+    seconds => (seconds, "second"),
+  };
+  format!("{} {}{} ago", value, unit, if value == 1 { "" } else { "s" })
+}
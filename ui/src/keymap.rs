@@ -0,0 +1,584 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::get_logger;
+
+// A key, whatever terminal library read it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+  Char(char),
+  Enter,
+  Esc,
+  Backspace,
+  Tab,
+  BackTab,
+  Up,
+  Down,
+  Left,
+  Right,
+  Home,
+  End,
+  PageUp,
+  PageDown,
+  Delete,
+  Insert,
+  F(u8),
+}
+
+// A key with the modifiers held with it. Shift is part of the key, e.g. `G` or `BackTab`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chord {
+  pub key: Key,
+  pub ctrl: bool,
+  pub alt: bool,
+}
+
+impl Chord {
+  pub fn new(key: Key) -> Chord {
+    Chord { key, ctrl: false, alt: false }
+  }
+
+  pub fn ctrl(key: Key) -> Chord {
+    Chord { ctrl: true, ..Chord::new(key) }
+  }
+
+  pub fn alt(key: Key) -> Chord {
+    Chord { alt: true, ..Chord::new(key) }
+  }
+
+  // The chord of a key read by crossterm, none for the keys no action can have
+  pub fn from_event(event: &KeyEvent) -> Option<Chord> {
+    let key = match event.code {
+      KeyCode::Char(c) => Key::Char(c),
+      KeyCode::Enter => Key::Enter,
+      KeyCode::Esc => Key::Esc,
+      KeyCode::Backspace => Key::Backspace,
+      KeyCode::Tab => Key::Tab,
+      KeyCode::BackTab => Key::BackTab,
+      KeyCode::Up => Key::Up,
+      KeyCode::Down => Key::Down,
+      KeyCode::Left => Key::Left,
+      KeyCode::Right => Key::Right,
+      KeyCode::Home => Key::Home,
+      KeyCode::End => Key::End,
+      KeyCode::PageUp => Key::PageUp,
+      KeyCode::PageDown => Key::PageDown,
+      KeyCode::Delete => Key::Delete,
+      KeyCode::Insert => Key::Insert,
+      KeyCode::F(n) => Key::F(n),
+      _ => return None,
+    };
+    Some(Chord { key, ctrl: event.modifiers.contains(KeyModifiers::CONTROL), alt: event.modifiers.contains(KeyModifiers::ALT) })
+  }
+}
+
+// `ctrl-p`, `shift-tab`, `space`, `G` or `f5`, the way the keymap file writes them
+impl FromStr for Chord {
+  type Err = String;
+
+  fn from_str(text: &str) -> Result<Chord, String> {
+    let (mut ctrl, mut alt, mut shift) = (false, false, false);
+    let mut rest = text;
+    // `ctrl--` is control and minus
+    while rest.len() > 1 {
+      let lower = rest.to_lowercase();
+      if lower.starts_with("ctrl-") {
+        ctrl = true;
+      } else if lower.starts_with("alt-") {
+        alt = true;
+      } else if lower.starts_with("shift-") {
+        shift = true;
+      } else {
+        break;
+      }
+      rest = &rest[rest.find('-').unwrap_or(0) + 1..];
+    }
+    let mut chars = rest.chars();
+    let key = match (chars.next(), chars.next()) {
+      (Some(c), None) => Key::Char(c),
+      _ => match rest.to_lowercase().as_str() {
+        "space" => Key::Char(' '),
+        "enter" | "return" => Key::Enter,
+        "esc" | "escape" => Key::Esc,
+        "backspace" => Key::Backspace,
+        "tab" => Key::Tab,
+        "backtab" => Key::BackTab,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "delete" | "del" => Key::Delete,
+        "insert" => Key::Insert,
+        name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+          Some(n @ 1..=12) => Key::F(n),
+          _ => return Err(format!("Unknown key `{}`", text)),
+        },
+      },
+    };
+    let key = match (key, shift) {
+      (Key::Tab, true) => Key::BackTab,
+      (Key::Char(c), true) => Key::Char(c.to_ascii_uppercase()),
+      (key, _) => key,
+    };
+    Ok(Chord { key, ctrl, alt })
+  }
+}
+
+// How the hints and the help write a chord
+impl fmt::Display for Chord {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if self.ctrl {
+      write!(f, "ctrl-")?;
+    }
+    if self.alt {
+      write!(f, "alt-")?;
+    }
+    match self.key {
+      Key::Char(' ') => write!(f, "space"),
+      Key::Char(c) => write!(f, "{}", c),
+      Key::Enter => write!(f, "enter"),
+      Key::Esc => write!(f, "esc"),
+      Key::Backspace => write!(f, "backspace"),
+      Key::Tab => write!(f, "tab"),
+      Key::BackTab => write!(f, "shift-tab"),
+      Key::Up => write!(f, "↑"),
+      Key::Down => write!(f, "↓"),
+      Key::Left => write!(f, "←"),
+      Key::Right => write!(f, "→"),
+      Key::Home => write!(f, "home"),
+      Key::End => write!(f, "end"),
+      Key::PageUp => write!(f, "pageup"),
+      Key::PageDown => write!(f, "pagedown"),
+      Key::Delete => write!(f, "delete"),
+      Key::Insert => write!(f, "insert"),
+      Key::F(n) => write!(f, "f{}", n),
+    }
+  }
+}
+
+// Where bindings apply. A screen looks its keys up in its scope, then in the parents of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+  Global,
+  Home,
+  Search,
+  // The filters of the search screen
+  Filters,
+  Video,
+  // The comments tab of the video screen
+  Comments,
+  Channel,
+  Queue,
+  // The full screen player, on its own
+  Player,
+}
+
+pub static SCOPES: [Scope; 9] = [Scope::Global, Scope::Home, Scope::Search, Scope::Filters, Scope::Video, Scope::Comments, Scope::Channel, Scope::Queue, Scope::Player];
+
+impl Scope {
+  // As the keymap file names it
+  pub fn name(&self) -> &'static str {
+    match self {
+      Scope::Global => "global",
+      Scope::Home => "home",
+      Scope::Search => "search",
+      Scope::Filters => "filters",
+      Scope::Video => "video",
+      Scope::Comments => "comments",
+      Scope::Channel => "channel",
+      Scope::Queue => "queue",
+      Scope::Player => "player",
+    }
+  }
+
+  // The scopes looked in after this one, closest first
+  pub fn parents(&self) -> &'static [Scope] {
+    match self {
+      Scope::Global | Scope::Player => &[],
+      Scope::Filters => &[Scope::Search, Scope::Global],
+      Scope::Comments => &[Scope::Video, Scope::Global],
+      _ => &[Scope::Global],
+    }
+  }
+}
+
+// What a key can do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+  Quit,
+  Back,
+  Help,
+  Palette,
+  FullScreen,
+  MiniPlayPause,
+  CloseMiniPlayer,
+  NextResult,
+  PreviousResult,
+  FirstResult,
+  LastResult,
+  NextPage,
+  PreviousPage,
+  Open,
+  NextTab,
+  PreviousTab,
+  Search,
+  FocusQuery,
+  FocusFilters,
+  PreviousChoice,
+  NextChoice,
+  Play,
+  Download,
+  AddToQueue,
+  OpenComments,
+  OpenChannel,
+  NextTimestamp,
+  PreviousTimestamp,
+  Sort,
+  Subscribe,
+  Remove,
+  PlayPause,
+  SeekForward,
+  SeekBackward,
+  SeekForwardLong,
+  SeekBackwardLong,
+  SpeedUp,
+  SpeedDown,
+  VolumeUp,
+  VolumeDown,
+  ToggleMute,
+  ToggleCaptions,
+  NextCaptions,
+  PreviousChapter,
+  NextChapter,
+  Minimize,
+}
+
+// An action in a scope, with the keys of each preset
+#[derive(Debug)]
+pub struct ActionInfo {
+  pub action: Action,
+  pub scope: Scope,
+  // As the keymap file names it
+  pub name: &'static str,
+  pub description: &'static str,
+  keys: &'static [&'static str],
+  // The vim preset uses the default keys when not set
+  vim: Option<&'static [&'static str]>,
+}
+
+const fn action(scope: Scope, action: Action, name: &'static str, description: &'static str, keys: &'static [&'static str], vim: Option<&'static [&'static str]>) -> ActionInfo {
+  ActionInfo { action, scope, name, description, keys, vim }
+}
+
+// Every action with its default keys. The help lists them in this order.
+pub static ACTIONS: &[ActionInfo] = &[
+  action(Scope::Global, Action::Quit, "quit", "Quit", &["q"], None),
+  action(Scope::Global, Action::Back, "back", "Go back", &["esc", "backspace"], None),
+  action(Scope::Global, Action::Help, "help", "Show the keys", &["?"], None),
+  action(Scope::Global, Action::Palette, "palette", "Find a command, search, video or channel", &[":", "ctrl-p"], None),
+  action(Scope::Global, Action::FullScreen, "full_screen", "Play the mini player full screen", &["F"], None),
+  action(Scope::Global, Action::MiniPlayPause, "mini_play_pause", "Pause or resume the mini player", &["P"], None),
+  action(Scope::Global, Action::CloseMiniPlayer, "close_mini_player", "Stop the mini player", &["X"], None),
+  action(Scope::Global, Action::NextResult, "next_result", "Select the next entry", &["down", "j"], None),
+  action(Scope::Global, Action::PreviousResult, "previous_result", "Select the previous entry", &["up", "k"], None),
+  action(Scope::Global, Action::FirstResult, "first_result", "Select the first entry", &["home"], Some(&["g", "home"])),
+  action(Scope::Global, Action::LastResult, "last_result", "Select the last entry", &["end"], Some(&["G", "end"])),
+  action(Scope::Global, Action::NextPage, "next_page", "Move a page down", &["pagedown"], Some(&["ctrl-d", "pagedown"])),
+  action(Scope::Global, Action::PreviousPage, "previous_page", "Move a page up", &["pageup"], Some(&["ctrl-u", "pageup"])),
+  action(Scope::Global, Action::Open, "open", "Open the selected entry", &["enter"], None),
+  action(Scope::Global, Action::NextTab, "next_tab", "Show the next tab or pane", &["tab"], None),
+  action(Scope::Global, Action::PreviousTab, "previous_tab", "Show the previous tab or pane", &["shift-tab"], None),
+  action(Scope::Home, Action::Search, "search", "Search YouTube", &["/"], None),
+  action(Scope::Search, Action::FocusQuery, "focus_query", "Edit the query", &["/"], None),
+  action(Scope::Search, Action::FocusFilters, "focus_filters", "Pick the filters", &["f"], None),
+  action(Scope::Filters, Action::PreviousChoice, "previous_choice", "Pick the previous choice", &["left", "h"], None),
+  action(Scope::Filters, Action::NextChoice, "next_choice", "Pick the next choice", &["right", "l", "space"], None),
+  action(Scope::Video, Action::Play, "play", "Play the video", &["p"], None),
+  action(Scope::Video, Action::Download, "download", "Download the video", &["d"], None),
+  action(Scope::Video, Action::AddToQueue, "add_to_queue", "Add the video to the queue", &["a"], None),
+  action(Scope::Video, Action::OpenComments, "open_comments", "Show the comments", &["C"], None),
+  action(Scope::Video, Action::OpenChannel, "open_channel", "Open the channel", &["c"], None),
+  action(Scope::Video, Action::NextTimestamp, "next_timestamp", "Select the next timestamp", &["t"], None),
+  action(Scope::Video, Action::PreviousTimestamp, "previous_timestamp", "Select the previous timestamp", &["T"], None),
+  action(Scope::Comments, Action::Sort, "sort", "Sort by top or new", &["s"], None),
+  action(Scope::Channel, Action::NextTab, "next_tab", "Show the next tab", &["right", "l"], None),
+  action(Scope::Channel, Action::PreviousTab, "previous_tab", "Show the previous tab", &["left", "h"], None),
+  action(Scope::Channel, Action::Sort, "sort", "Sort in the next order", &["s"], None),
+  action(Scope::Channel, Action::Subscribe, "subscribe", "Subscribe or unsubscribe", &["S"], None),
+  action(Scope::Queue, Action::Remove, "remove", "Remove the selected video", &["d", "delete"], None),
+  action(Scope::Player, Action::PlayPause, "play_pause", "Pause or resume", &["space", "p"], None),
+  action(Scope::Player, Action::SeekBackward, "seek_backward", "Go back 5 seconds", &["left"], Some(&["h", "left"])),
+  action(Scope::Player, Action::SeekForward, "seek_forward", "Go forward 5 seconds", &["right"], Some(&["l", "right"])),
+  action(Scope::Player, Action::SeekBackwardLong, "seek_backward_long", "Go back a minute", &["down"], Some(&["j", "down"])),
+  action(Scope::Player, Action::SeekForwardLong, "seek_forward_long", "Go forward a minute", &["up"], Some(&["k", "up"])),
+  action(Scope::Player, Action::SpeedDown, "speed_down", "Play slower", &["<"], None),
+  action(Scope::Player, Action::SpeedUp, "speed_up", "Play faster", &[">"], None),
+  action(Scope::Player, Action::VolumeDown, "volume_down", "Turn the volume down", &["-"], None),
+  action(Scope::Player, Action::VolumeUp, "volume_up", "Turn the volume up", &["+", "="], None),
+  action(Scope::Player, Action::ToggleMute, "toggle_mute", "Mute or unmute", &["m"], None),
+  action(Scope::Player, Action::ToggleCaptions, "toggle_captions", "Show or hide the captions", &["c"], None),
+  action(Scope::Player, Action::NextCaptions, "next_captions", "Switch to the next captions", &["l"], Some(&["L"])),
+  action(Scope::Player, Action::PreviousChapter, "previous_chapter", "Go to the previous chapter", &["["], None),
+  action(Scope::Player, Action::NextChapter, "next_chapter", "Go to the next chapter", &["]"], None),
+  action(Scope::Player, Action::Minimize, "minimize", "Keep playing in a corner of the interface", &["i"], None),
+  action(Scope::Player, Action::Quit, "quit", "Stop the playback", &["q", "esc", "ctrl-c"], None),
+];
+
+// The keys everything starts from, before the keymap file changes them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Preset {
+  #[default]
+  Default,
+  // Adds `g`, `G`, `ctrl-d` and `ctrl-u` to the lists, and `hjkl` to the player
+  Vim,
+}
+
+impl FromStr for Preset {
+  type Err = String;
+
+  fn from_str(name: &str) -> Result<Preset, String> {
+    match name {
+      "default" => Ok(Preset::Default),
+      "vim" => Ok(Preset::Vim),
+      _ => Err(format!("Unknown preset `{}`, expected `default` or `vim`", name)),
+    }
+  }
+}
+
+// The keymap file, e.g. `{ "preset": "vim", "bindings": { "video": { "play": ["p", "enter"] } } }`.
+// The keys given for an action replace the ones of the preset.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct KeymapFile {
+  preset: Option<String>,
+  // Whether the interface and the player take the clicks and the wheel, true when not set
+  mouse: Option<bool>,
+  // Keys by action, by scope
+  bindings: HashMap<String, HashMap<String, Vec<String>>>,
+}
+
+// Which action each key has, in each scope
+#[derive(Debug, Clone)]
+pub struct Keymap {
+  // In the order of `ACTIONS`
+  bindings: Vec<(&'static ActionInfo, Vec<Chord>)>,
+  // Capture the mouse, leaving the selection of text to the terminal when not set
+  pub mouse: bool,
+}
+
+impl Default for Keymap {
+  fn default() -> Self {
+    Keymap::preset(Preset::Default)
+  }
+}
+
+impl Keymap {
+  pub fn preset(preset: Preset) -> Keymap {
+    let bindings = ACTIONS.iter().map(|info| {
+      let keys = match preset {
+        Preset::Vim => info.vim.unwrap_or(info.keys),
+        Preset::Default => info.keys,
+      };
+      (info, keys.iter().map(|key| key.parse().expect("The preset keys are valid")).collect())
+    }).collect();
+    Keymap { bindings, mouse: true }
+  }
+
+  // The keymap of a keymap file, and what is wrong in it
+  pub fn parse(text: &str) -> Result<(Keymap, Vec<String>), String> {
+    let file: KeymapFile = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let preset = file.preset.as_deref().map(Preset::from_str).transpose()?.unwrap_or_default();
+    let mut keymap = Keymap::preset(preset);
+    keymap.mouse = file.mouse.unwrap_or(true);
+    let mut problems = vec![];
+    for (scope_name, actions) in &file.bindings {
+      let Some(scope) = SCOPES.iter().find(|scope| scope.name() == scope_name) else {
+        problems.push(format!("Unknown scope `{}`", scope_name));
+        continue;
+      };
+      for (name, keys) in actions {
+        let Some((_, chords)) = keymap.bindings.iter_mut().find(|(info, _)| info.scope == *scope && info.name == name) else {
+          problems.push(format!("No action `{}` in {}", name, scope_name));
+          continue;
+        };
+        chords.clear();
+        for key in keys {
+          match key.parse() {
+            Ok(chord) => chords.push(chord),
+            Err(e) => problems.push(format!("{} for {} in {}", e, name, scope_name)),
+          }
+        }
+      }
+    }
+    problems.extend(keymap.conflicts());
+    Ok((keymap, problems))
+  }
+
+  // The keymap of the file at `path`, the default one when there is none or it is broken
+  pub fn load(path: &str) -> Keymap {
+    let text = match std::fs::read_to_string(path) {
+      Ok(text) => text,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Keymap::default(),
+      Err(e) => {
+        get_logger().error(format!("Failed to read the keymap {}: {}", path, e));
+        return Keymap::default();
+      },
+    };
+    match Keymap::parse(&text) {
+      Ok((keymap, problems)) => {
+        get_logger().info(format!("Loaded the keymap {}", path));
+        for problem in problems {
+          get_logger().warn(format!("Keymap {}: {}", path, problem));
+        }
+        keymap
+      },
+      Err(e) => {
+        get_logger().error(format!("Failed to parse the keymap {}, using the default keys: {}", path, e));
+        Keymap::default()
+      },
+    }
+  }
+
+  // The chords bound in `scope` alone
+  fn chords(&self, scope: Scope) -> impl Iterator<Item = (Chord, Action)> + '_ {
+    self.bindings.iter()
+      .filter(move |(info, _)| info.scope == scope)
+      .flat_map(|(info, chords)| chords.iter().map(|chord| (*chord, info.action)))
+  }
+
+  // What `chord` does in `scope`, or else in its parents
+  pub fn action(&self, scope: Scope, chord: Chord) -> Option<Action> {
+    std::iter::once(scope).chain(scope.parents().iter().copied())
+      .find_map(|scope| self.chords(scope).find(|(other, _)| *other == chord).map(|(_, action)| action))
+  }
+
+  // What a key read by crossterm does in `scope`
+  pub fn key_action(&self, scope: Scope, key: &KeyEvent) -> Option<Action> {
+    self.action(scope, Chord::from_event(key)?)
+  }
+
+  // The chords that do `action` in `scope`, without the ones a closer scope takes over
+  pub fn keys(&self, scope: Scope, action: Action) -> Vec<Chord> {
+    std::iter::once(scope).chain(scope.parents().iter().copied())
+      .flat_map(|scope| self.chords(scope).filter(|(_, other)| *other == action).map(|(chord, _)| chord).collect::<Vec<_>>())
+      .filter(|chord| self.action(scope, *chord) == Some(action))
+      .fold(vec![], |mut chords, chord| {
+        if !chords.contains(&chord) {
+          chords.push(chord);
+        }
+        chords
+      })
+  }
+
+  // The bound actions of `scope` and its parents, closest scope first, for the help
+  pub fn help(&self, scope: Scope) -> Vec<(Scope, Vec<(String, &'static str)>)> {
+    std::iter::once(scope).chain(scope.parents().iter().copied()).map(|shown| {
+      let lines = self.bindings.iter()
+        .filter(|(info, _)| info.scope == shown)
+        .filter_map(|(info, chords)| {
+          let reachable: Vec<String> = chords.iter().filter(|chord| self.action(scope, **chord) == Some(info.action)).map(Chord::to_string).collect();
+          (!reachable.is_empty()).then(|| (reachable.join(" "), info.description))
+        })
+        .collect();
+      (shown, lines)
+    }).collect()
+  }
+
+  // The keys bound to two actions of a scope, or to another action than the one they have
+  // in a parent scope
+  pub fn conflicts(&self) -> Vec<String> {
+    let mut conflicts = vec![];
+    for scope in SCOPES {
+      let chords: Vec<(Chord, Action)> = self.chords(scope).collect();
+      for (index, (chord, action)) in chords.iter().enumerate() {
+        if let Some((_, other)) = chords[..index].iter().find(|(other, other_action)| other == chord && other_action != action) {
+          conflicts.push(format!("`{}` is bound to both {} and {} in {}, it does {}", chord, name(scope, *other), name(scope, *action), scope.name(), name(scope, *other)));
+        }
+        for parent in scope.parents() {
+          if let Some((_, other)) = self.chords(*parent).find(|(other, other_action)| other == chord && other_action != action) {
+            conflicts.push(format!("`{}` does {} in {} rather than {} from {}", chord, name(scope, *action), scope.name(), name(*parent, other), parent.name()));
+          }
+        }
+      }
+    }
+    conflicts
+  }
+}
+
+// The name of `action` in the keymap file
+pub fn name(scope: Scope, action: Action) -> &'static str {
+  ACTIONS.iter()
+    .find(|info| info.action == action && (info.scope == scope || scope.parents().contains(&info.scope)))
+    .or_else(|| ACTIONS.iter().find(|info| info.action == action))
+    .map_or("", |info| info.name)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_chords() {
+    assert_eq!("q".parse(), Ok(Chord::new(Key::Char('q'))));
+    assert_eq!("ctrl-p".parse(), Ok(Chord::ctrl(Key::Char('p'))));
+    assert_eq!("ctrl--".parse(), Ok(Chord::ctrl(Key::Char('-'))));
+    assert_eq!("Alt-Enter".parse(), Ok(Chord::alt(Key::Enter)));
+    assert_eq!("shift-tab".parse(), Ok(Chord::new(Key::BackTab)));
+    assert_eq!("shift-g".parse(), Ok(Chord::new(Key::Char('G'))));
+    assert_eq!("space".parse(), Ok(Chord::new(Key::Char(' '))));
+    assert_eq!("f5".parse(), Ok(Chord::new(Key::F(5))));
+    assert!("f13".parse::<Chord>().is_err());
+    assert!("hyper-x".parse::<Chord>().is_err());
+  }
+
+  #[test]
+  fn writes_chords_back() {
+    for text in ["ctrl-p", "alt-enter", "shift-tab", "space", "G", "f5"] {
+      assert_eq!(text.parse::<Chord>().unwrap().to_string(), text);
+    }
+  }
+
+  #[test]
+  fn presets_have_no_conflicts() {
+    assert_eq!(Keymap::preset(Preset::Default).conflicts(), Vec::<String>::new());
+    assert_eq!(Keymap::preset(Preset::Vim).conflicts(), Vec::<String>::new());
+  }
+
+  #[test]
+  fn looks_keys_up_in_the_parent_scopes() {
+    let keymap = Keymap::preset(Preset::Vim);
+    assert_eq!(keymap.action(Scope::Comments, Chord::new(Key::Char('G'))), Some(Action::LastResult));
+    assert_eq!(keymap.action(Scope::Video, Chord::new(Key::Char('p'))), Some(Action::Play));
+    assert_eq!(keymap.action(Scope::Home, Chord::new(Key::Char('p'))), None);
+    assert_eq!(keymap.action(Scope::Player, Chord::new(Key::Char('?'))), None);
+  }
+
+  #[test]
+  fn replaces_the_keys_of_an_action() {
+    let (keymap, problems) = Keymap::parse(r#"{"mouse": false, "bindings": {"video": {"play": ["enter", "P"]}}}"#).unwrap();
+    assert!(!keymap.mouse);
+    assert_eq!(keymap.keys(Scope::Video, Action::Play), vec![Chord::new(Key::Enter), Chord::new(Key::Char('P'))]);
+    assert_eq!(keymap.action(Scope::Video, Chord::new(Key::Char('p'))), None);
+    // Both keys do something else in the global scope
+    assert_eq!(problems.len(), 2);
+    assert!(problems[0].starts_with("`enter` does play in video rather than open"));
+  }
+
+  #[test]
+  fn reports_what_is_wrong_in_the_file() {
+    let text = r#"{"bindings": {"nowhere": {"quit": ["q"]}, "global": {"fly": ["f"], "quit": ["hyper-q", "Q"]}}}"#;
+    let (keymap, problems) = Keymap::parse(text).unwrap();
+    assert_eq!(problems.len(), 3);
+    assert_eq!(keymap.keys(Scope::Global, Action::Quit), vec![Chord::new(Key::Char('Q'))]);
+    assert!(Keymap::parse(r#"{"preset": "emacs"}"#).is_err());
+    assert!(Keymap::parse("{").is_err());
+  }
+}
@@ -0,0 +1,32 @@
+pub mod app;
+pub mod event;
+pub mod format;
+pub mod keymap;
+pub mod library;
+pub mod palette;
+pub mod provider;
+pub mod screens;
+pub mod terminal;
+pub mod text;
+pub mod theme;
+
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+
+use logger::{create_logger, ModuleType, Logger};
+
+pub use app::run;
+
+// Define a lazy_static global instance of the UiLogger
+lazy_static! {
+  static ref UI_LOGGER: Mutex<Box<dyn Logger>> = Mutex::new(create_logger(ModuleType::Ui));
+}
+
+// Function to access the global UiLogger instance
+pub fn get_logger() -> std::sync::MutexGuard<'static, Box<dyn Logger>> {
+  UI_LOGGER.lock().expect("Failed to lock UiLogger")
+}
+
+pub fn init_module() {
+  get_logger().info("Initializing UI module".to_string());
+}
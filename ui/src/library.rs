@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::get_logger;
+
+// How many searches and watched videos are kept, the oldest are dropped
+static MAX_SEARCHES: usize = 50;
+static MAX_HISTORY: usize = 200;
+
+// A video played from the interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Watched {
+  pub id: String,
+  pub title: String,
+}
+
+// A channel followed from its screen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subscription {
+  pub id: String,
+  pub name: String,
+}
+
+// What the interface keeps between runs, the latest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Library {
+  // Where it is saved after each change, nowhere when not set
+  #[serde(skip)]
+  path: Option<String>,
+  pub searches: Vec<String>,
+  pub history: Vec<Watched>,
+  pub subscriptions: Vec<Subscription>,
+}
+
+impl Library {
+  // The library of the file at `path`, an empty one when there is none or it is broken
+  pub fn load(path: &str) -> Library {
+    let library = match std::fs::read_to_string(path) {
+      Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+        get_logger().error(format!("Failed to parse the library {}, starting an empty one: {}", path, e));
+        Library::default()
+      }),
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => Library::default(),
+      Err(e) => {
+        get_logger().error(format!("Failed to read the library {}: {}", path, e));
+        Library::default()
+      },
+    };
+    Library { path: Some(path.to_string()), ..library }
+  }
+
+  fn save(&self) {
+    let Some(path) = &self.path else {
+      return;
+    };
+    let result = Path::new(path).parent().map_or(Ok(()), std::fs::create_dir_all)
+      .and_then(|_| std::fs::write(path, serde_json::to_string_pretty(self).map_err(std::io::Error::other)?));
+    if let Err(e) = result {
+      get_logger().error(format!("Failed to save the library {}: {}", path, e));
+    }
+  }
+
+  pub fn add_search(&mut self, query: &str) {
+    self.searches.retain(|search| search != query);
+    self.searches.insert(0, query.to_string());
+    self.searches.truncate(MAX_SEARCHES);
+    self.save();
+  }
+
+  pub fn add_watched(&mut self, id: &str, title: &str) {
+    self.history.retain(|watched| watched.id != id);
+    self.history.insert(0, Watched { id: id.to_string(), title: title.to_string() });
+    self.history.truncate(MAX_HISTORY);
+    self.save();
+  }
+
+  pub fn is_subscribed(&self, id: &str) -> bool {
+    self.subscriptions.iter().any(|subscription| subscription.id == id)
+  }
+
+  // Subscribe to the channel, or unsubscribe when already subscribed. True when subscribed.
+  pub fn toggle_subscription(&mut self, id: &str, name: &str) -> bool {
+    let subscribed = !self.is_subscribed(id);
+    match subscribed {
+      true => self.subscriptions.insert(0, Subscription { id: id.to_string(), name: name.to_string() }),
+      false => self.subscriptions.retain(|subscription| subscription.id != id),
+    }
+    self.save();
+    subscribed
+  }
+}
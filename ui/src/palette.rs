@@ -0,0 +1,199 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Position, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, List, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::app::Context;
+use crate::keymap::{Action, Scope, ACTIONS};
+use crate::theme;
+
+// Rows of matches shown at most
+static MAX_ROWS: u16 = 12;
+
+// What picking an entry of the palette does
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+  // Do an action of the shown screen, or a global one
+  Action(Action),
+  Search(String),
+  // Open a video of the history
+  Video(String),
+  // Open a channel subscribed to
+  Channel(String),
+  // Play the queue from this video
+  Queued(usize),
+}
+
+struct Entry {
+  command: Command,
+  // Where it comes from, e.g. `history`
+  kind: &'static str,
+  label: String,
+  // The keys of an action
+  keys: String,
+}
+
+// What the palette wants after a key
+pub enum Outcome {
+  Stay,
+  Close,
+  Run(Command),
+}
+
+// Finds actions, recent searches, watched videos, subscriptions and queued videos by a few
+// letters of their names
+pub struct CommandPalette {
+  query: String,
+  entries: Vec<Entry>,
+  // The entries matching the query, best first, with the positions of the matched characters
+  matches: Vec<(usize, Vec<usize>)>,
+  list: ListState,
+}
+
+// Where the characters of `query` are in `text`, in order and ignoring case, and how well they
+// match: runs of characters and starts of words count more, gaps count less
+fn fuzzy(query: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+  let text: Vec<char> = text.chars().collect();
+  let mut positions: Vec<usize> = vec![];
+  let mut score = 0;
+  let mut from = 0;
+  for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+    let index = (from..text.len()).find(|index| text[*index].to_lowercase().eq(wanted.to_lowercase()))?;
+    score += 1;
+    if index > 0 && positions.last() == Some(&(index - 1)) {
+      score += 5;
+    }
+    if index == 0 || !text[index - 1].is_alphanumeric() {
+      score += 3;
+    }
+    score -= (index - from).min(3) as i64;
+    positions.push(index);
+    from = index + 1;
+  }
+  Some((score, positions))
+}
+
+impl CommandPalette {
+  // The actions of `scope` and its parents, then what the context keeps
+  pub fn new(scope: Scope, context: &Context) -> CommandPalette {
+    let mut entries: Vec<Entry> = vec![];
+    for shown in std::iter::once(scope).chain(scope.parents().iter().copied()) {
+      for info in ACTIONS.iter().filter(|info| info.scope == shown && info.action != Action::Palette) {
+        // A closer scope takes the action over
+        if entries.iter().any(|entry| entry.command == Command::Action(info.action)) {
+          continue;
+        }
+        let keys: Vec<String> = context.keymap.keys(scope, info.action).iter().map(ToString::to_string).collect();
+        entries.push(Entry { command: Command::Action(info.action), kind: "action", label: info.description.to_string(), keys: keys.join(" ") });
+      }
+    }
+    let library = &context.library;
+    for search in &library.searches {
+      entries.push(Entry { command: Command::Search(search.clone()), kind: "search", label: search.clone(), keys: String::new() });
+    }
+    for watched in &library.history {
+      entries.push(Entry { command: Command::Video(watched.id.clone()), kind: "history", label: watched.title.clone(), keys: String::new() });
+    }
+    for subscription in &library.subscriptions {
+      entries.push(Entry { command: Command::Channel(subscription.id.clone()), kind: "channel", label: subscription.name.clone(), keys: String::new() });
+    }
+    for (index, video) in context.queue.iter().enumerate() {
+      entries.push(Entry { command: Command::Queued(index), kind: "queue", label: video.title.clone(), keys: String::new() });
+    }
+    let mut palette = CommandPalette { query: String::new(), entries, matches: vec![], list: ListState::default() };
+    palette.filter();
+    palette
+  }
+
+  // Match the entries with the query again, and search it on YouTube as the last choice
+  fn filter(&mut self) {
+    self.entries.retain(|entry| entry.kind != "new search");
+    let query = self.query.trim().to_string();
+    let mut matches: Vec<(i64, usize, Vec<usize>)> = self.entries.iter().enumerate()
+      .filter_map(|(index, entry)| fuzzy(&query, &entry.label).map(|(score, positions)| (score, index, positions)))
+      .collect();
+    // The best first, in the order of the entries when even
+    matches.sort_by_key(|(score, index, _)| (-score, *index));
+    self.matches = matches.into_iter().map(|(_, index, positions)| (index, positions)).collect();
+    if !query.is_empty() {
+      self.entries.push(Entry { command: Command::Search(query.clone()), kind: "new search", label: format!("Search YouTube for \"{}\"", query), keys: String::new() });
+      self.matches.push((self.entries.len() - 1, vec![]));
+    }
+    self.list.select((!self.matches.is_empty()).then_some(0));
+  }
+
+  pub fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+      KeyCode::Esc => return Outcome::Close,
+      KeyCode::Enter => {
+        return match self.list.selected().and_then(|row| self.matches.get(row)) {
+          Some((index, _)) => Outcome::Run(self.entries[*index].command.clone()),
+          None => Outcome::Close,
+        };
+      },
+      KeyCode::Up | KeyCode::BackTab => self.list.select_previous(),
+      KeyCode::Char('p' | 'k') if ctrl => self.list.select_previous(),
+      KeyCode::Down | KeyCode::Tab => self.list.select_next(),
+      KeyCode::Char('n' | 'j') if ctrl => self.list.select_next(),
+      KeyCode::Backspace => {
+        self.query.pop();
+        self.filter();
+      },
+      KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+        self.query.push(c);
+        self.filter();
+      },
+      _ => {},
+    }
+    Outcome::Stay
+  }
+
+  // Move the selection with the wheel
+  pub fn scroll(&mut self, down: bool) {
+    match down {
+      true => self.list.select_next(),
+      false => self.list.select_previous(),
+    }
+  }
+
+  // Over the top of `area`, the query then the matches
+  pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let width = area.width.min(80);
+    let height = (self.matches.len() as u16).min(MAX_ROWS) + 3;
+    let area = Rect::new(area.x + (area.width - width) / 2, area.y, width, height.min(area.height));
+    let block = Block::bordered().title(" Commands ").border_style(Style::new().fg(theme.accent));
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+    if inner.height == 0 {
+      return;
+    }
+    let prompt = Line::from(vec![Span::raw("› ").fg(theme.accent), Span::raw(self.query.as_str())]);
+    frame.render_widget(Paragraph::new(prompt), Rect { height: 1, ..inner });
+    frame.set_cursor_position(Position::new(inner.x + 2 + (self.query.chars().count() as u16).min(inner.width.saturating_sub(3)), inner.y));
+
+    let rows = self.matches.iter().map(|(index, positions)| {
+      let entry = &self.entries[*index];
+      let mut spans = vec![Span::raw(format!("{:<11}", entry.kind)).fg(theme.muted)];
+      for (position, c) in entry.label.chars().enumerate() {
+        spans.push(match positions.contains(&position) {
+          true => Span::raw(c.to_string()).fg(theme.accent).add_modifier(Modifier::BOLD),
+          false => Span::raw(c.to_string()),
+        });
+      }
+      // The keys on the right when there is room for them, past the highlight symbol
+      let room = (inner.width as usize).saturating_sub(Line::from(spans.clone()).width() + 3);
+      if !entry.keys.is_empty() && entry.keys.chars().count() < room {
+        spans.push(Span::raw(format!("{:>width$}", entry.keys, width = room)).fg(theme.muted));
+      }
+      Line::from(spans)
+    });
+    let list = List::new(rows).highlight_style(theme.selected()).highlight_symbol("▌ ");
+    let matches = Rect { y: inner.y + 1, height: inner.height - 1, ..inner };
+    frame.render_stateful_widget(list, matches, &mut self.list);
+  }
+}
@@ -0,0 +1,57 @@
+use std::io::Result;
+use std::time::Duration;
+
+use api::fetcher::{ChannelTab, ChannelTabParams, CommentsParams, SearchParams};
+use api::models::{ChannelObject, ChannelPlaylistsObject, ChannelVideosObject, CommentsObject, CommunityObject, PlaylistObject, SearchItem, VideoObject};
+use youtube::models::video::VideoInfo;
+
+// What the interface needs from the rest of the client. The calls block, the interface
+// makes them away from its event loop.
+pub trait Provider: Send + Sync {
+  fn search(&self, params: SearchParams) -> Result<Vec<SearchItem>>;
+  // Queries starting like `query`, to complete it
+  fn suggestions(&self, query: &str) -> Result<Vec<String>>;
+  fn video(&self, id: &str) -> Result<VideoObject>;
+  // What the YouTube API tells of a video and Invidious does not, e.g. its tags
+  fn info(&self, id: &str) -> Result<VideoInfo>;
+  fn comments(&self, id: &str, params: CommentsParams) -> Result<CommentsObject>;
+  fn channel(&self, id: &str) -> Result<ChannelObject>;
+  // A page of the videos, shorts or streams of a channel
+  fn channel_videos(&self, id: &str, tab: ChannelTab, params: ChannelTabParams) -> Result<ChannelVideosObject>;
+  fn channel_playlists(&self, id: &str, params: ChannelTabParams) -> Result<ChannelPlaylistsObject>;
+  fn community(&self, id: &str, params: ChannelTabParams) -> Result<CommunityObject>;
+  // A page of 100 videos of a playlist, from 1
+  fn playlist(&self, id: &str, page: u32) -> Result<PlaylistObject>;
+  // Play a video full screen from `start`, the interface leaves the terminal to it until it returns.
+  // The mini player when the user shrank it rather than stopping it.
+  fn play(&self, id: &str, start: Option<Duration>) -> Result<Option<Box<dyn MiniPlayer>>>;
+  // Download a video in the background
+  fn download(&self, id: &str) -> Result<()>;
+  // What the settings screen lists, as names and values
+  fn settings(&self) -> Vec<(String, String)>;
+}
+
+// A cell of a video frame: the character, its color and the color behind it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VideoCell {
+  pub ch: char,
+  pub fg: (u8, u8, u8),
+  pub bg: Option<(u8, u8, u8)>,
+}
+
+// A playback going on in a corner of the interface, after the player was shrunk. It keeps
+// decoding and playing the sound while the user browses.
+pub trait MiniPlayer {
+  // The frame to show now in `cols` by `rows` cells, row by row, none until one is decoded.
+  // Without colors the cells are meant to be read in their characters only.
+  fn frame(&mut self, cols: u16, rows: u16, colored: bool) -> Result<Option<Vec<Vec<VideoCell>>>>;
+  fn position(&mut self) -> Duration;
+  // How long the video is, when known
+  fn duration(&self) -> Option<Duration>;
+  fn is_paused(&self) -> bool;
+  fn toggle_pause(&mut self);
+  // The video was played to its end
+  fn is_over(&self) -> bool;
+  // Play full screen again, until the player stops or is shrunk once more
+  fn expand(self: Box<Self>) -> Result<Option<Box<dyn MiniPlayer>>>;
+}
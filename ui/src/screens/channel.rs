@@ -0,0 +1,362 @@
+use api::fetcher::{ChannelTab, ChannelTabParams};
+use api::models::{ChannelObject, CommunityPostObject, PlaylistObject, VideoObject};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph, Tabs, Wrap};
+use ratatui::Frame;
+
+use crate::app::Context;
+use crate::event::{RequestId, Response};
+use crate::{format, get_logger, theme};
+use crate::keymap::{Action, Scope};
+use crate::screens::playlist::PlaylistScreen;
+use crate::screens::results::video_entry;
+use crate::screens::video::VideoScreen;
+use crate::screens::{Click, ListHits, Loading, Screen, Transition, PAGE};
+
+// How close to the last entry the selection gets before the next page is fetched
+static LOAD_AHEAD: usize = 5;
+
+// Lines of a community post shown in the list
+static POST_LINES: usize = 4;
+
+static TABS: [ChannelTab; 5] = [ChannelTab::Videos, ChannelTab::Shorts, ChannelTab::Streams, ChannelTab::Playlists, ChannelTab::Community];
+
+fn tab_name(tab: ChannelTab) -> &'static str {
+  match tab {
+    ChannelTab::Videos => "Videos",
+    ChannelTab::Shorts => "Shorts",
+    ChannelTab::Streams => "Live",
+    ChannelTab::Playlists => "Playlists",
+    ChannelTab::Community => "Community",
+  }
+}
+
+// The orders a tab can be sorted in, as values of `sort_by`. The first one is the default.
+fn sorts(tab: ChannelTab) -> &'static [&'static str] {
+  match tab {
+    ChannelTab::Videos | ChannelTab::Shorts | ChannelTab::Streams => &["newest", "popular", "oldest"],
+    ChannelTab::Playlists => &["newest", "oldest"],
+    ChannelTab::Community => &[],
+  }
+}
+
+enum Entry {
+  Video(Box<VideoObject>),
+  Playlist(PlaylistObject),
+  Post(CommunityPostObject),
+}
+
+fn entry(entry: &Entry) -> ListItem<'_> {
+  let theme = theme::current();
+  match entry {
+    Entry::Video(video) => video_entry(video),
+    Entry::Playlist(playlist) => ListItem::new(Text::from(vec![
+      Line::from(Span::raw(playlist.title.as_str()).bold()),
+      Line::from(format!("playlist · {} videos", playlist.videoCount)).fg(theme.muted),
+    ])),
+    Entry::Post(post) => {
+      let mut details = format!("{} · {} likes · {} replies", post.publishedText, format::count(post.likeCount as i64), post.replyCount);
+      if let Some(attachment) = post.attachment.as_ref().filter(|attachment| !attachment.r#type.is_empty()) {
+        details.push_str(&format!(" · [{}]", attachment.r#type));
+      }
+      let mut lines = vec![Line::from(vec![Span::raw(post.author.as_str()).bold(), Span::raw(format!(" · {}", details)).fg(theme.muted)])];
+      let content: Vec<&str> = post.content.lines().collect();
+      lines.extend(content.iter().take(POST_LINES).map(|line| Line::from(*line)));
+      if content.len() > POST_LINES {
+        lines.push(Line::from("…").fg(theme.muted));
+      }
+      lines.push(Line::default());
+      ListItem::new(Text::from(lines))
+    },
+  }
+}
+
+// A list of the channel, fetched a page at a time as the selection goes down
+struct TabList {
+  tab: ChannelTab,
+  // Index in `sorts(tab)`
+  sort: usize,
+  entries: Vec<Entry>,
+  list: ListState,
+  continuation: Option<String>,
+  pending: Option<RequestId>,
+  // Pages fetched so far
+  pages: u32,
+  // The last page had no continuation
+  exhausted: bool,
+  error: Option<String>,
+  hits: ListHits,
+}
+
+impl TabList {
+  fn new(tab: ChannelTab, sort: usize) -> TabList {
+    TabList {
+      tab,
+      sort,
+      entries: vec![],
+      list: ListState::default().with_selected(Some(0)),
+      continuation: None,
+      pending: None,
+      pages: 0,
+      exhausted: false,
+      error: None,
+      hits: ListHits::default(),
+    }
+  }
+
+  fn load_more(&mut self, channel_id: &str, context: &mut Context) {
+    if self.pending.is_some() || self.exhausted || self.error.is_some() {
+      return;
+    }
+    let (id, tab) = (channel_id.to_string(), self.tab);
+    let params = ChannelTabParams {
+      sort_by: sorts(tab).get(self.sort).map(|sort| sort.to_string()),
+      continuation: self.continuation.clone(),
+    };
+    self.pending = Some(context.request(move |provider| match tab {
+      ChannelTab::Playlists => provider.channel_playlists(&id, params).map(Response::ChannelPlaylists),
+      ChannelTab::Community => provider.community(&id, params).map(Response::Community),
+      _ => provider.channel_videos(&id, tab, params).map(Response::ChannelVideos),
+    }));
+  }
+
+  // Fetch the first page, the tabs are fetched when they are first shown
+  fn start(&mut self, channel_id: &str, context: &mut Context) {
+    if self.pages == 0 {
+      self.load_more(channel_id, context);
+    }
+  }
+
+  fn load_near_bottom(&mut self, channel_id: &str, context: &mut Context) {
+    if self.list.selected().unwrap_or(0) + LOAD_AHEAD >= self.entries.len() {
+      self.load_more(channel_id, context);
+    }
+  }
+
+  fn handle_response(&mut self, response: &std::io::Result<Response>) {
+    self.pending = None;
+    let (entries, continuation): (Vec<Entry>, Option<String>) = match response {
+      Ok(Response::ChannelVideos(page)) => (page.videos.iter().cloned().map(|video| Entry::Video(Box::new(video))).collect(), page.continuation.clone()),
+      Ok(Response::ChannelPlaylists(page)) => (page.playlists.iter().cloned().map(Entry::Playlist).collect(), page.continuation.clone()),
+      Ok(Response::Community(page)) => (page.comments.iter().cloned().map(Entry::Post).collect(), page.continuation.clone()),
+      Ok(_) => {
+        self.error = Some("Unexpected response".to_string());
+        return;
+      },
+      Err(e) => {
+        self.error = Some(e.to_string());
+        return;
+      },
+    };
+    self.pages += 1;
+    self.exhausted = entries.is_empty() || continuation.is_none();
+    self.continuation = continuation;
+    self.entries.extend(entries);
+  }
+
+  fn draw(&mut self, frame: &mut Frame, area: Rect, context: &Context) {
+    let theme = theme::current();
+    let footer = if self.pending.is_some() {
+      Some(Line::from(format!("{} Loading...", context.spinner())).fg(theme.muted))
+    } else if let Some(error) = &self.error {
+      Some(Line::from(error.clone()).fg(theme.error))
+    } else if self.exhausted && self.entries.is_empty() {
+      Some(Line::from(format!("No {}", tab_name(self.tab).to_lowercase())).fg(theme.muted))
+    } else {
+      None
+    };
+    // The footer is never selected
+    match self.entries.is_empty() {
+      true => self.list.select(None),
+      false => self.list.select(Some(self.list.selected().unwrap_or(0).min(self.entries.len() - 1))),
+    }
+    let mut items: Vec<ListItem> = self.entries.iter().map(entry).collect();
+    items.extend(footer.map(ListItem::new));
+    self.hits.set(area, &items);
+    let list = List::new(items)
+      .highlight_style(theme.selected())
+      .highlight_symbol("▌ ");
+    frame.render_stateful_widget(list, area, &mut self.list);
+  }
+}
+
+// A channel, with its videos, shorts, streams, playlists and posts in tabs
+pub struct ChannelScreen {
+  id: String,
+  channel: Loading<Box<ChannelObject>>,
+  // One list per tab, in the order of `TABS`
+  tabs: Vec<TabList>,
+  tab: usize,
+  // Where the titles of the tabs were drawn, and how wide each one is
+  tabs_area: Rect,
+  tab_widths: Vec<u16>,
+}
+
+impl ChannelScreen {
+  pub fn new(id: String, context: &mut Context) -> ChannelScreen {
+    let channel_id = id.clone();
+    let request = context.request(move |provider| provider.channel(&channel_id).map(|channel| Response::Channel(Box::new(channel))));
+    let mut tabs: Vec<TabList> = TABS.iter().map(|tab| TabList::new(*tab, 0)).collect();
+    tabs[0].start(&id, context);
+    ChannelScreen { id, channel: Loading::Pending(request), tabs, tab: 0, tabs_area: Rect::default(), tab_widths: vec![] }
+  }
+
+  fn switch(&mut self, tab: usize, context: &mut Context) {
+    self.tab = tab;
+    self.tabs[tab].start(&self.id, context);
+  }
+
+  // Sort the shown tab in its next order, from its first page again
+  fn sort(&mut self, context: &mut Context) {
+    let current = &self.tabs[self.tab];
+    let count = sorts(current.tab).len();
+    if count < 2 {
+      return;
+    }
+    let mut list = TabList::new(current.tab, (current.sort + 1) % count);
+    list.start(&self.id, context);
+    self.tabs[self.tab] = list;
+  }
+
+  fn open(&self, context: &mut Context) -> Option<Transition> {
+    let list = &self.tabs[self.tab];
+    match list.entries.get(list.list.selected()?)? {
+      Entry::Video(video) => Some(Transition::Push(Box::new(VideoScreen::new(video.videoId.clone(), context)))),
+      Entry::Playlist(playlist) => Some(Transition::Push(Box::new(PlaylistScreen::new(playlist.playlistId.clone(), context)))),
+      Entry::Post(_) => None,
+    }
+  }
+}
+
+impl Screen for ChannelScreen {
+  fn title(&self) -> String {
+    match &self.channel {
+      Loading::Ready(channel) => channel.author.clone(),
+      _ => self.id.clone(),
+    }
+  }
+
+  fn scope(&self) -> Scope {
+    Scope::Channel
+  }
+
+  fn hints(&self) -> Vec<(Action, &'static str)> {
+    let mut hints = vec![(Action::Open, "open"), (Action::NextTab, "next tab")];
+    if sorts(self.tabs[self.tab].tab).len() > 1 {
+      hints.push((Action::Sort, "sort"));
+    }
+    hints.push((Action::Subscribe, "subscribe"));
+    hints.push((Action::Back, "back"));
+    hints
+  }
+
+  fn draw(&mut self, frame: &mut Frame, area: Rect, context: &Context) {
+    let Some(channel) = self.channel.draw(frame, area, context) else {
+      return;
+    };
+    let theme = theme::current();
+    let [header, description, tabs, list] = Layout::vertical([Constraint::Length(2), Constraint::Length(3), Constraint::Length(2), Constraint::Min(0)]).areas(area);
+    let mut name = vec![Span::raw(channel.author.as_str()).bold()];
+    if channel.authorVerified {
+      name.push(Span::raw(" ✓").fg(theme.highlight));
+    }
+    let mut details = vec![Span::raw(format!("{} subscribers · {} videos", format::count(channel.subCount), channel.videoCount)).fg(theme.muted)];
+    if context.library.is_subscribed(&self.id) {
+      details.push(Span::raw(" · subscribed").fg(theme.accent));
+    }
+    let header_text = Text::from(vec![Line::from(name), Line::from(details)]);
+    frame.render_widget(Paragraph::new(header_text), header);
+    frame.render_widget(Paragraph::new(channel.description.as_str()).wrap(Wrap { trim: true }), description);
+
+    let current = &self.tabs[self.tab];
+    let mut titles: Vec<Line> = TABS.iter().map(|tab| Line::from(tab_name(*tab))).collect();
+    if let Some(sort) = sorts(current.tab).get(current.sort) {
+      titles[self.tab] = Line::from(format!("{} ({})", tab_name(current.tab), sort));
+    }
+    self.tabs_area = tabs;
+    self.tab_widths = titles.iter().map(|title| title.width() as u16).collect();
+    let tabs_widget = Tabs::new(titles).select(self.tab).highlight_style(Style::new().fg(theme.accent).bold());
+    frame.render_widget(tabs_widget, tabs);
+
+    self.tabs[self.tab].draw(frame, list, context);
+  }
+
+  fn handle_key(&mut self, key: KeyEvent, context: &mut Context) -> Option<Transition> {
+    // The number of a tab shows it
+    if let KeyCode::Char(c @ '1'..='5') = key.code {
+      self.switch(c as usize - '1' as usize, context);
+      return Some(Transition::Stay);
+    }
+    let action = context.action(Scope::Channel, &key)?;
+    self.perform(action, context)
+  }
+
+  fn perform(&mut self, action: Action, context: &mut Context) -> Option<Transition> {
+    let list = &mut self.tabs[self.tab].list;
+    match action {
+      Action::NextTab => self.switch((self.tab + 1) % TABS.len(), context),
+      Action::PreviousTab => self.switch((self.tab + TABS.len() - 1) % TABS.len(), context),
+      Action::Sort => self.sort(context),
+      Action::Subscribe => {
+        if let Loading::Ready(channel) = &self.channel {
+          match context.library.toggle_subscription(&self.id, &channel.author) {
+            true => get_logger().success(format!("Subscribed to {}", channel.author)),
+            false => get_logger().success(format!("Unsubscribed from {}", channel.author)),
+          }
+        }
+      },
+      Action::PreviousResult => list.select_previous(),
+      Action::NextResult => list.select_next(),
+      Action::PreviousPage => list.scroll_up_by(PAGE),
+      Action::NextPage => list.scroll_down_by(PAGE),
+      Action::FirstResult => list.select_first(),
+      Action::LastResult => {
+        let last = self.tabs[self.tab].entries.len().saturating_sub(1);
+        self.tabs[self.tab].list.select(Some(last));
+      },
+      Action::Open => return Some(self.open(context).unwrap_or(Transition::Stay)),
+      _ => return None,
+    }
+    self.tabs[self.tab].load_near_bottom(&self.id, context);
+    Some(Transition::Stay)
+  }
+
+  // A click on the title of a tab shows it, a click on an entry selects it then opens it
+  fn click(&mut self, mouse: MouseEvent, context: &mut Context) -> Option<Transition> {
+    if self.tabs_area.contains(Position::new(mouse.column, mouse.row)) {
+      // Each title is padded with a space on both sides and followed by a divider
+      let mut right = self.tabs_area.x;
+      for (tab, width) in self.tab_widths.iter().enumerate() {
+        right += width + 3;
+        if mouse.column < right {
+          self.switch(tab, context);
+          break;
+        }
+      }
+      return Some(Transition::Stay);
+    }
+    let list = &mut self.tabs[self.tab];
+    let click = list.hits.click(&mut list.list, list.entries.len(), &mouse)?;
+    list.load_near_bottom(&self.id, context);
+    match click {
+      Click::Open => self.perform(Action::Open, context),
+      Click::Selected => Some(Transition::Stay),
+    }
+  }
+
+  fn handle_response(&mut self, request: RequestId, response: &std::io::Result<Response>, context: &mut Context) {
+    self.channel.resolve(request, response, |response| match response {
+      Response::Channel(channel) => Some(channel.clone()),
+      _ => None,
+    });
+    let id = self.id.clone();
+    if let Some(list) = self.tabs.iter_mut().find(|list| list.pending == Some(request)) {
+      list.handle_response(response);
+      // A short page may leave the selection near the bottom still
+      list.load_near_bottom(&id, context);
+    }
+  }
+}
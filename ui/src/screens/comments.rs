@@ -0,0 +1,387 @@
+use std::time::Duration;
+
+use api::fetcher::CommentsParams;
+use api::models::{CommentObject, CommentsObject};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Stylize;
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{List, ListItem, ListState};
+use ratatui::Frame;
+
+use crate::app::Context;
+use crate::event::{RequestId, Response};
+use crate::keymap::Action;
+use crate::screens::PAGE;
+use crate::{format, text, theme};
+
+// How close to the last row the selection gets before the next page is fetched
+static LOAD_AHEAD: usize = 5;
+
+// Columns a reply is indented by
+static REPLY_INDENT: usize = 4;
+
+// The orders the comments can be sorted in, as values of `sort_by`
+static SORTS: [&str; 2] = ["top", "new"];
+
+// A comment and the replies to it, fetched when it is first expanded
+struct Thread {
+  comment: CommentObject,
+  replies: Vec<CommentObject>,
+  expanded: bool,
+  // The next page of replies, the first one until it is fetched
+  continuation: Option<String>,
+  pending: Option<RequestId>,
+  error: Option<String>,
+}
+
+impl Thread {
+  fn new(comment: CommentObject) -> Thread {
+    let continuation = comment.replies.as_ref().map(|replies| replies.continuation.clone()).filter(|continuation| !continuation.is_empty());
+    Thread { comment, replies: vec![], expanded: false, continuation, pending: None, error: None }
+  }
+
+  fn reply_count(&self) -> i32 {
+    self.comment.replies.as_ref().map_or(0, |replies| replies.repliesCount)
+  }
+
+  // Whether the thread ends with a row to show or fetch its replies
+  fn has_more(&self) -> bool {
+    self.reply_count() > 0 && (!self.expanded || self.continuation.is_some() || self.pending.is_some() || self.error.is_some())
+  }
+}
+
+// A row of the tree
+#[derive(Clone, Copy, PartialEq)]
+enum Row {
+  // A thread, and one of its replies
+  Comment(usize, Option<usize>),
+  // The replies of a thread to show or to fetch
+  MoreReplies(usize),
+}
+
+// The comments of a video as a tree of threads, fetched a page at a time
+pub struct CommentsPane {
+  video_id: String,
+  // Index in `SORTS`
+  sort: usize,
+  threads: Vec<Thread>,
+  // Only sent with the first page
+  count: Option<i32>,
+  continuation: Option<String>,
+  pending: Option<RequestId>,
+  // Pages fetched so far
+  pages: u32,
+  // The last page had no continuation
+  exhausted: bool,
+  error: Option<String>,
+  rows: Vec<Row>,
+  list: ListState,
+  // The timestamp of the selected comment picked to play from
+  timestamp: Option<usize>,
+}
+
+// `author · 3 days ago (edited) · 12 likes`, after the pinned marker
+fn comment_header(comment: &CommentObject) -> Line<'static> {
+  let theme = theme::current();
+  let mut header = vec![];
+  if comment.isPinned {
+    header.push(Span::raw("Pinned · ").fg(theme.warn));
+  }
+  header.push(match comment.authorIsChannelOwner {
+    true => Span::raw(format!(" {} ", comment.author)).bold().style(theme.badge()),
+    false => Span::raw(comment.author.clone()).bold(),
+  });
+  let mut details = match comment.published {
+    0 => comment.publishedText.clone(),
+    published => format::ago(published),
+  };
+  if comment.isEdited {
+    details.push_str(" (edited)");
+  }
+  if comment.likeCount > 0 {
+    details.push_str(&format!(" · {} likes", format::count(comment.likeCount as i64)));
+  }
+  header.push(Span::raw(format!(" · {}", details)).fg(theme.muted));
+  Line::from(header)
+}
+
+// The lines of the content of a comment, and the positions of its timestamps
+fn comment_content(comment: &CommentObject, timestamp: Option<usize>) -> (Vec<Line<'static>>, Vec<Duration>) {
+  match comment.contentHtml.is_empty() {
+    true => text::html(&comment.content.replace('<', "&lt;"), timestamp),
+    false => text::html(&comment.contentHtml, timestamp),
+  }
+}
+
+fn comment_item(comment: &CommentObject, indent: usize, width: usize, timestamp: Option<usize>) -> ListItem<'static> {
+  let padding = " ".repeat(indent);
+  let mut lines = vec![comment_header(comment)];
+  for line in comment_content(comment, timestamp).0 {
+    lines.extend(text::wrap(line, width.saturating_sub(indent)));
+  }
+  lines.push(Line::default());
+  let lines: Vec<Line> = lines.into_iter().map(|line| {
+    let mut spans = vec![Span::raw(padding.clone())];
+    spans.extend(line.spans);
+    Line::from(spans)
+  }).collect();
+  ListItem::new(Text::from(lines))
+}
+
+impl CommentsPane {
+  pub fn new(video_id: String) -> CommentsPane {
+    CommentsPane::sorted(video_id, 0)
+  }
+
+  fn sorted(video_id: String, sort: usize) -> CommentsPane {
+    CommentsPane {
+      video_id,
+      sort,
+      threads: vec![],
+      count: None,
+      continuation: None,
+      pending: None,
+      pages: 0,
+      exhausted: false,
+      error: None,
+      rows: vec![],
+      list: ListState::default().with_selected(Some(0)),
+      timestamp: None,
+    }
+  }
+
+  // How many comments the video has, once the first page is fetched
+  pub fn count(&self) -> Option<i32> {
+    self.count
+  }
+
+  fn request(&self, continuation: Option<String>, context: &mut Context) -> RequestId {
+    let id = self.video_id.clone();
+    let params = CommentsParams { sort_by: Some(SORTS[self.sort].to_string()), continuation };
+    context.request(move |provider| provider.comments(&id, params).map(Response::Comments))
+  }
+
+  fn load_more(&mut self, context: &mut Context) {
+    if self.pending.is_some() || self.exhausted || self.error.is_some() {
+      return;
+    }
+    self.pending = Some(self.request(self.continuation.clone(), context));
+  }
+
+  // Fetch the first page, the comments are fetched when they are first shown
+  pub fn start(&mut self, context: &mut Context) {
+    if self.pages == 0 {
+      self.load_more(context);
+    }
+  }
+
+  fn load_near_bottom(&mut self, context: &mut Context) {
+    if self.list.selected().unwrap_or(0) + LOAD_AHEAD >= self.rows.len() {
+      self.load_more(context);
+    }
+  }
+
+  fn update_rows(&mut self) {
+    self.rows.clear();
+    for (index, thread) in self.threads.iter().enumerate() {
+      self.rows.push(Row::Comment(index, None));
+      if thread.expanded {
+        self.rows.extend((0..thread.replies.len()).map(|reply| Row::Comment(index, Some(reply))));
+      }
+      if thread.has_more() {
+        self.rows.push(Row::MoreReplies(index));
+      }
+    }
+  }
+
+  fn selected_row(&self) -> Option<Row> {
+    self.rows.get(self.list.selected()?).copied()
+  }
+
+  fn selected_comment(&self) -> Option<&CommentObject> {
+    match self.selected_row()? {
+      Row::Comment(thread, None) => Some(&self.threads[thread].comment),
+      Row::Comment(thread, Some(reply)) => self.threads[thread].replies.get(reply),
+      Row::MoreReplies(_) => None,
+    }
+  }
+
+  // Where the picked timestamp of the selected comment is
+  pub fn timestamp(&self) -> Option<Duration> {
+    let positions = comment_content(self.selected_comment()?, None).1;
+    positions.get(self.timestamp?).copied()
+  }
+
+  // Pick the next or the previous timestamp of the selected comment
+  fn select_timestamp(&mut self, next: bool) {
+    let count = self.selected_comment().map_or(0, |comment| comment_content(comment, None).1.len());
+    if count == 0 {
+      return;
+    }
+    self.timestamp = Some(match (self.timestamp, next) {
+      (None, true) => 0,
+      (None, false) => count - 1,
+      (Some(index), true) => (index + 1) % count,
+      (Some(index), false) => index.checked_sub(1).unwrap_or(count - 1),
+    });
+  }
+
+  fn select(&mut self, row: Row) {
+    self.list.select(self.rows.iter().position(|other| *other == row));
+    self.timestamp = None;
+  }
+
+  // Show or hide the replies of the selected thread, fetching them the first time, or fetch
+  // more of them from the row after them
+  fn toggle(&mut self, context: &mut Context) {
+    let index = match self.selected_row() {
+      Some(Row::Comment(thread, _)) | Some(Row::MoreReplies(thread)) => thread,
+      None => return,
+    };
+    let more = self.selected_row() == Some(Row::MoreReplies(index));
+    let thread = &self.threads[index];
+    if thread.reply_count() == 0 || thread.pending.is_some() {
+      return;
+    }
+    let collapse = thread.expanded && !more;
+    let fetch = match (thread.expanded, more) {
+      (false, _) => thread.replies.is_empty(),
+      (true, more) => more && (thread.continuation.is_some() || thread.error.is_some()),
+    };
+    if fetch {
+      let request = self.request(thread.continuation.clone(), context);
+      let thread = &mut self.threads[index];
+      thread.pending = Some(request);
+      thread.error = None;
+    }
+    self.threads[index].expanded = !collapse;
+    self.update_rows();
+    if collapse {
+      self.select(Row::Comment(index, None));
+    }
+  }
+
+  // Sort the comments in the next order, from the first page again
+  fn sort(&mut self, context: &mut Context) {
+    *self = CommentsPane::sorted(self.video_id.clone(), (self.sort + 1) % SORTS.len());
+    self.start(context);
+  }
+
+  pub fn hints(&self) -> Vec<(Action, &'static str)> {
+    vec![(Action::Open, "replies"), (Action::NextTimestamp, "timestamps"), (Action::Sort, "sort")]
+  }
+
+  pub fn draw(&mut self, frame: &mut Frame, area: Rect, context: &Context) {
+    let theme = theme::current();
+    let [header, body] = Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).areas(area);
+    let count = match self.count {
+      Some(count) => format!("{} comments", format::count(count as i64)),
+      None => "Comments".to_string(),
+    };
+    frame.render_widget(Line::from(format!("{} · {} first", count, SORTS[self.sort])).fg(theme.muted), header);
+
+    let footer = if self.pending.is_some() {
+      Some(Line::from(format!("{} Loading...", context.spinner())).fg(theme.muted))
+    } else if let Some(error) = &self.error {
+      Some(Line::from(error.clone()).fg(theme.error))
+    } else if self.exhausted && self.threads.is_empty() {
+      Some(Line::from("No comments").fg(theme.muted))
+    } else {
+      None
+    };
+    // The footer is never selected
+    match self.rows.is_empty() {
+      true => self.list.select(None),
+      false => self.list.select(Some(self.list.selected().unwrap_or(0).min(self.rows.len() - 1))),
+    }
+    // Past the highlight symbol
+    let width = (body.width as usize).saturating_sub(2);
+    let selected = self.list.selected();
+    let mut items: Vec<ListItem> = self.rows.iter().enumerate().map(|(index, row)| {
+      let timestamp = self.timestamp.filter(|_| selected == Some(index));
+      match *row {
+        Row::Comment(thread, None) => comment_item(&self.threads[thread].comment, 0, width, timestamp),
+        Row::Comment(thread, Some(reply)) => comment_item(&self.threads[thread].replies[reply], REPLY_INDENT, width, timestamp),
+        Row::MoreReplies(thread) => {
+          let thread = &self.threads[thread];
+          let line = if thread.pending.is_some() {
+            Line::from(format!("{} Loading replies...", context.spinner())).fg(theme.muted)
+          } else if let Some(error) = &thread.error {
+            Line::from(format!("Failed to load the replies: {}", error)).fg(theme.error)
+          } else if !thread.expanded {
+            let count = thread.reply_count();
+            Line::from(format!("▸ {} {}", format::count(count as i64), if count == 1 { "reply" } else { "replies" })).fg(theme.link)
+          } else {
+            Line::from("▸ More replies").fg(theme.link)
+          };
+          let mut spans = vec![Span::raw(" ".repeat(REPLY_INDENT))];
+          spans.extend(line.spans.into_iter().map(|span| span.patch_style(line.style)));
+          ListItem::new(Text::from(vec![Line::from(spans), Line::default()]))
+        },
+      }
+    }).collect();
+    items.extend(footer.map(ListItem::new));
+    let list = List::new(items)
+      .highlight_style(theme.selected())
+      .highlight_symbol("▌ ");
+    frame.render_stateful_widget(list, body, &mut self.list);
+  }
+
+  // Do `action` on the comments, false when they have nothing to do with it
+  pub fn perform(&mut self, action: Action, context: &mut Context) -> bool {
+    let selected = self.list.selected();
+    match action {
+      Action::PreviousResult => self.list.select_previous(),
+      Action::NextResult => self.list.select_next(),
+      Action::PreviousPage => self.list.scroll_up_by(PAGE),
+      Action::NextPage => self.list.scroll_down_by(PAGE),
+      Action::FirstResult => self.list.select_first(),
+      Action::LastResult => self.list.select(Some(self.rows.len().saturating_sub(1))),
+      Action::Open => self.toggle(context),
+      Action::NextTimestamp => self.select_timestamp(true),
+      Action::PreviousTimestamp => self.select_timestamp(false),
+      Action::Sort => self.sort(context),
+      _ => return false,
+    }
+    if self.list.selected() != selected {
+      self.timestamp = None;
+      self.load_near_bottom(context);
+    }
+    true
+  }
+
+  pub fn handle_response(&mut self, request: RequestId, response: &std::io::Result<Response>, context: &mut Context) {
+    if Some(request) == self.pending {
+      self.pending = None;
+      match response {
+        Ok(Response::Comments(page)) => self.add_page(page),
+        Ok(_) => self.error = Some("Unexpected response".to_string()),
+        Err(e) => self.error = Some(e.to_string()),
+      }
+      self.update_rows();
+      // A short page may leave the selection near the bottom still
+      self.load_near_bottom(context);
+    } else if let Some(thread) = self.threads.iter_mut().find(|thread| thread.pending == Some(request)) {
+      thread.pending = None;
+      match response {
+        Ok(Response::Comments(page)) => {
+          thread.replies.extend(page.comments.iter().cloned());
+          thread.continuation = page.continuation.clone().filter(|_| !page.comments.is_empty());
+        },
+        Ok(_) => thread.error = Some("Unexpected response".to_string()),
+        Err(e) => thread.error = Some(e.to_string()),
+      }
+      self.update_rows();
+    }
+  }
+
+  fn add_page(&mut self, page: &CommentsObject) {
+    self.pages += 1;
+    if page.commentCount.is_some() {
+      self.count = page.commentCount;
+    }
+    self.exhausted = page.comments.is_empty() || page.continuation.is_none();
+    self.continuation = page.continuation.clone();
+    self.threads.extend(page.comments.iter().cloned().map(Thread::new));
+  }
+}
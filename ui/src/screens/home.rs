@@ -0,0 +1,97 @@
+use ratatui::crossterm::event::MouseEvent;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Stylize;
+use ratatui::text::{Line, Text};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::app::Context;
+use crate::keymap::{Action, Scope};
+use crate::screens::queue::QueueScreen;
+use crate::screens::search::SearchScreen;
+use crate::screens::settings::SettingsScreen;
+use crate::screens::{Click, ListHits, Screen, Transition};
+use crate::theme;
+
+static BANNER: &str = "
+██╗   ██╗████████╗██████╗  █████╗ ███████╗ ██████╗██╗██╗
+╚██╗ ██╔╝╚══██╔══╝██╔══██╗██╔══██╗██╔════╝██╔════╝██║██║
+ ╚████╔╝    ██║   ██████╔╝███████║███████╗██║     ██║██║
+  ╚██╔╝     ██║   ██╔══██╗██╔══██║╚════██║██║     ██║██║
+   ██║      ██║   ██████╔╝██║  ██║███████║╚██████╗██║██║
+   ╚═╝      ╚═╝   ╚═════╝ ╚═╝  ╚═╝╚══════╝ ╚═════╝╚═╝╚═╝";
+
+static ENTRIES: &[&str] = &["Search", "Queue", "Settings", "Quit"];
+
+// The first screen, a menu of the others
+pub struct HomeScreen {
+  menu: ListState,
+  hits: ListHits,
+}
+
+impl HomeScreen {
+  pub fn new(_context: &mut Context) -> HomeScreen {
+    HomeScreen { menu: ListState::default().with_selected(Some(0)), hits: ListHits::default() }
+  }
+
+  fn open(&self, entry: &str, context: &mut Context) -> Transition {
+    match entry {
+      "Search" => Transition::Push(Box::new(SearchScreen::new())),
+      "Queue" => Transition::Push(Box::new(QueueScreen::new())),
+      "Settings" => Transition::Push(Box::new(SettingsScreen::new(context))),
+      _ => Transition::Quit,
+    }
+  }
+}
+
+impl Screen for HomeScreen {
+  fn title(&self) -> String {
+    "Home".to_string()
+  }
+
+  fn scope(&self) -> Scope {
+    Scope::Home
+  }
+
+  fn hints(&self) -> Vec<(Action, &'static str)> {
+    vec![(Action::Open, "open"), (Action::Search, "search"), (Action::Quit, "quit")]
+  }
+
+  fn draw(&mut self, frame: &mut Frame, area: Rect, _context: &Context) {
+    let theme = theme::current();
+    let [banner, menu] = Layout::vertical([Constraint::Length(8), Constraint::Min(0)]).areas(area);
+    frame.render_widget(Paragraph::new(Text::from(BANNER).fg(theme.accent)).centered(), banner);
+
+    let width = ENTRIES.iter().map(|entry| entry.len()).max().unwrap_or(0) as u16 + 4;
+    let [_, menu, _] = Layout::horizontal([Constraint::Fill(1), Constraint::Length(width), Constraint::Fill(1)]).areas(menu);
+    let entries: Vec<ListItem> = ENTRIES.iter().map(|entry| ListItem::new(Line::from(*entry))).collect();
+    self.hits.set(menu, &entries);
+    let list = List::new(entries)
+      .highlight_style(theme.inverse())
+      .highlight_symbol("> ");
+    frame.render_stateful_widget(list, menu, &mut self.menu);
+  }
+
+  fn click(&mut self, mouse: MouseEvent, context: &mut Context) -> Option<Transition> {
+    match self.hits.click(&mut self.menu, ENTRIES.len(), &mouse)? {
+      Click::Open => self.perform(Action::Open, context),
+      Click::Selected => Some(Transition::Stay),
+    }
+  }
+
+  fn perform(&mut self, action: Action, context: &mut Context) -> Option<Transition> {
+    match action {
+      Action::PreviousResult => self.menu.select_previous(),
+      Action::NextResult => self.menu.select_next(),
+      Action::FirstResult => self.menu.select_first(),
+      Action::LastResult => self.menu.select(Some(ENTRIES.len() - 1)),
+      Action::Search => return Some(self.open("Search", context)),
+      Action::Open => {
+        let entry = ENTRIES[self.menu.selected().unwrap_or(0).min(ENTRIES.len() - 1)];
+        return Some(self.open(entry, context));
+      },
+      _ => return None,
+    }
+    Some(Transition::Stay)
+  }
+}
@@ -0,0 +1,193 @@
+pub mod channel;
+pub mod comments;
+pub mod home;
+pub mod player;
+pub mod playlist;
+pub mod queue;
+pub mod results;
+pub mod search;
+pub mod settings;
+pub mod video;
+
+use std::time::Duration;
+
+use ratatui::crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::{ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::app::Context;
+use crate::event::{RequestId, Response};
+use crate::keymap::{Action, Scope};
+use crate::theme;
+
+// Entries a page up or down moves by
+pub static PAGE: u16 = 10;
+
+// Where a list was last drawn and how tall its entries are, to find the one clicked
+#[derive(Debug, Default)]
+pub struct ListHits {
+  // Inside the borders
+  area: Rect,
+  heights: Vec<u16>,
+}
+
+impl ListHits {
+  pub fn set(&mut self, area: Rect, entries: &[ListItem]) {
+    self.area = area;
+    self.heights = entries.iter().map(|entry| entry.height() as u16).collect();
+  }
+
+  // The entry under `mouse`, scrolled as `list` was drawn
+  pub fn entry(&self, list: &ListState, mouse: &MouseEvent) -> Option<usize> {
+    if !self.area.contains(Position::new(mouse.column, mouse.row)) {
+      return None;
+    }
+    let mut bottom = self.area.y;
+    for (index, height) in self.heights.iter().enumerate().skip(list.offset()) {
+      bottom += height;
+      if mouse.row < bottom {
+        return Some(index);
+      }
+    }
+    None
+  }
+
+  // Select the clicked one of the first `count` entries, a click on the selected one opens it
+  pub fn click(&self, list: &mut ListState, count: usize, mouse: &MouseEvent) -> Option<Click> {
+    let index = self.entry(list, mouse).filter(|index| *index < count)?;
+    if list.selected() == Some(index) {
+      return Some(Click::Open);
+    }
+    list.select(Some(index));
+    Some(Click::Selected)
+  }
+}
+
+// What a click on a list did
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Click {
+  Selected,
+  // The entry was selected already
+  Open,
+}
+
+// A video a screen asks to play full screen
+pub struct Playback {
+  pub id: String,
+  // Kept in the watch history
+  pub title: String,
+  // Where to start, the beginning when not set
+  pub start: Option<Duration>,
+}
+
+// What the app does with the screens after a key
+pub enum Transition {
+  Stay,
+  // Open a screen over the shown one
+  Push(Box<dyn Screen>),
+  // Close the shown screen
+  Pop,
+  Quit,
+}
+
+// A view of the interface, the screens opened are kept in a stack
+pub trait Screen {
+  // Shown in the path at the top of the interface
+  fn title(&self) -> String;
+
+  // Where its keys are looked up in the keymap, before the global ones
+  fn scope(&self) -> Scope {
+    Scope::Global
+  }
+
+  // The main actions of the screen and what they do there, shown on the status line with
+  // their keys
+  fn hints(&self) -> Vec<(Action, &'static str)> {
+    vec![]
+  }
+
+  fn draw(&mut self, frame: &mut Frame, area: Rect, context: &Context);
+
+  // React to a key, `None` lets the app handle it. Most screens do what it is bound to.
+  fn handle_key(&mut self, key: KeyEvent, context: &mut Context) -> Option<Transition> {
+    let action = context.action(self.scope(), &key)?;
+    self.perform(action, context)
+  }
+
+  // Do `action`, whether its key was pressed or it was picked in the palette. `None` lets
+  // the app handle it.
+  fn perform(&mut self, action: Action, context: &mut Context) -> Option<Transition>;
+
+  // React to the mouse: the wheel moves the selection, and the left button clicks
+  fn handle_mouse(&mut self, mouse: MouseEvent, context: &mut Context) -> Option<Transition> {
+    match mouse.kind {
+      MouseEventKind::ScrollDown => self.perform(Action::NextResult, context),
+      MouseEventKind::ScrollUp => self.perform(Action::PreviousResult, context),
+      MouseEventKind::Down(MouseButton::Left) => self.click(mouse, context),
+      _ => None,
+    }
+  }
+
+  // React to a click of the left button at the position of `mouse`
+  fn click(&mut self, _mouse: MouseEvent, _context: &mut Context) -> Option<Transition> {
+    None
+  }
+
+  // Every response reaches every screen opened, each one keeps the ones it asked for
+  fn handle_response(&mut self, _request: RequestId, _response: &std::io::Result<Response>, _context: &mut Context) {}
+
+  // A video to play full screen, asked after each event
+  fn pending_playback(&mut self) -> Option<Playback> {
+    None
+  }
+
+  // How the playback asked with `pending_playback` ended
+  fn playback_ended(&mut self, _result: std::io::Result<()>) {}
+
+  // The playback asked with `pending_playback` goes on in the mini player
+  fn playback_minimized(&mut self) -> Transition {
+    Transition::Stay
+  }
+}
+
+// What a screen shows while its data is fetched, or when it failed
+pub enum Loading<T> {
+  Pending(RequestId),
+  Ready(T),
+  Failed(String),
+}
+
+impl<T> Loading<T> {
+  // Keep the response of `request` when it is the one waited for
+  pub fn resolve(&mut self, request: RequestId, response: &std::io::Result<Response>, keep: impl FnOnce(&Response) -> Option<T>) {
+    if !matches!(self, Loading::Pending(pending) if *pending == request) {
+      return;
+    }
+    *self = match response {
+      Ok(response) => match keep(response) {
+        Some(value) => Loading::Ready(value),
+        None => Loading::Failed("Unexpected response".to_string()),
+      },
+      Err(e) => Loading::Failed(e.to_string()),
+    };
+  }
+
+  // Draw the spinner or the error, returns the data once it is there
+  pub fn draw(&mut self, frame: &mut Frame, area: Rect, context: &Context) -> Option<&mut T> {
+    let theme = theme::current();
+    match self {
+      Loading::Pending(_) => {
+        frame.render_widget(Paragraph::new(Line::from(format!(" {} Loading...", context.spinner())).fg(theme.muted)), area);
+        None
+      },
+      Loading::Failed(error) => {
+        frame.render_widget(Paragraph::new(Line::from(format!(" {}", error)).fg(theme.error)), area);
+        None
+      },
+      Loading::Ready(value) => Some(value),
+    }
+  }
+}
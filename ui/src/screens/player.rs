@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::{Line, Text};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use crate::app::{Context, QueuedVideo};
+use crate::keymap::Action;
+use crate::screens::{Playback, Screen, Transition};
+use crate::theme;
+
+// Plays videos full screen one after the other when it is opened, then tells how the playback went
+pub struct PlayerScreen {
+  videos: Vec<QueuedVideo>,
+  // The video playing, or the last one played
+  index: usize,
+  // Where the first video starts
+  start: Option<Duration>,
+  // Set until the app starts the playback
+  pending: bool,
+  // How the last playback ended
+  result: Option<Result<(), String>>,
+}
+
+impl PlayerScreen {
+  pub fn new(id: String, title: String) -> PlayerScreen {
+    PlayerScreen::queue(vec![QueuedVideo { id, title }])
+  }
+
+  // Start `id` at `start` rather than at the beginning
+  pub fn at(id: String, title: String, start: Duration) -> PlayerScreen {
+    PlayerScreen { start: Some(start), ..PlayerScreen::new(id, title) }
+  }
+
+  // Play `videos` in order, stopping at the first one that fails
+  pub fn queue(videos: Vec<QueuedVideo>) -> PlayerScreen {
+    PlayerScreen { videos, index: 0, start: None, pending: true, result: None }
+  }
+
+  fn video(&self) -> &QueuedVideo {
+    &self.videos[self.index]
+  }
+}
+
+impl Screen for PlayerScreen {
+  fn title(&self) -> String {
+    match self.videos.len() {
+      1 => format!("Playing {}", self.video().title),
+      count => format!("Playing {} ({}/{})", self.video().title, self.index + 1, count),
+    }
+  }
+
+  fn hints(&self) -> Vec<(Action, &'static str)> {
+    vec![(Action::Open, "play again"), (Action::Back, "back")]
+  }
+
+  fn draw(&mut self, frame: &mut Frame, area: Rect, _context: &Context) {
+    let theme = theme::current();
+    let title = &self.video().title;
+    let status = match &self.result {
+      None => Line::from(format!(" Starting {}...", title)).fg(theme.muted),
+      Some(Ok(())) => Line::from(format!(" Played {}", title)),
+      Some(Err(error)) => Line::from(format!(" Failed to play {}: {}", title, error)).fg(theme.error),
+    };
+    frame.render_widget(Paragraph::new(Text::from(vec![Line::default(), status])), area);
+  }
+
+  fn perform(&mut self, action: Action, _context: &mut Context) -> Option<Transition> {
+    match action {
+      Action::Open => self.pending = true,
+      _ => return None,
+    }
+    Some(Transition::Stay)
+  }
+
+  fn pending_playback(&mut self) -> Option<Playback> {
+    std::mem::take(&mut self.pending).then(|| Playback { id: self.video().id.clone(), title: self.video().title.clone(), start: self.start })
+  }
+
+  // The user browses on, the videos queued after it are left
+  fn playback_minimized(&mut self) -> Transition {
+    Transition::Pop
+  }
+
+  fn playback_ended(&mut self, result: std::io::Result<()>) {
+    // Playing it again starts from the beginning
+    self.start = None;
+    if result.is_ok() && self.index + 1 < self.videos.len() {
+      self.index += 1;
+      self.pending = true;
+    }
+    self.result = Some(result.map_err(|e| e.to_string()));
+  }
+}
@@ -0,0 +1,102 @@
+use api::models::PlaylistObject;
+use ratatui::crossterm::event::MouseEvent;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Stylize;
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::app::Context;
+use crate::event::{RequestId, Response};
+use crate::keymap::Action;
+use crate::screens::video::VideoScreen;
+use crate::screens::{Click, ListHits, Loading, Screen, Transition, PAGE};
+use crate::theme;
+
+// The videos of a playlist
+pub struct PlaylistScreen {
+  id: String,
+  playlist: Loading<PlaylistObject>,
+  videos: ListState,
+  hits: ListHits,
+}
+
+impl PlaylistScreen {
+  pub fn new(id: String, context: &mut Context) -> PlaylistScreen {
+    let playlist_id = id.clone();
+    let request = context.request(move |provider| provider.playlist(&playlist_id, 1).map(Response::Playlist));
+    PlaylistScreen { id, playlist: Loading::Pending(request), videos: ListState::default().with_selected(Some(0)), hits: ListHits::default() }
+  }
+}
+
+impl Screen for PlaylistScreen {
+  fn title(&self) -> String {
+    match &self.playlist {
+      Loading::Ready(playlist) => playlist.title.clone(),
+      _ => self.id.clone(),
+    }
+  }
+
+  fn hints(&self) -> Vec<(Action, &'static str)> {
+    vec![(Action::Open, "open"), (Action::Back, "back")]
+  }
+
+  fn draw(&mut self, frame: &mut Frame, area: Rect, context: &Context) {
+    let Some(playlist) = self.playlist.draw(frame, area, context) else {
+      return;
+    };
+    let theme = theme::current();
+    let [header, videos] = Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(area);
+    let header_text = Text::from(vec![
+      Line::from(playlist.title.as_str()).bold(),
+      Line::from(format!("{} · {} videos", playlist.author, playlist.videoCount)).fg(theme.muted),
+    ]);
+    frame.render_widget(Paragraph::new(header_text), header);
+
+    let entries: Vec<ListItem> = playlist.videos.iter().enumerate().map(|(index, video)| {
+      ListItem::new(Line::from(vec![Span::raw(format!("{:>4} ", index + 1)).fg(theme.muted), Span::raw(video.title.as_str())]))
+    }).collect();
+    self.hits.set(videos, &entries);
+    let list = List::new(entries)
+      .highlight_style(theme.selected())
+      .highlight_symbol("▌ ");
+    frame.render_stateful_widget(list, videos, &mut self.videos);
+  }
+
+  fn click(&mut self, mouse: MouseEvent, context: &mut Context) -> Option<Transition> {
+    let Loading::Ready(playlist) = &self.playlist else {
+      return None;
+    };
+    match self.hits.click(&mut self.videos, playlist.videos.len(), &mouse)? {
+      Click::Open => self.perform(Action::Open, context),
+      Click::Selected => Some(Transition::Stay),
+    }
+  }
+
+  fn perform(&mut self, action: Action, context: &mut Context) -> Option<Transition> {
+    match action {
+      Action::PreviousResult => self.videos.select_previous(),
+      Action::NextResult => self.videos.select_next(),
+      Action::FirstResult => self.videos.select_first(),
+      Action::LastResult => self.videos.select_last(),
+      Action::PreviousPage => self.videos.scroll_up_by(PAGE),
+      Action::NextPage => self.videos.scroll_down_by(PAGE),
+      Action::Open => {
+        if let (Loading::Ready(playlist), Some(index)) = (&self.playlist, self.videos.selected()) {
+          if let Some(video) = playlist.videos.get(index) {
+            return Some(Transition::Push(Box::new(VideoScreen::new(video.videoId.clone(), context))));
+          }
+        }
+      },
+      _ => return None,
+    }
+    Some(Transition::Stay)
+  }
+
+  fn handle_response(&mut self, request: RequestId, response: &std::io::Result<Response>, _context: &mut Context) {
+    self.playlist.resolve(request, response, |response| match response {
+      Response::Playlist(playlist) => Some(playlist.clone()),
+      _ => None,
+    });
+  }
+}
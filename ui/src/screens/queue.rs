@@ -0,0 +1,87 @@
+use ratatui::crossterm::event::MouseEvent;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::{List, ListItem, ListState};
+use ratatui::Frame;
+
+use crate::app::Context;
+use crate::keymap::{Action, Scope};
+use crate::screens::player::PlayerScreen;
+use crate::screens::{Click, ListHits, Screen, Transition};
+use crate::theme;
+
+// The videos queued to watch, played in order from the selected one
+pub struct QueueScreen {
+  list: ListState,
+  hits: ListHits,
+}
+
+impl QueueScreen {
+  pub fn new() -> QueueScreen {
+    QueueScreen { list: ListState::default().with_selected(Some(0)), hits: ListHits::default() }
+  }
+}
+
+impl Default for QueueScreen {
+  fn default() -> Self {
+    QueueScreen::new()
+  }
+}
+
+impl Screen for QueueScreen {
+  fn title(&self) -> String {
+    "Queue".to_string()
+  }
+
+  fn scope(&self) -> Scope {
+    Scope::Queue
+  }
+
+  fn hints(&self) -> Vec<(Action, &'static str)> {
+    vec![(Action::Open, "play from here"), (Action::Remove, "remove"), (Action::Back, "back")]
+  }
+
+  fn draw(&mut self, frame: &mut Frame, area: Rect, context: &Context) {
+    let theme = theme::current();
+    if context.queue.is_empty() {
+      frame.render_widget(Line::from(" The queue is empty, add videos with `a` on their screen").fg(theme.muted), area);
+      return;
+    }
+    let entries: Vec<ListItem> = context.queue.iter().enumerate().map(|(index, video)| ListItem::new(format!("{:>3}. {}", index + 1, video.title))).collect();
+    self.hits.set(area, &entries);
+    let list = List::new(entries)
+      .highlight_style(theme.selected())
+      .highlight_symbol("▌ ");
+    frame.render_stateful_widget(list, area, &mut self.list);
+  }
+
+  fn click(&mut self, mouse: MouseEvent, context: &mut Context) -> Option<Transition> {
+    match self.hits.click(&mut self.list, context.queue.len(), &mouse)? {
+      Click::Open => self.perform(Action::Open, context),
+      Click::Selected => Some(Transition::Stay),
+    }
+  }
+
+  fn perform(&mut self, action: Action, context: &mut Context) -> Option<Transition> {
+    let selected = self.list.selected().filter(|index| *index < context.queue.len());
+    match action {
+      Action::PreviousResult => self.list.select_previous(),
+      Action::NextResult => self.list.select_next(),
+      Action::FirstResult => self.list.select_first(),
+      Action::LastResult => self.list.select(context.queue.len().checked_sub(1)),
+      Action::Open => {
+        if let Some(index) = selected {
+          return Some(Transition::Push(Box::new(PlayerScreen::queue(context.queue[index..].to_vec()))));
+        }
+      },
+      Action::Remove => {
+        if let Some(index) = selected {
+          context.queue.remove(index);
+        }
+      },
+      _ => return None,
+    }
+    Some(Transition::Stay)
+  }
+}
@@ -0,0 +1,209 @@
+use api::fetcher::SearchParams;
+use api::models::{SearchItem, VideoObject};
+use ratatui::crossterm::event::MouseEvent;
+use ratatui::layout::Rect;
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, List, ListItem, ListState};
+use ratatui::Frame;
+
+use crate::app::Context;
+use crate::event::{RequestId, Response};
+use crate::{format, theme};
+use crate::screens::channel::ChannelScreen;
+use crate::screens::playlist::PlaylistScreen;
+use crate::screens::video::VideoScreen;
+use crate::screens::{Click, ListHits, Transition};
+
+// How close to the last result the selection gets before the next page is fetched
+static LOAD_AHEAD: usize = 5;
+
+// The results of a search, fetched a page at a time as the selection goes down
+pub struct Results {
+  params: SearchParams,
+  items: Vec<SearchItem>,
+  list: ListState,
+  // The last page asked for, from 1
+  page: u32,
+  pending: Option<RequestId>,
+  // An empty page came back, there is nothing more
+  exhausted: bool,
+  error: Option<String>,
+  hits: ListHits,
+}
+
+// The screen showing a result
+pub fn open(item: &SearchItem, context: &mut Context) -> Transition {
+  match item {
+    SearchItem::Video(video) => Transition::Push(Box::new(VideoScreen::new(video.videoId.clone(), context))),
+    SearchItem::Channel(channel) => Transition::Push(Box::new(ChannelScreen::new(channel.authorId.clone(), context))),
+    SearchItem::Playlist(playlist) => Transition::Push(Box::new(PlaylistScreen::new(playlist.playlistId.clone(), context))),
+    SearchItem::Other => Transition::Stay,
+  }
+}
+
+// What a video is, after its title: `author · 3:32 · 1.2M views · 2 years ago`
+fn video_details(video: &VideoObject) -> String {
+  let mut details = vec![video.author.clone()];
+  match video.liveNow {
+    true => details.push("LIVE".to_string()),
+    false if video.lengthSeconds > 0 => details.push(format::duration(video.lengthSeconds as i64)),
+    false => {},
+  }
+  details.push(format!("{} views", format::count(video.viewCount)));
+  if !video.publishedText.is_empty() {
+    details.push(video.publishedText.clone());
+  }
+  details.join(" · ")
+}
+
+// A video on two lines, like the results
+pub fn video_entry(video: &VideoObject) -> ListItem<'_> {
+  ListItem::new(Text::from(vec![Line::from(Span::raw(video.title.as_str()).bold()), Line::from(video_details(video)).fg(theme::current().muted)]))
+}
+
+// A result on two lines: its title, and what it is
+fn entry(item: &SearchItem) -> ListItem<'_> {
+  let (title, details) = match item {
+    SearchItem::Video(video) => return video_entry(video),
+    SearchItem::Channel(channel) => (channel.author.as_str(), format!("channel · {} subscribers · {} videos", format::count(channel.subCount), channel.videoCount)),
+    SearchItem::Playlist(playlist) => (playlist.title.as_str(), format!("playlist · {} · {} videos", playlist.author, playlist.videoCount)),
+    SearchItem::Other => ("", String::new()),
+  };
+  ListItem::new(Text::from(vec![Line::from(Span::raw(title).bold()), Line::from(details).fg(theme::current().muted)]))
+}
+
+impl Results {
+  // Search with `params`, from its first page
+  pub fn new(params: SearchParams, context: &mut Context) -> Results {
+    let mut results = Results {
+      params,
+      items: vec![],
+      list: ListState::default().with_selected(Some(0)),
+      page: 0,
+      pending: None,
+      exhausted: false,
+      error: None,
+      hits: ListHits::default(),
+    };
+    results.load_more(context);
+    results
+  }
+
+  pub fn query(&self) -> &str {
+    &self.params.q
+  }
+
+  fn load_more(&mut self, context: &mut Context) {
+    if self.pending.is_some() || self.exhausted || self.error.is_some() {
+      return;
+    }
+    let params = SearchParams { page: Some(self.page + 1), ..self.params.clone() };
+    self.pending = Some(context.request(move |provider| provider.search(params).map(Response::Search)));
+  }
+
+  // Fetch the next page when the selection nears the last result
+  fn load_near_bottom(&mut self, context: &mut Context) {
+    if self.list.selected().unwrap_or(0) + LOAD_AHEAD >= self.items.len() {
+      self.load_more(context);
+    }
+  }
+
+  pub fn select_next(&mut self, context: &mut Context) {
+    let index = self.list.selected().map_or(0, |index| index + 1);
+    self.list.select(Some(index.min(self.items.len().saturating_sub(1))));
+    self.load_near_bottom(context);
+  }
+
+  pub fn select_first(&mut self) {
+    if !self.items.is_empty() {
+      self.list.select_first();
+    }
+  }
+
+  // The last result fetched, the next page comes after it
+  pub fn select_last(&mut self, context: &mut Context) {
+    if !self.items.is_empty() {
+      self.list.select(Some(self.items.len() - 1));
+      self.load_near_bottom(context);
+    }
+  }
+
+  pub fn select_previous(&mut self) {
+    if !self.items.is_empty() {
+      self.list.select_previous();
+    }
+  }
+
+  // Select the clicked result, or tell it is to be opened when it was selected
+  pub fn click(&mut self, mouse: &MouseEvent, context: &mut Context) -> Option<Click> {
+    let click = self.hits.click(&mut self.list, self.items.len(), mouse)?;
+    self.load_near_bottom(context);
+    Some(click)
+  }
+
+  pub fn selected(&self) -> Option<&SearchItem> {
+    self.items.get(self.list.selected()?)
+  }
+
+  pub fn handle_response(&mut self, request: RequestId, response: &std::io::Result<Response>, context: &mut Context) {
+    if self.pending != Some(request) {
+      return;
+    }
+    self.pending = None;
+    match response {
+      Ok(Response::Search(items)) => {
+        self.page += 1;
+        let items: Vec<SearchItem> = items.iter().filter(|item| !matches!(item, SearchItem::Other)).cloned().collect();
+        self.exhausted = items.is_empty();
+        self.items.extend(items);
+        // A short page may leave the selection near the bottom still
+        self.load_near_bottom(context);
+      },
+      Ok(_) => self.error = Some("Unexpected response".to_string()),
+      Err(e) => self.error = Some(e.to_string()),
+    }
+  }
+
+  pub fn draw(&mut self, frame: &mut Frame, area: Rect, context: &Context, focused: bool) {
+    let theme = theme::current();
+    let title = match self.page {
+      0 => format!(" Results for \"{}\" ", self.params.q),
+      _ => format!(" Results for \"{}\" · {} ", self.params.q, self.items.len()),
+    };
+    let block = Block::bordered().title(title).border_style(match focused {
+      true => Style::new().fg(theme.accent),
+      false => Style::new().fg(theme.muted),
+    });
+
+    // What comes after the results: more of them, an error, or the end
+    let footer = if self.pending.is_some() {
+      Some(Line::from(format!("{} Loading...", context.spinner())).fg(theme.muted))
+    } else if let Some(error) = &self.error {
+      Some(Line::from(error.clone()).fg(theme.error))
+    } else if self.exhausted && self.items.is_empty() {
+      Some(Line::from("No results").fg(theme.muted))
+    } else if self.exhausted {
+      Some(Line::from("No more results").fg(theme.muted))
+    } else {
+      None
+    };
+    // The footer is never selected
+    match self.items.is_empty() {
+      true => self.list.select(None),
+      false => self.list.select(Some(self.list.selected().unwrap_or(0).min(self.items.len() - 1))),
+    }
+    let mut entries: Vec<ListItem> = self.items.iter().map(entry).collect();
+    entries.extend(footer.map(ListItem::new));
+    self.hits.set(block.inner(area), &entries);
+
+    let list = List::new(entries)
+      .block(block)
+      .highlight_style(match focused {
+        true => theme.selected(),
+        false => Style::new(),
+      })
+      .highlight_symbol("▌ ");
+    frame.render_stateful_widget(list, area, &mut self.list);
+  }
+}
@@ -0,0 +1,465 @@
+use api::fetcher::SearchParams;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::app::Context;
+use crate::event::{RequestId, Response};
+use crate::keymap::{Action, Scope};
+use crate::screens::results::{self, Results};
+use crate::screens::{Click, ListHits, Screen, Transition, PAGE};
+use crate::theme;
+
+// The choices of the filter panel as labels and values, the first one leaves it to the server
+static SORT_BY: &[(&str, &str)] = &[("relevance", ""), ("rating", "rating"), ("upload date", "upload_date"), ("views", "view_count")];
+static DATES: &[(&str, &str)] = &[("any time", ""), ("last hour", "hour"), ("today", "today"), ("this week", "week"), ("this month", "month"), ("this year", "year")];
+static DURATIONS: &[(&str, &str)] = &[("any", ""), ("under 4 min", "short"), ("4-20 min", "medium"), ("over 20 min", "long")];
+static TYPES: &[(&str, &str)] = &[("all", ""), ("videos", "video"), ("channels", "channel"), ("playlists", "playlist")];
+static FEATURES: &[(&str, &str)] = &[
+  ("HD", "hd"), ("subtitles", "subtitles"), ("creative commons", "creative_commons"), ("3D", "3d"), ("live", "live"),
+  ("4K", "4k"), ("360°", "360"), ("location", "location"), ("HDR", "hdr"), ("VR180", "vr180"),
+];
+
+// Width of the filter panel, it is shown next to the results when they leave this much room
+static FILTERS_WIDTH: u16 = 30;
+static RESULTS_MIN_WIDTH: u16 = 50;
+
+// Most suggestions listed under the query
+static MAX_SUGGESTIONS: usize = 8;
+
+// What the keys go to
+#[derive(Clone, Copy, PartialEq)]
+enum Focus {
+  Query,
+  Filters,
+  Results,
+}
+
+// The filters of the search, as indexes in the choices above
+#[derive(Default)]
+struct Filters {
+  sort_by: usize,
+  date: usize,
+  duration: usize,
+  ctype: usize,
+  features: Vec<bool>,
+  // The row selected in the panel: the 4 choices then the features
+  list: ListState,
+  hits: ListHits,
+}
+
+impl Filters {
+  fn new() -> Filters {
+    Filters { features: vec![false; FEATURES.len()], list: ListState::default().with_selected(Some(0)), ..Default::default() }
+  }
+
+  // The choice on `row` and its index, `None` for the features
+  fn choice(&mut self, row: usize) -> Option<(&'static [(&'static str, &'static str)], &mut usize)> {
+    match row {
+      0 => Some((SORT_BY, &mut self.sort_by)),
+      1 => Some((DATES, &mut self.date)),
+      2 => Some((DURATIONS, &mut self.duration)),
+      3 => Some((TYPES, &mut self.ctype)),
+      _ => None,
+    }
+  }
+
+  // Change the selected row, a step through its choices or a feature toggled
+  fn change(&mut self, step: isize) {
+    let row = self.list.selected().unwrap_or(0);
+    match self.choice(row) {
+      Some((choices, index)) => {
+        *index = (*index as isize + step).rem_euclid(choices.len() as isize) as usize;
+      },
+      None => {
+        let feature = &mut self.features[row - 4];
+        *feature = !*feature;
+      },
+    }
+  }
+
+  fn rows(&self) -> usize {
+    4 + FEATURES.len()
+  }
+
+  // The parameters of a search for `query` with these filters
+  fn params(&self, query: &str) -> SearchParams {
+    let value = |choices: &[(&str, &str)], index: usize| Some(choices[index].1.to_string()).filter(|value| !value.is_empty());
+    let features: Vec<String> = FEATURES.iter().zip(&self.features)
+      .filter(|(_, on)| **on)
+      .map(|((_, value), _)| value.to_string())
+      .collect();
+    SearchParams {
+      q: query.to_string(),
+      page: None,
+      sort_by: value(SORT_BY, self.sort_by),
+      date: value(DATES, self.date),
+      duration: value(DURATIONS, self.duration),
+      ctype: value(TYPES, self.ctype),
+      features: Some(features).filter(|features| !features.is_empty()),
+      region: None,
+    }
+  }
+
+  fn draw(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+    let theme = theme::current();
+    let names = ["Sort by", "Date", "Duration", "Type"];
+    let mut rows: Vec<ListItem> = vec![];
+    for (row, name) in names.iter().enumerate() {
+      let (choices, index) = self.choice(row).expect("The first rows are choices");
+      let value = choices[*index].0;
+      let style = if *index == 0 { Style::new().fg(theme.muted) } else { Style::new().fg(theme.accent) };
+      rows.push(ListItem::new(Line::from(vec![Span::raw(format!("{:<9}", name)), Span::styled(format!("‹ {} ›", value), style)])));
+    }
+    for ((label, _), on) in FEATURES.iter().zip(&self.features) {
+      let line = match on {
+        true => Line::from(vec![Span::raw("[x] ").fg(theme.accent), Span::raw(*label)]),
+        false => Line::from(vec![Span::raw("[ ] ").fg(theme.muted), Span::raw(*label)]),
+      };
+      rows.push(ListItem::new(line));
+    }
+    let block = Block::bordered().title(" Filters ").border_style(match focused {
+      true => Style::new().fg(theme.accent),
+      false => Style::new().fg(theme.muted),
+    });
+    self.hits.set(block.inner(area), &rows);
+    let list = List::new(rows).block(block).highlight_style(match focused {
+      true => theme.selected(),
+      false => Style::new(),
+    });
+    frame.render_stateful_widget(list, area, &mut self.list);
+  }
+}
+
+// Where a search is typed, its filters picked and its results browsed
+pub struct SearchScreen {
+  query: String,
+  focus: Focus,
+  filters: Filters,
+  results: Option<Results>,
+  suggestions: Vec<String>,
+  // The suggestion picked with the arrows, the typed query otherwise
+  suggestion: Option<usize>,
+  // The last suggestions asked for, the older ones are dropped
+  suggestions_request: Option<RequestId>,
+  // Where the query was drawn, the suggestions under it, and whether the results were drawn
+  query_area: Rect,
+  suggestion_hits: ListHits,
+  results_shown: bool,
+}
+
+impl SearchScreen {
+  pub fn new() -> SearchScreen {
+    SearchScreen {
+      query: String::new(),
+      focus: Focus::Query,
+      filters: Filters::new(),
+      results: None,
+      suggestions: vec![],
+      suggestion: None,
+      suggestions_request: None,
+      query_area: Rect::default(),
+      suggestion_hits: ListHits::default(),
+      results_shown: false,
+    }
+  }
+
+  // Search `query` right away
+  pub fn with_query(query: String, context: &mut Context) -> SearchScreen {
+    let mut screen = SearchScreen { query, ..SearchScreen::new() };
+    screen.search(context);
+    screen
+  }
+
+  fn edited(&mut self, context: &mut Context) {
+    self.suggestion = None;
+    let query = self.query.trim().to_string();
+    if query.is_empty() {
+      self.suggestions.clear();
+      self.suggestions_request = None;
+      return;
+    }
+    self.suggestions_request = Some(context.request(move |provider| provider.suggestions(&query).map(Response::Suggestions)));
+  }
+
+  // Search what is typed, or the picked suggestion
+  fn search(&mut self, context: &mut Context) {
+    if let Some(suggestion) = self.suggestion.and_then(|index| self.suggestions.get(index)) {
+      self.query = suggestion.clone();
+    }
+    let query = self.query.trim().to_string();
+    if query.is_empty() {
+      return;
+    }
+    self.suggestions.clear();
+    self.suggestion = None;
+    self.suggestions_request = None;
+    context.library.add_search(&query);
+    self.results = Some(Results::new(self.filters.params(&query), context));
+    self.focus = Focus::Results;
+  }
+
+  // Search again with the new filters, for the query of the results shown
+  fn refresh(&mut self, context: &mut Context) {
+    if let Some(results) = &self.results {
+      let query = results.query().to_string();
+      self.results = Some(Results::new(self.filters.params(&query), context));
+    }
+  }
+
+  // The next pane or the previous one, skipping the results until there are some
+  fn cycle_focus(&mut self, forward: bool) {
+    let has_results = self.results.is_some();
+    self.focus = match (self.focus, forward) {
+      (Focus::Query, true) | (Focus::Results, false) => Focus::Filters,
+      (Focus::Filters, true) if has_results => Focus::Results,
+      (Focus::Query, false) if has_results => Focus::Results,
+      (Focus::Query, false) | (Focus::Filters, _) | (Focus::Results, true) => Focus::Query,
+    };
+    self.suggestions.clear();
+  }
+
+  // Edit the query with `key` when it is typed rather than an action, i.e. without control
+  // or alt held
+  fn type_key(&mut self, key: KeyEvent, context: &mut Context) -> bool {
+    match key.code {
+      KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => self.query.push(c),
+      KeyCode::Backspace if !self.query.is_empty() => {
+        self.query.pop();
+      },
+      _ => return false,
+    }
+    self.edited(context);
+    true
+  }
+
+  fn perform_on_query(&mut self, action: Action, context: &mut Context) -> Option<Transition> {
+    match action {
+      Action::PreviousResult if !self.suggestions.is_empty() => {
+        self.suggestion = self.suggestion.and_then(|index| index.checked_sub(1));
+      },
+      Action::NextResult if !self.suggestions.is_empty() => {
+        let last = self.suggestions.len().min(MAX_SUGGESTIONS) - 1;
+        self.suggestion = Some(self.suggestion.map_or(0, |index| (index + 1).min(last)));
+      },
+      Action::NextResult if self.results.is_some() => self.focus = Focus::Results,
+      Action::Back if !self.suggestions.is_empty() => {
+        self.suggestions.clear();
+        self.suggestion = None;
+        self.suggestions_request = None;
+      },
+      Action::Open => self.search(context),
+      _ => return None,
+    }
+    Some(Transition::Stay)
+  }
+
+  fn perform_on_filters(&mut self, action: Action, context: &mut Context) -> Option<Transition> {
+    let last = self.filters.rows() - 1;
+    match action {
+      Action::PreviousResult => self.filters.list.select_previous(),
+      Action::NextResult => {
+        let row = self.filters.list.selected().map_or(0, |row| (row + 1).min(last));
+        self.filters.list.select(Some(row));
+      },
+      Action::FirstResult => self.filters.list.select_first(),
+      Action::LastResult => self.filters.list.select(Some(last)),
+      Action::PreviousChoice => {
+        self.filters.change(-1);
+        self.refresh(context);
+      },
+      Action::NextChoice | Action::Open => {
+        self.filters.change(1);
+        self.refresh(context);
+      },
+      Action::Back => self.focus = Focus::Query,
+      _ => return None,
+    }
+    Some(Transition::Stay)
+  }
+
+  fn perform_on_results(&mut self, action: Action, context: &mut Context) -> Option<Transition> {
+    let results = self.results.as_mut()?;
+    match action {
+      Action::PreviousResult => results.select_previous(),
+      Action::NextResult => results.select_next(context),
+      Action::FirstResult => results.select_first(),
+      Action::LastResult => results.select_last(context),
+      Action::NextPage => (0..PAGE).for_each(|_| results.select_next(context)),
+      Action::PreviousPage => (0..PAGE).for_each(|_| results.select_previous()),
+      Action::Open => {
+        if let Some(item) = results.selected() {
+          return Some(results::open(item, context));
+        }
+      },
+      Action::FocusQuery => self.focus = Focus::Query,
+      Action::FocusFilters => self.focus = Focus::Filters,
+      _ => return None,
+    }
+    Some(Transition::Stay)
+  }
+
+  fn draw_query(&self, frame: &mut Frame, area: Rect) {
+    let theme = theme::current();
+    let block = Block::bordered().title(" Search YouTube ").border_style(match self.focus {
+      Focus::Query => Style::new().fg(theme.accent),
+      _ => Style::new().fg(theme.muted),
+    });
+    let inner = block.inner(area);
+    frame.render_widget(Paragraph::new(Line::from(self.query.as_str())).block(block), area);
+    if self.query.is_empty() {
+      frame.render_widget(Line::from("Videos, channels or playlists").fg(theme.muted), inner);
+    }
+    if self.focus == Focus::Query {
+      let cursor = inner.x + (self.query.chars().count() as u16).min(inner.width.saturating_sub(1));
+      frame.set_cursor_position(Position::new(cursor, inner.y));
+    }
+  }
+
+  // The suggestions drop down from the query, over what is under it
+  fn draw_suggestions(&mut self, frame: &mut Frame, query: Rect, body: Rect) {
+    self.suggestion_hits = ListHits::default();
+    if self.focus != Focus::Query || self.suggestions.is_empty() {
+      return;
+    }
+    let theme = theme::current();
+    let suggestions = &self.suggestions[..self.suggestions.len().min(MAX_SUGGESTIONS)];
+    let height = (suggestions.len() as u16 + 2).min(body.height);
+    let area = Rect::new(query.x, body.y, query.width, height);
+    let entries: Vec<ListItem> = suggestions.iter().map(|suggestion| ListItem::new(suggestion.as_str())).collect();
+    let block = Block::bordered().border_style(Style::new().fg(theme.muted));
+    self.suggestion_hits.set(block.inner(area), &entries);
+    let list = List::new(entries).block(block).highlight_style(theme.inverse());
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut ListState::default().with_selected(self.suggestion));
+  }
+}
+
+impl Default for SearchScreen {
+  fn default() -> Self {
+    SearchScreen::new()
+  }
+}
+
+impl Screen for SearchScreen {
+  fn title(&self) -> String {
+    match &self.results {
+      Some(results) => format!("Search \"{}\"", results.query()),
+      None => "Search".to_string(),
+    }
+  }
+
+  fn scope(&self) -> Scope {
+    match self.focus {
+      Focus::Filters => Scope::Filters,
+      _ => Scope::Search,
+    }
+  }
+
+  fn hints(&self) -> Vec<(Action, &'static str)> {
+    match self.focus {
+      Focus::Query => vec![(Action::Open, "search"), (Action::NextResult, "suggestions"), (Action::NextTab, "filters"), (Action::Back, "back")],
+      Focus::Filters => vec![(Action::PreviousChoice, "previous"), (Action::NextChoice, "next"), (Action::NextTab, "results"), (Action::Back, "query")],
+      Focus::Results => vec![(Action::Open, "open"), (Action::FocusQuery, "query"), (Action::FocusFilters, "filters"), (Action::Back, "back")],
+    }
+  }
+
+  fn draw(&mut self, frame: &mut Frame, area: Rect, context: &Context) {
+    let [query, body] = Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(area);
+    self.draw_query(frame, query);
+    self.query_area = query;
+    self.filters.hits = ListHits::default();
+    self.results_shown = false;
+
+    // The filters beside the results, or instead of them while picked on a narrow terminal
+    let side_by_side = body.width >= FILTERS_WIDTH + RESULTS_MIN_WIDTH;
+    if side_by_side {
+      let [results, filters] = Layout::horizontal([Constraint::Min(0), Constraint::Length(FILTERS_WIDTH)]).areas(body);
+      self.filters.draw(frame, filters, self.focus == Focus::Filters);
+      if let Some(list) = self.results.as_mut() {
+        list.draw(frame, results, context, self.focus == Focus::Results);
+        self.results_shown = true;
+      }
+    } else if self.focus == Focus::Filters || self.results.is_none() {
+      self.filters.draw(frame, body, self.focus == Focus::Filters);
+    } else if let Some(list) = self.results.as_mut() {
+      list.draw(frame, body, context, self.focus == Focus::Results);
+      self.results_shown = true;
+    }
+    self.draw_suggestions(frame, query, body);
+  }
+
+  fn handle_key(&mut self, key: KeyEvent, context: &mut Context) -> Option<Transition> {
+    if self.focus == Focus::Query && self.type_key(key, context) {
+      return Some(Transition::Stay);
+    }
+    let action = context.action(self.scope(), &key)?;
+    self.perform(action, context)
+  }
+
+  fn perform(&mut self, action: Action, context: &mut Context) -> Option<Transition> {
+    match action {
+      Action::NextTab => self.cycle_focus(true),
+      Action::PreviousTab => self.cycle_focus(false),
+      _ => {
+        return match self.focus {
+          Focus::Query => self.perform_on_query(action, context),
+          Focus::Filters => self.perform_on_filters(action, context),
+          Focus::Results => self.perform_on_results(action, context),
+        }
+      },
+    }
+    Some(Transition::Stay)
+  }
+
+  // A click focuses the pane under it: a suggestion is searched at once, a filter changes once selected
+  // and a result opens once selected
+  fn click(&mut self, mouse: MouseEvent, context: &mut Context) -> Option<Transition> {
+    let suggestions = self.suggestions.len().min(MAX_SUGGESTIONS);
+    let mut shown = ListState::default().with_selected(self.suggestion);
+    if self.suggestion_hits.click(&mut shown, suggestions, &mouse).is_some() {
+      self.suggestion = shown.selected();
+      self.search(context);
+      return Some(Transition::Stay);
+    }
+    if self.query_area.contains(Position::new(mouse.column, mouse.row)) {
+      self.focus = Focus::Query;
+      return Some(Transition::Stay);
+    }
+    let rows = self.filters.rows();
+    if let Some(click) = self.filters.hits.click(&mut self.filters.list, rows, &mouse) {
+      self.focus = Focus::Filters;
+      self.suggestions.clear();
+      if click == Click::Open {
+        return self.perform_on_filters(Action::Open, context);
+      }
+      return Some(Transition::Stay);
+    }
+    let results = self.results.as_mut().filter(|_| self.results_shown)?;
+    let click = results.click(&mouse, context)?;
+    self.focus = Focus::Results;
+    self.suggestions.clear();
+    match click {
+      Click::Open => self.perform_on_results(Action::Open, context),
+      Click::Selected => Some(Transition::Stay),
+    }
+  }
+
+  fn handle_response(&mut self, request: RequestId, response: &std::io::Result<Response>, context: &mut Context) {
+    if self.suggestions_request == Some(request) {
+      self.suggestions_request = None;
+      match response {
+        Ok(Response::Suggestions(suggestions)) => self.suggestions = suggestions.clone(),
+        // Searching works without them
+        _ => self.suggestions.clear(),
+      }
+      self.suggestion = None;
+    }
+    if let Some(results) = self.results.as_mut() {
+      results.handle_response(request, response, context);
+    }
+  }
+}
@@ -0,0 +1,39 @@
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::Stylize;
+use ratatui::widgets::{Row, Table};
+use ratatui::Frame;
+
+use crate::app::Context;
+use crate::keymap::Action;
+use crate::screens::{Screen, Transition};
+
+// What the client is set up with
+pub struct SettingsScreen {
+  settings: Vec<(String, String)>,
+}
+
+impl SettingsScreen {
+  pub fn new(context: &mut Context) -> SettingsScreen {
+    SettingsScreen { settings: context.provider().settings() }
+  }
+}
+
+impl Screen for SettingsScreen {
+  fn title(&self) -> String {
+    "Settings".to_string()
+  }
+
+  fn hints(&self) -> Vec<(Action, &'static str)> {
+    vec![(Action::Back, "back")]
+  }
+
+  fn draw(&mut self, frame: &mut Frame, area: Rect, _context: &Context) {
+    let width = self.settings.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0) as u16;
+    let rows = self.settings.iter().map(|(name, value)| Row::new(vec![name.clone().bold(), value.clone().into()]));
+    frame.render_widget(Table::new(rows, [Constraint::Length(width + 2), Constraint::Fill(1)]), area);
+  }
+
+  fn perform(&mut self, _action: Action, _context: &mut Context) -> Option<Transition> {
+    None
+  }
+}
@@ -0,0 +1,423 @@
+use std::time::Duration;
+
+use api::models::{FormatObject, VideoObject};
+use ratatui::crossterm::event::MouseEvent;
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph, Row, Table, TableState, Tabs, Wrap};
+use ratatui::Frame;
+use youtube::models::video::VideoInfo;
+
+use crate::app::{Context, QueuedVideo};
+use crate::event::{RequestId, Response};
+use crate::keymap::{Action, Scope};
+use crate::screens::channel::ChannelScreen;
+use crate::screens::comments::CommentsPane;
+use crate::screens::player::PlayerScreen;
+use crate::screens::results::video_entry;
+use crate::screens::{Click, ListHits, Loading, Screen, Transition, PAGE};
+use crate::{format, get_logger, text, theme};
+
+// What the body of the screen shows
+#[derive(Clone, Copy, PartialEq)]
+enum Tab {
+  Description,
+  Formats,
+  Recommended,
+  Comments,
+}
+
+static TABS: [Tab; 4] = [Tab::Description, Tab::Formats, Tab::Recommended, Tab::Comments];
+
+impl Tab {
+  fn name(&self) -> &'static str {
+    match self {
+      Tab::Description => "Description",
+      Tab::Formats => "Formats",
+      Tab::Recommended => "Recommended",
+      Tab::Comments => "Comments",
+    }
+  }
+}
+
+// A video before it is played: what it is, the streams it comes in, what to watch next and
+// what people say of it
+pub struct VideoScreen {
+  id: String,
+  video: Loading<Box<VideoObject>>,
+  // The tags and the likes, from the YouTube API. The screen works without them.
+  info: Option<VideoInfo>,
+  info_request: RequestId,
+  download_request: Option<RequestId>,
+  tab: Tab,
+  // Lines of the description scrolled past
+  scroll: u16,
+  // The timestamp of the description picked to play from
+  timestamp: Option<usize>,
+  formats: TableState,
+  recommended: ListState,
+  recommended_hits: ListHits,
+  comments: CommentsPane,
+  // Where the titles of the tabs were drawn, and how wide each one is
+  tabs_area: Rect,
+  tab_widths: Vec<u16>,
+}
+
+// `12.3 MiB` from the size Invidious sends as a string
+fn format_size(bytes: &str) -> String {
+  match bytes.parse::<u64>() {
+    Ok(bytes) => format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0)),
+    Err(_) => String::new(),
+  }
+}
+
+// A row of the formats table
+fn format_row(format: &FormatObject) -> Row<'_> {
+  // `video/mp4; codecs="avc1.4d401e"`
+  let kind = format.ctype.split(';').next().unwrap_or_default();
+  let quality = match format.qualityLabel.as_str() {
+    "" => format.bitrate.parse::<u64>().map(|bitrate| format!("{}k", bitrate / 1000)).unwrap_or_default(),
+    label => label.to_string(),
+  };
+  Row::new(vec![
+    format.itag.clone(),
+    kind.to_string(),
+    quality,
+    format.resolution.clone(),
+    format.encoding.clone(),
+    format_size(&format.clen),
+  ])
+}
+
+fn draw_description(frame: &mut Frame, area: Rect, video: &VideoObject, scroll: u16, timestamp: Option<usize>) {
+  let mut first = 0;
+  let lines: Vec<Line> = video.description.lines().map(|line| {
+    let highlighted = text::highlight_timestamps(line, first, timestamp);
+    first += text::timestamps(line).len();
+    highlighted
+  }).collect();
+  frame.render_widget(Paragraph::new(Text::from(lines)).wrap(Wrap { trim: false }).scroll((scroll, 0)), area);
+}
+
+fn draw_formats(frame: &mut Frame, area: Rect, formats: &[FormatObject], state: &mut TableState) {
+  let theme = theme::current();
+  if formats.is_empty() {
+    frame.render_widget(Line::from("No formats listed").fg(theme.muted), area);
+    return;
+  }
+  let widths = [Constraint::Length(5), Constraint::Length(11), Constraint::Length(8), Constraint::Length(10), Constraint::Length(6), Constraint::Min(10)];
+  let header = Row::new(vec!["itag", "type", "quality", "resolution", "codec", "size"]).bold();
+  let table = Table::new(formats.iter().map(format_row), widths)
+    .header(header)
+    .row_highlight_style(theme.selected());
+  frame.render_stateful_widget(table, area, state);
+}
+
+fn draw_recommended(frame: &mut Frame, area: Rect, videos: &[VideoObject], state: &mut ListState, hits: &mut ListHits) {
+  let theme = theme::current();
+  if videos.is_empty() {
+    frame.render_widget(Line::from("No recommended videos").fg(theme.muted), area);
+    return;
+  }
+  let entries: Vec<ListItem> = videos.iter().map(video_entry).collect();
+  hits.set(area, &entries);
+  let list = List::new(entries)
+    .highlight_style(theme.selected())
+    .highlight_symbol("▌ ");
+  frame.render_stateful_widget(list, area, state);
+}
+
+impl VideoScreen {
+  pub fn new(id: String, context: &mut Context) -> VideoScreen {
+    let video_id = id.clone();
+    let request = context.request(move |provider| provider.video(&video_id).map(|video| Response::Video(Box::new(video))));
+    let video_id = id.clone();
+    let info_request = context.request(move |provider| provider.info(&video_id).map(Response::Info));
+    VideoScreen {
+      comments: CommentsPane::new(id.clone()),
+      id,
+      video: Loading::Pending(request),
+      info: None,
+      info_request,
+      download_request: None,
+      tab: Tab::Description,
+      scroll: 0,
+      timestamp: None,
+      formats: TableState::default().with_selected(Some(0)),
+      recommended: ListState::default().with_selected(Some(0)),
+      recommended_hits: ListHits::default(),
+      tabs_area: Rect::default(),
+      tab_widths: vec![],
+    }
+  }
+
+  // The timestamps of the description, with the line each one is on
+  fn timestamps(&self) -> Vec<(usize, Duration)> {
+    let Loading::Ready(video) = &self.video else {
+      return vec![];
+    };
+    video.description.lines().enumerate()
+      .flat_map(|(line, text)| text::timestamps(text).into_iter().map(move |(_, position)| (line, position)))
+      .collect()
+  }
+
+  // Select the next or the previous timestamp, and scroll to it
+  fn select_timestamp(&mut self, next: bool) {
+    let timestamps = self.timestamps();
+    if timestamps.is_empty() {
+      return;
+    }
+    let index = match (self.timestamp, next) {
+      (None, true) => 0,
+      (None, false) => timestamps.len() - 1,
+      (Some(index), true) => (index + 1) % timestamps.len(),
+      (Some(index), false) => index.checked_sub(1).unwrap_or(timestamps.len() - 1),
+    };
+    self.timestamp = Some(index);
+    self.scroll = timestamps[index].0.saturating_sub(2) as u16;
+  }
+
+  // Show `tab`, fetching the comments the first time they are shown
+  fn switch(&mut self, tab: Tab, context: &mut Context) {
+    self.tab = tab;
+    if tab == Tab::Comments {
+      self.comments.start(context);
+    }
+  }
+
+  fn play(&self) -> Transition {
+    let title = self.title();
+    let start = match self.tab {
+      Tab::Comments => self.comments.timestamp(),
+      _ => self.timestamp.and_then(|index| self.timestamps().get(index).copied()).map(|(_, start)| start),
+    };
+    match start {
+      Some(start) => Transition::Push(Box::new(PlayerScreen::at(self.id.clone(), title, start))),
+      None => Transition::Push(Box::new(PlayerScreen::new(self.id.clone(), title))),
+    }
+  }
+
+  fn header(&self, video: &VideoObject) -> Text<'static> {
+    let theme = theme::current();
+    let mut author = vec![Span::raw(video.author.clone()).bold()];
+    if video.authorVerified {
+      author.push(Span::raw(" ✓").fg(theme.highlight));
+    }
+    if !video.subCountText.is_empty() {
+      author.push(Span::raw(format!(" · {} subscribers", video.subCountText)).fg(theme.muted));
+    }
+
+    let likes = match &self.info {
+      Some(info) if info.likes > 0 => info.likes as i64,
+      _ => video.likeCount,
+    };
+    let published = match &self.info {
+      Some(info) if info.upload_date.len() >= 10 => info.upload_date[..10].to_string(),
+      _ => format::date(video.published),
+    };
+    let mut stats = vec![format!("{} views", format::count(video.viewCount)), format!("{} likes", format::count(likes))];
+    match video.liveNow {
+      true => stats.push("LIVE".to_string()),
+      false => stats.push(format::duration(video.lengthSeconds as i64)),
+    }
+    stats.push(match video.publishedText.is_empty() {
+      true => published,
+      false => format!("{} ({})", published, video.publishedText),
+    });
+
+    let tags = match &self.info {
+      Some(info) if !info.tags.is_empty() => &info.tags,
+      _ => &video.keywords,
+    };
+    let tags: Vec<String> = tags.iter().map(|tag| format!("#{}", tag.replace(' ', ""))).collect();
+
+    Text::from(vec![
+      Line::from(video.title.clone()).bold(),
+      Line::from(author),
+      Line::from(stats.join(" · ")).fg(theme.muted),
+      Line::from(tags.join(" ")).fg(theme.link),
+    ])
+  }
+
+  fn recommended(&self) -> Option<&VideoObject> {
+    let Loading::Ready(video) = &self.video else {
+      return None;
+    };
+    video.recommendedVideos.as_ref()?.get(self.recommended.selected()?)
+  }
+}
+
+impl Screen for VideoScreen {
+  fn title(&self) -> String {
+    match &self.video {
+      Loading::Ready(video) => video.title.clone(),
+      _ => self.id.clone(),
+    }
+  }
+
+  fn scope(&self) -> Scope {
+    match self.tab {
+      Tab::Comments => Scope::Comments,
+      _ => Scope::Video,
+    }
+  }
+
+  fn hints(&self) -> Vec<(Action, &'static str)> {
+    let mut hints = match self.tab {
+      Tab::Description => vec![(Action::Open, "play"), (Action::NextTimestamp, "timestamps")],
+      Tab::Formats => vec![(Action::Play, "play")],
+      Tab::Recommended => vec![(Action::Open, "open"), (Action::Play, "play")],
+      Tab::Comments => self.comments.hints(),
+    };
+    if self.tab == Tab::Comments {
+      hints.push((Action::Play, "play"));
+    } else {
+      hints.push((Action::OpenComments, "comments"));
+    }
+    hints.extend([(Action::Download, "download"), (Action::AddToQueue, "queue"), (Action::OpenChannel, "channel"), (Action::NextTab, "next tab")]);
+    hints
+  }
+
+  fn draw(&mut self, frame: &mut Frame, area: Rect, context: &Context) {
+    if self.video.draw(frame, area, context).is_none() {
+      return;
+    }
+    let Loading::Ready(video) = &self.video else {
+      return;
+    };
+    let [header, tabs, body] = Layout::vertical([Constraint::Length(5), Constraint::Length(2), Constraint::Min(0)]).areas(area);
+    frame.render_widget(Paragraph::new(self.header(video)), header);
+
+    let index = TABS.iter().position(|tab| *tab == self.tab).unwrap_or(0);
+    let titles = TABS.iter().map(|tab| match tab {
+      Tab::Formats => format!("{} ({})", tab.name(), video.adaptiveFormats.as_ref().map_or(0, Vec::len)),
+      Tab::Recommended => format!("{} ({})", tab.name(), video.recommendedVideos.as_ref().map_or(0, Vec::len)),
+      Tab::Comments => match self.comments.count() {
+        Some(count) => format!("{} ({})", tab.name(), format::count(count as i64)),
+        None => tab.name().to_string(),
+      },
+      Tab::Description => tab.name().to_string(),
+    }).collect::<Vec<String>>();
+    self.tabs_area = tabs;
+    self.tab_widths = titles.iter().map(|title| Line::from(title.as_str()).width() as u16).collect();
+    let tabs_widget = Tabs::new(titles).select(index).highlight_style(Style::new().fg(theme::current().accent).bold());
+    frame.render_widget(tabs_widget, tabs);
+
+    self.recommended_hits = ListHits::default();
+    match self.tab {
+      Tab::Description => draw_description(frame, body, video, self.scroll, self.timestamp),
+      Tab::Formats => draw_formats(frame, body, video.adaptiveFormats.as_deref().unwrap_or_default(), &mut self.formats),
+      Tab::Recommended => draw_recommended(frame, body, video.recommendedVideos.as_deref().unwrap_or_default(), &mut self.recommended, &mut self.recommended_hits),
+      Tab::Comments => self.comments.draw(frame, body, context),
+    }
+  }
+
+  // A click on the title of a tab shows it, a click on a recommended video selects it then
+  // opens it
+  fn click(&mut self, mouse: MouseEvent, context: &mut Context) -> Option<Transition> {
+    if self.tabs_area.contains(Position::new(mouse.column, mouse.row)) {
+      // Each title is padded with a space on both sides and followed by a divider
+      let mut right = self.tabs_area.x;
+      for (tab, width) in TABS.iter().zip(&self.tab_widths) {
+        right += width + 3;
+        if mouse.column < right {
+          self.switch(*tab, context);
+          break;
+        }
+      }
+      return Some(Transition::Stay);
+    }
+    let count = match &self.video {
+      Loading::Ready(video) => video.recommendedVideos.as_ref().map_or(0, Vec::len),
+      _ => 0,
+    };
+    match self.recommended_hits.click(&mut self.recommended, count, &mouse)? {
+      Click::Open => self.perform(Action::Open, context),
+      Click::Selected => Some(Transition::Stay),
+    }
+  }
+
+  fn perform(&mut self, action: Action, context: &mut Context) -> Option<Transition> {
+    if self.tab == Tab::Comments && self.comments.perform(action, context) {
+      return Some(Transition::Stay);
+    }
+    match (action, self.tab) {
+      (Action::NextTab, _) => {
+        let index = TABS.iter().position(|tab| *tab == self.tab).unwrap_or(0);
+        self.switch(TABS[(index + 1) % TABS.len()], context);
+      },
+      (Action::PreviousTab, _) => {
+        let index = TABS.iter().position(|tab| *tab == self.tab).unwrap_or(0);
+        self.switch(TABS[(index + TABS.len() - 1) % TABS.len()], context);
+      },
+      (Action::PreviousResult, Tab::Description) => self.scroll = self.scroll.saturating_sub(1),
+      (Action::NextResult, Tab::Description) => self.scroll = self.scroll.saturating_add(1),
+      (Action::PreviousPage, Tab::Description) => self.scroll = self.scroll.saturating_sub(PAGE),
+      (Action::NextPage, Tab::Description) => self.scroll = self.scroll.saturating_add(PAGE),
+      (Action::FirstResult, Tab::Description) => self.scroll = 0,
+      (Action::NextTimestamp, Tab::Description) => self.select_timestamp(true),
+      (Action::PreviousTimestamp, Tab::Description) => self.select_timestamp(false),
+      (Action::PreviousResult, Tab::Formats) => self.formats.select_previous(),
+      (Action::NextResult, Tab::Formats) => self.formats.select_next(),
+      (Action::PreviousPage, Tab::Formats) => self.formats.scroll_up_by(PAGE),
+      (Action::NextPage, Tab::Formats) => self.formats.scroll_down_by(PAGE),
+      (Action::FirstResult, Tab::Formats) => self.formats.select_first(),
+      (Action::LastResult, Tab::Formats) => self.formats.select_last(),
+      (Action::PreviousResult, Tab::Recommended) => self.recommended.select_previous(),
+      (Action::NextResult, Tab::Recommended) => self.recommended.select_next(),
+      (Action::PreviousPage, Tab::Recommended) => self.recommended.scroll_up_by(PAGE),
+      (Action::NextPage, Tab::Recommended) => self.recommended.scroll_down_by(PAGE),
+      (Action::FirstResult, Tab::Recommended) => self.recommended.select_first(),
+      (Action::LastResult, Tab::Recommended) => self.recommended.select_last(),
+      (Action::Open, Tab::Recommended) => {
+        if let Some(video) = self.recommended() {
+          return Some(Transition::Push(Box::new(VideoScreen::new(video.videoId.clone(), context))));
+        }
+      },
+      (Action::Open, Tab::Description) | (Action::Play, _) => return Some(self.play()),
+      (Action::Download, _) => {
+        let id = self.id.clone();
+        self.download_request = Some(context.request(move |provider| provider.download(&id).map(|_| Response::Done)));
+      },
+      (Action::AddToQueue, _) => {
+        let title = self.title();
+        if context.queue.iter().any(|video| video.id == self.id) {
+          get_logger().info(format!("{} is already queued", title));
+        } else {
+          get_logger().success(format!("Queued {}", title));
+          context.queue.push(QueuedVideo { id: self.id.clone(), title });
+        }
+      },
+      (Action::OpenComments, _) => self.switch(Tab::Comments, context),
+      (Action::OpenChannel, _) => {
+        if let Loading::Ready(video) = &self.video {
+          return Some(Transition::Push(Box::new(ChannelScreen::new(video.authorId.clone(), context))));
+        }
+      },
+      _ => return None,
+    }
+    Some(Transition::Stay)
+  }
+
+  fn handle_response(&mut self, request: RequestId, response: &std::io::Result<Response>, context: &mut Context) {
+    self.comments.handle_response(request, response, context);
+    if request == self.info_request {
+      match response {
+        Ok(Response::Info(info)) => self.info = Some(info.clone()),
+        Ok(_) => {},
+        Err(e) => get_logger().debug(format!("No YouTube infos for {}: {}", self.id, e)),
+      }
+    }
+    if Some(request) == self.download_request {
+      self.download_request = None;
+      if let Err(e) = response {
+        get_logger().error(format!("Failed to download {}: {}", self.id, e));
+      }
+    }
+    self.video.resolve(request, response, |response| match response {
+      Response::Video(video) => Some(video.clone()),
+      _ => None,
+    });
+  }
+}
@@ -0,0 +1,52 @@
+use std::io::Stdout;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use ratatui::crossterm::cursor::Show;
+use ratatui::crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::prelude::CrosstermBackend;
+use ratatui::Terminal;
+
+pub type Tui = Terminal<CrosstermBackend<Stdout>>;
+
+// Whether the mouse is captured while the interface is shown, set by `init`
+static MOUSE: AtomicBool = AtomicBool::new(false);
+
+// Take the terminal over: raw mode on the alternate screen, with the mouse captured when
+// `mouse` is set. A panic gives the terminal back before its message is printed.
+pub fn init(mouse: bool) -> std::io::Result<Tui> {
+  MOUSE.store(mouse, Ordering::SeqCst);
+  let hook = std::panic::take_hook();
+  std::panic::set_hook(Box::new(move |info| {
+    let _ = restore();
+    logger::set_sink(None);
+    hook(info);
+  }));
+  enter()?;
+  Terminal::new(CrosstermBackend::new(std::io::stdout()))
+}
+
+fn enter() -> std::io::Result<()> {
+  enable_raw_mode()?;
+  execute!(std::io::stdout(), EnterAlternateScreen)?;
+  if MOUSE.load(Ordering::SeqCst) {
+    execute!(std::io::stdout(), EnableMouseCapture)?;
+  }
+  Ok(())
+}
+
+// Give the terminal back as it was
+pub fn restore() -> std::io::Result<()> {
+  disable_raw_mode()?;
+  execute!(std::io::stdout(), DisableMouseCapture, LeaveAlternateScreen, Show)
+}
+
+// Give the terminal to another program until `run` returns, then draw everything again
+pub fn suspend<T>(terminal: &mut Tui, run: impl FnOnce() -> T) -> std::io::Result<T> {
+  restore()?;
+  let result = run();
+  enter()?;
+  terminal.clear()?;
+  Ok(result)
+}
@@ -0,0 +1,188 @@
+use std::ops::Range;
+use std::time::Duration;
+
+use api::chapters::{decode_entities, parse_timestamp};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+
+use crate::theme;
+
+// The timestamps written in `line`, like `1:02` or `1:02:03`, as byte ranges and positions
+pub fn timestamps(line: &str) -> Vec<(Range<usize>, Duration)> {
+  let mut timestamps = vec![];
+  let mut start = None;
+  // A space at the end closes the last run
+  for (index, c) in line.char_indices().chain([(line.len(), ' ')]) {
+    let part = c.is_ascii_digit() || c == ':';
+    match (start, part) {
+      (None, true) => start = Some(index),
+      (Some(from), false) => {
+        start = None;
+        let run = &line[from..index];
+        let trimmed = run.trim_matches(':');
+        let from = from + run.find(trimmed).unwrap_or(0);
+        if trimmed.contains(':') {
+          if let Some(position) = parse_timestamp(trimmed) {
+            timestamps.push((from..from + trimmed.len(), position));
+          }
+        }
+      },
+      _ => {},
+    }
+  }
+  timestamps
+}
+
+// How a timestamp is drawn, reversed when it is the selected one
+pub fn timestamp_style(selected: bool) -> Style {
+  let style = Style::new().fg(theme::current().highlight).add_modifier(Modifier::UNDERLINED);
+  match selected {
+    true => style.add_modifier(Modifier::REVERSED),
+    false => style,
+  }
+}
+
+// `line` with its timestamps highlighted. `first` is the index of its first timestamp
+// among the ones of the whole text, and `selected` the index of the selected one.
+pub fn highlight_timestamps(line: &str, first: usize, selected: Option<usize>) -> Line<'static> {
+  let mut spans = vec![];
+  let mut end = 0;
+  for (index, (range, _)) in timestamps(line).into_iter().enumerate() {
+    spans.push(Span::raw(line[end..range.start].to_string()));
+    spans.push(Span::styled(line[range.clone()].to_string(), timestamp_style(selected == Some(first + index))));
+    end = range.end;
+  }
+  spans.push(Span::raw(line[end..].to_string()));
+  Line::from(spans)
+}
+
+// Builds the lines of `html`, see `html`
+struct HtmlLines {
+  lines: Vec<Vec<Span<'static>>>,
+  positions: Vec<Duration>,
+  selected: Option<usize>,
+}
+
+impl HtmlLines {
+  fn push(&mut self, text: String, style: Style) {
+    if !text.is_empty() {
+      self.lines.last_mut().unwrap().push(Span::styled(text, style));
+    }
+  }
+
+  fn push_timestamp(&mut self, text: &str, position: Duration) {
+    let style = timestamp_style(self.selected == Some(self.positions.len()));
+    self.positions.push(position);
+    self.push(text.to_string(), style);
+  }
+
+  // Text outside of the tags, `link` being set inside of an `<a>`
+  fn text(&mut self, text: &str, style: Style, link: bool) {
+    for (index, line) in decode_entities(text).split('\n').enumerate() {
+      if index > 0 {
+        self.lines.push(vec![]);
+      }
+      let linked = line.trim();
+      match parse_timestamp(linked).filter(|_| link && linked.contains(':')) {
+        Some(position) => self.push_timestamp(line, position),
+        None if link => self.push(line.to_string(), style.fg(theme::current().link).add_modifier(Modifier::UNDERLINED)),
+        None => {
+          let mut end = 0;
+          for (range, position) in timestamps(line) {
+            self.push(line[end..range.start].to_string(), style);
+            self.push_timestamp(&line[range.clone()], position);
+            end = range.end;
+          }
+          self.push(line[end..].to_string(), style);
+        },
+      }
+    }
+  }
+}
+
+// The HTML of a comment as lines: bold, italics and links are kept, and the timestamps,
+// written or linked, are highlighted. Returns the positions of the timestamps too, in order,
+// `selected` being the index of the one reversed.
+pub fn html(html: &str, selected: Option<usize>) -> (Vec<Line<'static>>, Vec<Duration>) {
+  let mut lines = HtmlLines { lines: vec![vec![]], positions: vec![], selected };
+  let (mut bold, mut italic, mut crossed, mut link) = (0u32, 0u32, 0u32, false);
+  let mut rest = html;
+  while !rest.is_empty() {
+    let style = Style::new()
+      .add_modifier(if bold > 0 { Modifier::BOLD } else { Modifier::empty() })
+      .add_modifier(if italic > 0 { Modifier::ITALIC } else { Modifier::empty() })
+      .add_modifier(if crossed > 0 { Modifier::CROSSED_OUT } else { Modifier::empty() });
+    let Some((start, end)) = rest.find('<').and_then(|start| Some((start, start + rest[start..].find('>')?))) else {
+      lines.text(rest, style, link);
+      break;
+    };
+    lines.text(&rest[..start], style, link);
+    let tag = rest[start + 1..end].trim().to_lowercase();
+    rest = &rest[end + 1..];
+    let (closing, name) = match tag.strip_prefix('/') {
+      Some(name) => (true, name.trim_end_matches('/').trim()),
+      None => (false, tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or_default()),
+    };
+    let depth = match name {
+      "b" | "strong" => &mut bold,
+      "i" | "em" => &mut italic,
+      "s" | "strike" | "del" => &mut crossed,
+      "a" => {
+        link = !closing;
+        continue;
+      },
+      "br" => {
+        lines.lines.push(vec![]);
+        continue;
+      },
+      _ => continue,
+    };
+    *depth = match closing {
+      true => depth.saturating_sub(1),
+      false => *depth + 1,
+    };
+  }
+  (lines.lines.into_iter().map(Line::from).collect(), lines.positions)
+}
+
+// `line` broken between its words into lines at most `width` columns wide
+pub fn wrap(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+  let width = width.max(1);
+  let mut lines = vec![];
+  let mut current: Vec<Span<'static>> = vec![];
+  let mut used = 0;
+  for span in line.spans {
+    for word in span.content.split_inclusive(' ') {
+      let mut word = word.to_string();
+      let mut columns = Span::raw(word.as_str()).width();
+      if used + columns.min(width) > width && !current.is_empty() {
+        lines.push(Line::from(std::mem::take(&mut current)));
+        used = 0;
+        word = word.trim_start().to_string();
+        columns = Span::raw(word.as_str()).width();
+      }
+      // A word longer than a line is cut
+      while columns > width {
+        let mut cut = 0;
+        let mut cut_columns = 0;
+        for (index, c) in word.char_indices() {
+          let c_columns = Span::raw(c.to_string()).width();
+          if cut_columns + c_columns > width - used && cut > 0 {
+            break;
+          }
+          cut = index + c.len_utf8();
+          cut_columns += c_columns;
+        }
+        current.push(Span::styled(word[..cut].to_string(), span.style));
+        lines.push(Line::from(std::mem::take(&mut current)));
+        used = 0;
+        word = word[cut..].to_string();
+        columns = Span::raw(word.as_str()).width();
+      }
+      used += columns;
+      current.push(Span::styled(word, span.style));
+    }
+  }
+  lines.push(Line::from(current));
+  lines
+}
@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use logger::colors::{self, ColorDepth, LevelColors, Shade};
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::get_logger;
+
+// The colors of a theme, whatever the terminal shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+  // The brand, focused borders and the current tab
+  pub accent: Shade,
+  // Text over the accent
+  pub on_accent: Shade,
+  // Details, hints and placeholders
+  pub muted: Shade,
+  // Behind the selected row
+  pub selection: Shade,
+  pub link: Shade,
+  // Verified badges and timestamps
+  pub highlight: Shade,
+  // Behind the channel owner in comments
+  pub badge: Shade,
+  pub on_badge: Shade,
+  pub success: Shade,
+  pub info: Shade,
+  pub warn: Shade,
+  pub debug: Shade,
+  pub error: Shade,
+}
+
+pub static DARK: Palette = Palette {
+  accent: Shade::new((230, 33, 23), 160, 1),
+  on_accent: Shade::new((0, 0, 0), 16, 0),
+  muted: Shade::new((128, 128, 128), 244, 8),
+  selection: Shade::new((58, 58, 58), 237, 8),
+  link: Shade::new((95, 135, 255), 69, 4),
+  highlight: Shade::new((0, 215, 215), 44, 6),
+  badge: Shade::new((188, 188, 188), 250, 7),
+  on_badge: Shade::new((0, 0, 0), 16, 0),
+  success: Shade::new((95, 215, 95), 77, 2),
+  info: Shade::new((0, 175, 215), 38, 6),
+  warn: Shade::new((255, 215, 0), 220, 3),
+  debug: Shade::new((175, 95, 215), 134, 5),
+  error: Shade::new((255, 95, 95), 203, 1),
+};
+
+pub static LIGHT: Palette = Palette {
+  accent: Shade::new((204, 0, 0), 160, 1),
+  on_accent: Shade::new((255, 255, 255), 231, 15),
+  muted: Shade::new((108, 108, 108), 242, 8),
+  selection: Shade::new((208, 208, 208), 252, 7),
+  link: Shade::new((0, 95, 215), 26, 4),
+  highlight: Shade::new((0, 135, 135), 30, 6),
+  badge: Shade::new((88, 88, 88), 240, 8),
+  on_badge: Shade::new((255, 255, 255), 231, 15),
+  success: Shade::new((0, 135, 0), 28, 2),
+  info: Shade::new((0, 95, 175), 25, 6),
+  warn: Shade::new((175, 95, 0), 130, 3),
+  debug: Shade::new((135, 0, 175), 91, 5),
+  error: Shade::new((215, 0, 0), 160, 1),
+};
+
+// The names of the roles in the theme file, in the order of `Palette`
+static ROLES: [&str; 13] = [
+  "accent", "on_accent", "muted", "selection", "link", "highlight", "badge", "on_badge", "success", "info", "warn", "debug", "error",
+];
+
+// The basic colors, by their index and their name in the theme file
+static ANSI16: [(Color, &str); 16] = [
+  (Color::Black, "black"),
+  (Color::Red, "red"),
+  (Color::Green, "green"),
+  (Color::Yellow, "yellow"),
+  (Color::Blue, "blue"),
+  (Color::Magenta, "magenta"),
+  (Color::Cyan, "cyan"),
+  (Color::Gray, "gray"),
+  (Color::DarkGray, "dark_gray"),
+  (Color::LightRed, "light_red"),
+  (Color::LightGreen, "light_green"),
+  (Color::LightYellow, "light_yellow"),
+  (Color::LightBlue, "light_blue"),
+  (Color::LightMagenta, "light_magenta"),
+  (Color::LightCyan, "light_cyan"),
+  (Color::White, "white"),
+];
+
+impl Palette {
+  fn role_mut(&mut self, name: &str) -> Option<&mut Shade> {
+    let shade = match name {
+      "accent" => &mut self.accent,
+      "on_accent" => &mut self.on_accent,
+      "muted" => &mut self.muted,
+      "selection" => &mut self.selection,
+      "link" => &mut self.link,
+      "highlight" => &mut self.highlight,
+      "badge" => &mut self.badge,
+      "on_badge" => &mut self.on_badge,
+      "success" => &mut self.success,
+      "info" => &mut self.info,
+      "warn" => &mut self.warn,
+      "debug" => &mut self.debug,
+      "error" => &mut self.error,
+      _ => return None,
+    };
+    Some(shade)
+  }
+
+  // The colors of the log levels in this palette
+  pub fn levels(&self) -> LevelColors {
+    LevelColors { success: self.success, info: self.info, warn: self.warn, debug: self.debug, error: self.error }
+  }
+}
+
+// `#rrggbb`, or `{"truecolor": "#rrggbb", "256": 208, "16": "yellow"}` where the missing ones come from `shade`
+fn parse_shade(value: &Value, shade: Shade) -> Result<Shade, String> {
+  if let Some(hex) = value.as_str() {
+    return Shade::parse(hex).ok_or(format!("`{}` is not a #rrggbb color", hex));
+  }
+  let Some(variants) = value.as_object() else {
+    return Err(format!("`{}` is neither a #rrggbb color nor an object of variants", value));
+  };
+  // The truecolor variant derives the other two, so it goes before their overrides whatever the key order
+  let mut shade = match variants.get("truecolor") {
+    Some(Value::String(hex)) => Shade::parse(hex).ok_or(format!("`{}` is not a #rrggbb color", hex))?,
+    Some(value) => return Err(format!("`{}` is not a #rrggbb color", value)),
+    None => shade,
+  };
+  for (variant, value) in variants {
+    match variant.as_str() {
+      "truecolor" => {},
+      "256" => match value.as_u64() {
+        Some(index) if index < 256 => shade.ansi256 = index as u8,
+        _ => return Err(format!("`{}` is not a color of the 256 palette", value)),
+      },
+      "16" => match (value.as_u64(), value.as_str()) {
+        (Some(index), _) if index < 16 => shade.ansi16 = index as u8,
+        (_, Some(name)) if ANSI16.iter().any(|(_, n)| *n == name) => {
+          shade.ansi16 = ANSI16.iter().position(|(_, n)| *n == name).unwrap_or(0) as u8;
+        },
+        _ => return Err(format!("`{}` is not one of the 16 colors", value)),
+      },
+      _ => return Err(format!("Unknown variant `{}`, it is truecolor, 256 or 16", variant)),
+    }
+  }
+  Ok(shade)
+}
+
+// The theme file, e.g. `{"theme": "mine", "palettes": {"mine": {"base": "light", "accent": "#ff8700"}}}`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ThemeFile {
+  // A palette name, `dark` and `light` are built in, the terminal background picks when there is none
+  theme: Option<String>,
+  // `auto`, `truecolor`, `256`, `16` or `none`
+  color: Option<String>,
+  palettes: HashMap<String, HashMap<String, Value>>,
+}
+
+// The built-in palette matching the terminal background, as `COLORFGBG` tells it
+fn background_palette() -> (&'static str, Palette) {
+  let background = std::env::var("COLORFGBG").ok().and_then(|value| value.rsplit(';').next()?.parse::<u8>().ok());
+  match background {
+    Some(7) | Some(15) => ("light", LIGHT),
+    _ => ("dark", DARK),
+  }
+}
+
+fn parse_depth(name: &str) -> Result<ColorDepth, String> {
+  match name {
+    "auto" => Ok(ColorDepth::detect()),
+    "truecolor" | "24bit" => Ok(ColorDepth::TrueColor),
+    "256" => Ok(ColorDepth::Ansi256),
+    "16" => Ok(ColorDepth::Ansi16),
+    "none" => Ok(ColorDepth::None),
+    _ => Err(format!("Unknown color depth `{}`, it is auto, truecolor, 256, 16 or none", name)),
+  }
+}
+
+fn color(shade: Shade, depth: ColorDepth) -> Color {
+  match depth {
+    ColorDepth::TrueColor => Color::Rgb(shade.rgb.0, shade.rgb.1, shade.rgb.2),
+    ColorDepth::Ansi256 => Color::Indexed(shade.ansi256),
+    ColorDepth::Ansi16 => ANSI16[shade.ansi16 as usize % 16].0,
+    ColorDepth::None => Color::Reset,
+  }
+}
+
+// A palette drawn in as many colors as the terminal has
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+  pub name: &'static str,
+  pub palette: Palette,
+  pub depth: ColorDepth,
+  pub accent: Color,
+  pub on_accent: Color,
+  pub muted: Color,
+  pub selection: Color,
+  pub link: Color,
+  pub highlight: Color,
+  pub badge: Color,
+  pub on_badge: Color,
+  pub success: Color,
+  pub warn: Color,
+  pub error: Color,
+}
+
+impl Default for Theme {
+  fn default() -> Theme {
+    let (name, palette) = background_palette();
+    Theme::new(name, palette, ColorDepth::detect())
+  }
+}
+
+impl Theme {
+  pub fn new(name: &'static str, palette: Palette, depth: ColorDepth) -> Theme {
+    // `NO_COLOR` wins over the theme file
+    let depth = match ColorDepth::detect() {
+      ColorDepth::None => ColorDepth::None,
+      _ => depth,
+    };
+    let color = |shade: Shade| color(shade, depth);
+    Theme {
+      name,
+      palette,
+      depth,
+      accent: color(palette.accent),
+      on_accent: color(palette.on_accent),
+      muted: color(palette.muted),
+      selection: color(palette.selection),
+      link: color(palette.link),
+      highlight: color(palette.highlight),
+      badge: color(palette.badge),
+      on_badge: color(palette.on_badge),
+      success: color(palette.success),
+      warn: color(palette.warn),
+      error: color(palette.error),
+    }
+  }
+
+  // Any color, e.g. of a video frame, in as many colors as the terminal has
+  pub fn rgb(&self, rgb: (u8, u8, u8)) -> Color {
+    match self.depth {
+      ColorDepth::TrueColor => Color::Rgb(rgb.0, rgb.1, rgb.2),
+      depth => color(Shade::from_rgb(rgb), depth),
+    }
+  }
+
+  // The selected row, reversed when there are no colors
+  pub fn selected(&self) -> Style {
+    match self.depth {
+      ColorDepth::None => Style::new().add_modifier(Modifier::REVERSED),
+      _ => Style::new().bg(self.selection),
+    }
+  }
+
+  // Text over the accent, e.g. the brand or the highlighted menu entry
+  pub fn inverse(&self) -> Style {
+    match self.depth {
+      ColorDepth::None => Style::new().add_modifier(Modifier::REVERSED),
+      _ => Style::new().fg(self.on_accent).bg(self.accent),
+    }
+  }
+
+  // The channel owner in comments
+  pub fn badge(&self) -> Style {
+    match self.depth {
+      ColorDepth::None => Style::new().add_modifier(Modifier::REVERSED),
+      _ => Style::new().fg(self.on_badge).bg(self.badge),
+    }
+  }
+
+  // The theme of a theme file, along with what is wrong in it
+  pub fn parse(text: &str) -> Result<(Theme, Vec<String>), String> {
+    let file: ThemeFile = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let depth = parse_depth(file.color.as_deref().unwrap_or("auto"))?;
+    let mut problems = vec![];
+    let mut palettes = vec![("dark".to_string(), DARK), ("light".to_string(), LIGHT)];
+    // Custom palettes may be based on each other, in any order
+    let mut pending: Vec<_> = file.palettes.iter().collect();
+    pending.sort_by_key(|(name, _)| name.as_str());
+    while !pending.is_empty() {
+      let count = pending.len();
+      pending.retain(|(name, roles)| {
+        let base = roles.get("base").and_then(Value::as_str).unwrap_or("dark");
+        let Some(mut palette) = palettes.iter().find(|(n, _)| n == base).map(|(_, palette)| *palette) else {
+          return true;
+        };
+        let mut roles: Vec<_> = roles.iter().filter(|(role, _)| *role != "base").collect();
+        roles.sort_by_key(|(role, _)| ROLES.iter().position(|r| r == role));
+        for (role, value) in roles {
+          let Some(shade) = palette.role_mut(role) else {
+            problems.push(format!("Unknown role `{}` in {}, it is one of {}", role, name, ROLES.join(", ")));
+            continue;
+          };
+          match parse_shade(value, *shade) {
+            Ok(parsed) => *shade = parsed,
+            Err(e) => problems.push(format!("{} for {} in {}", e, role, name)),
+          }
+        }
+        palettes.push((name.to_string(), palette));
+        false
+      });
+      if pending.len() == count {
+        for (name, roles) in pending.drain(..) {
+          let base = roles.get("base").and_then(Value::as_str).unwrap_or("dark");
+          problems.push(format!("Unknown base `{}` of {}", base, name));
+        }
+      }
+    }
+    let theme = match file.theme {
+      Some(name) => match palettes.iter().find(|(n, _)| *n == name) {
+        // Leaked once, the theme lives as long as the program
+        Some((_, palette)) => Theme::new(Box::leak(name.into_boxed_str()), *palette, depth),
+        None => {
+          problems.push(format!("Unknown theme `{}`, using the dark one", name));
+          Theme::new("dark", DARK, depth)
+        },
+      },
+      None => {
+        let (name, palette) = background_palette();
+        Theme::new(name, palette, depth)
+      },
+    };
+    Ok((theme, problems))
+  }
+
+  // The theme of the file at `path`, the default one when there is none or it is broken
+  pub fn load(path: &str) -> Theme {
+    let text = match std::fs::read_to_string(path) {
+      Ok(text) => text,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Theme::default(),
+      Err(e) => {
+        get_logger().error(format!("Failed to read the theme {}: {}", path, e));
+        return Theme::default();
+      },
+    };
+    match Theme::parse(&text) {
+      Ok((theme, problems)) => {
+        get_logger().info(format!("Loaded the theme {} from {}", theme.name, path));
+        for problem in problems {
+          get_logger().warn(format!("Theme {}: {}", path, problem));
+        }
+        theme
+      },
+      Err(e) => {
+        get_logger().error(format!("Failed to parse the theme {}, using the default one: {}", path, e));
+        Theme::default()
+      },
+    }
+  }
+}
+
+lazy_static! {
+  static ref CURRENT: RwLock<Theme> = RwLock::new(Theme::default());
+}
+
+// Draw the interface and the log levels with `theme`
+pub fn set(theme: Theme) {
+  colors::set_level_colors(theme.depth, theme.palette.levels());
+  *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = theme;
+}
+
+// The theme the screens are drawn with
+pub fn current() -> Theme {
+  *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn parses_a_hex_shade() {
+    let shade = parse_shade(&json!("#ff8700"), DARK.accent).unwrap();
+    assert_eq!(shade, Shade::from_rgb((255, 135, 0)));
+    assert!(parse_shade(&json!("orange"), DARK.accent).is_err());
+  }
+
+  #[test]
+  fn keeps_the_overrides_of_a_truecolor_variant() {
+    let shade = parse_shade(&json!({"truecolor": "#ff8700", "256": 208, "16": "yellow"}), DARK.accent).unwrap();
+    assert_eq!(shade, Shade::new((255, 135, 0), 208, 3));
+  }
+
+  #[test]
+  fn completes_a_variant_from_the_base_shade() {
+    let shade = parse_shade(&json!({"16": 9}), DARK.accent).unwrap();
+    assert_eq!(shade, Shade::new(DARK.accent.rgb, DARK.accent.ansi256, 9));
+  }
+
+  #[test]
+  fn rejects_invalid_variants() {
+    assert!(parse_shade(&json!({"truecolor": {"truecolor": "#ff8700"}}), DARK.accent).is_err());
+    assert!(parse_shade(&json!({"256": 256}), DARK.accent).is_err());
+    assert!(parse_shade(&json!({"16": "orange"}), DARK.accent).is_err());
+    assert!(parse_shade(&json!({"88": 1}), DARK.accent).is_err());
+  }
+
+  #[test]
+  fn parses_palettes_based_on_each_other() {
+    let text = r##"{
+      "theme": "b",
+      "color": "256",
+      "palettes": {
+        "b": {"base": "a", "link": "#000000"},
+        "a": {"base": "light", "accent": "#ff8700", "shiny": "#ffffff"}
+      }
+    }"##;
+    let (theme, problems) = Theme::parse(text).unwrap();
+    assert_eq!(theme.name, "b");
+    assert_eq!(theme.palette.accent, Shade::from_rgb((255, 135, 0)));
+    assert_eq!(theme.palette.link, Shade::from_rgb((0, 0, 0)));
+    assert_eq!(theme.palette.muted, LIGHT.muted);
+    assert_eq!(problems.len(), 1);
+  }
+
+  #[test]
+  fn reports_unknown_bases_and_themes() {
+    let (theme, problems) = Theme::parse(r#"{"theme": "x", "palettes": {"x": {"base": "y"}}}"#).unwrap();
+    assert_eq!(theme.name, "dark");
+    assert_eq!(problems.len(), 2);
+    assert!(Theme::parse(r#"{"color": "12bit"}"#).is_err());
+  }
+}
@@ -0,0 +1,266 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::get_logger;
+use crate::http::{get_content_length, get_range};
+use crate::models::error::Error;
+use crate::models::stream::Stream;
+
+// Failed requests in a row before a chunk is given up
+static MAX_RETRIES: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Progress {
+  // Bytes on disk, including the ones of a resumed download
+  pub downloaded: u64,
+  // Size of the whole file
+  pub total: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DownloadOptions {
+  // Number of chunks fetched at the same time
+  pub connections: usize,
+  // Size of the range requests
+  pub chunk_size: u64,
+}
+
+// What was downloaded so far, saved in the `.part` sidecar to resume from there
+struct PartState {
+  total: u64,
+  chunk_size: u64,
+  // Bytes written at the start of each chunk
+  chunks: Vec<u64>,
+}
+
+// Why a chunk failed, sent back from the download threads
+enum ChunkError {
+  Network(curl::Error),
+  File(std::io::Error),
+  Short { expected: u64, found: u64 },
+  // The progress callback asked to stop
+  Cancelled,
+}
+
+impl Default for DownloadOptions {
+  fn default() -> DownloadOptions {
+    DownloadOptions { connections: 4, chunk_size: 4 * 1024 * 1024 }
+  }
+}
+
+impl Progress {
+  // Downloaded part of the file, from 0.0 to 1.0
+  pub fn fraction(&self) -> f64 {
+    if self.total == 0 {
+      return 1.0;
+    }
+    self.downloaded as f64 / self.total as f64
+  }
+}
+
+impl From<ChunkError> for Error {
+  fn from(error: ChunkError) -> Error {
+    match error {
+      ChunkError::Network(e) => Error::NetworkError(Box::new(e)),
+      ChunkError::File(e) => Error::FileError(e),
+      ChunkError::Short { expected, found } => Error::SizeMismatch { expected, found },
+      ChunkError::Cancelled => Error::Cancelled,
+    }
+  }
+}
+
+impl PartState {
+  fn new(total: u64, chunk_size: u64) -> PartState {
+    PartState { total, chunk_size, chunks: vec![0; total.div_ceil(chunk_size) as usize] }
+  }
+
+  // Byte range of a chunk, `end` included
+  fn range(&self, index: usize) -> (u64, u64) {
+    let start = index as u64 * self.chunk_size;
+    (start, (start + self.chunk_size).min(self.total) - 1)
+  }
+
+  fn downloaded(&self) -> u64 {
+    self.chunks.iter().sum()
+  }
+
+  // The state of a previous download of the same file, if it can be resumed
+  fn load(sidecar: &str, total: u64, chunk_size: u64) -> Option<PartState> {
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(sidecar).ok()?).ok()?;
+    if json["total"].as_u64()? != total || json["chunk_size"].as_u64()? != chunk_size {
+      return None;
+    }
+    let chunks: Vec<u64> = json["chunks"].as_array()?.iter().map(|done| done.as_u64()).collect::<Option<_>>()?;
+    if chunks.len() != total.div_ceil(chunk_size) as usize {
+      return None;
+    }
+    Some(PartState { total, chunk_size, chunks })
+  }
+
+  fn save(&self, sidecar: &str) -> std::io::Result<()> {
+    let json = json!({ "total": self.total, "chunk_size": self.chunk_size, "chunks": self.chunks });
+    std::fs::write(sidecar, json.to_string())
+  }
+}
+
+// Fetch one chunk into the file, retrying from where a request stopped. `get_range` hands
+// over nothing outside the chunk, so a server ignoring the range cannot overwrite its neighbours.
+fn download_chunk(
+  url: &str,
+  path: &str,
+  index: usize,
+  state: &Mutex<PartState>,
+  downloaded: &AtomicU64,
+  failed: &AtomicBool,
+  on_progress: &(dyn Fn(Progress) -> bool + Sync),
+) -> Result<(), ChunkError> {
+  let (chunk_start, end) = state.lock().expect("Failed to lock PartState").range(index);
+  let total = state.lock().expect("Failed to lock PartState").total;
+  let mut file = OpenOptions::new().write(true).open(path).map_err(ChunkError::File)?;
+  let mut failures = 0;
+
+  loop {
+    let start = chunk_start + state.lock().expect("Failed to lock PartState").chunks[index];
+    if start > end {
+      return Ok(());
+    }
+    file.seek(SeekFrom::Start(start)).map_err(ChunkError::File)?;
+
+    let mut write_error = None;
+    let mut cancelled = false;
+    let result = get_range(url, start, end, |data| {
+      if failed.load(Ordering::Relaxed) {
+        return false;
+      }
+      if let Err(e) = file.write_all(data) {
+        write_error = Some(e);
+        return false;
+      }
+      state.lock().expect("Failed to lock PartState").chunks[index] += data.len() as u64;
+      let done = downloaded.fetch_add(data.len() as u64, Ordering::Relaxed) + data.len() as u64;
+      cancelled = !on_progress(Progress { downloaded: done, total });
+      !cancelled
+    });
+    if let Some(e) = write_error {
+      return Err(ChunkError::File(e));
+    }
+    if cancelled {
+      return Err(ChunkError::Cancelled);
+    }
+    // Another chunk failed, the download stops
+    if failed.load(Ordering::Relaxed) {
+      return Ok(());
+    }
+
+    let found = state.lock().expect("Failed to lock PartState").chunks[index];
+    let error = match result {
+      Ok(()) if chunk_start + found > end => return Ok(()),
+      Ok(()) => ChunkError::Short { expected: end - chunk_start + 1, found },
+      Err(e) => ChunkError::Network(e),
+    };
+
+    failures += 1;
+    if failures > MAX_RETRIES {
+      return Err(error);
+    }
+    get_logger().warn(format!("Chunk {} of {} failed, retrying from byte {}", index, path, chunk_start + found));
+    std::thread::sleep(Duration::from_secs(failures as u64));
+  }
+}
+
+// Download `stream` to its `file_path` with parallel range requests. An interrupted
+// download is resumed from its `.part` sidecar, which is removed once the file is complete.
+// The download stops with `Error::Cancelled` when `on_progress` returns false.
+pub fn download_stream(stream: &Stream, options: &DownloadOptions, on_progress: &(dyn Fn(Progress) -> bool + Sync)) -> Result<(), Error> {
+  let path = stream.file_path.as_str();
+  let sidecar = format!("{}.part", path);
+
+  let total = match stream.content_length as u64 {
+    0 => get_content_length(&stream.url)
+      .map_err(|e| Error::NetworkError(Box::new(e)))?
+      .ok_or_else(|| Error::NetworkError(Box::new(std::io::Error::other("The server did not send the stream size"))))?,
+    length => length,
+  };
+  let chunk_size = options.chunk_size.max(1);
+
+  // A file without its sidecar is complete
+  let existing = std::fs::metadata(path).ok().map(|metadata| metadata.len());
+  if existing == Some(total) && std::fs::metadata(&sidecar).is_err() {
+    get_logger().info(format!("{} is already downloaded", path));
+    on_progress(Progress { downloaded: total, total });
+    return Ok(());
+  }
+
+  let state = match PartState::load(&sidecar, total, chunk_size).filter(|_| existing == Some(total)) {
+    Some(state) => {
+      get_logger().info(format!("Resuming {} at {} of {} bytes", path, state.downloaded(), total));
+      state
+    },
+    None => {
+      let file = File::create(path).map_err(Error::FileError)?;
+      file.set_len(total).map_err(Error::FileError)?;
+      let state = PartState::new(total, chunk_size);
+      state.save(&sidecar).map_err(Error::FileError)?;
+      state
+    },
+  };
+
+  let chunks = state.chunks.len();
+  let downloaded = AtomicU64::new(state.downloaded());
+  if !on_progress(Progress { downloaded: downloaded.load(Ordering::Relaxed), total }) {
+    return Err(Error::Cancelled);
+  }
+  get_logger().debug(format!(
+    "Downloading {} bytes to {} in {} chunks over {} connections",
+    total, path, chunks, options.connections,
+  ));
+
+  let state = Mutex::new(state);
+  let next = AtomicUsize::new(0);
+  let failed = AtomicBool::new(false);
+  let error: Mutex<Option<ChunkError>> = Mutex::new(None);
+
+  std::thread::scope(|scope| {
+    for _ in 0..options.connections.clamp(1, chunks.max(1)) {
+      scope.spawn(|| loop {
+        let index = next.fetch_add(1, Ordering::Relaxed);
+        if index >= chunks || failed.load(Ordering::Relaxed) {
+          return;
+        }
+        match download_chunk(&stream.url, path, index, &state, &downloaded, &failed, on_progress) {
+          Ok(()) => {
+            if let Err(e) = state.lock().expect("Failed to lock PartState").save(&sidecar) {
+              get_logger().warn(format!("Failed to save the progress of {}: {}", path, e));
+            }
+          },
+          Err(e) => {
+            failed.store(true, Ordering::Relaxed);
+            error.lock().expect("Failed to lock ChunkError").get_or_insert(e);
+            return;
+          },
+        }
+      });
+    }
+  });
+
+  let state = state.into_inner().expect("Failed to lock PartState");
+  // Keep what was fetched for the next attempt
+  state.save(&sidecar).map_err(Error::FileError)?;
+  if let Some(e) = error.into_inner().expect("Failed to lock ChunkError") {
+    return Err(e.into());
+  }
+
+  let found = std::fs::metadata(path).map_err(Error::FileError)?.len();
+  if found != total || state.downloaded() != total {
+    return Err(Error::SizeMismatch { expected: total, found: state.downloaded() });
+  }
+  std::fs::remove_file(&sidecar).map_err(Error::FileError)?;
+  get_logger().info(format!("Downloaded {} ({} bytes)", path, total));
+  Ok(())
+}
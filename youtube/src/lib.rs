@@ -1,5 +1,25 @@
-
-
 pub mod models;
+pub mod download;
 pub mod http;
-pub mod utils;
\ No newline at end of file
+pub mod mux;
+pub mod queue;
+pub mod utils;
+
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+
+use logger::{create_logger, ModuleType, Logger};
+
+// Define a lazy_static global instance of the YoutubeLogger
+lazy_static! {
+  static ref YOUTUBE_LOGGER: Mutex<Box<dyn Logger>> = Mutex::new(create_logger(ModuleType::Youtube));
+}
+
+// Function to access the global YoutubeLogger instance
+pub fn get_logger() -> std::sync::MutexGuard<'static, Box<dyn Logger>> {
+  YOUTUBE_LOGGER.lock().expect("Failed to lock YoutubeLogger")
+}
+
+pub fn init_module() {
+  get_logger().info("Initializing Youtube module".to_string());
+}
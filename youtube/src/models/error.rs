@@ -1,5 +1,30 @@
 #[derive(Debug)]
 pub enum Error {
   VideoNotFound,
-  NetworkError(Box<dyn std::error::Error>),
-}
\ No newline at end of file
+  StreamNotFound,
+  NetworkError(Box<dyn std::error::Error + Send + Sync>),
+  // Reading or writing a downloaded file failed
+  FileError(std::io::Error),
+  // The downloaded file does not have the size the server announced
+  SizeMismatch { expected: u64, found: u64 },
+  // The download was stopped before its end
+  Cancelled,
+  // ffmpeg failed to put the streams together, with what it said
+  MuxError(String),
+}
+
+impl std::fmt::Display for Error {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Error::VideoNotFound => write!(f, "Video not found"),
+      Error::StreamNotFound => write!(f, "No matching stream"),
+      Error::NetworkError(e) => write!(f, "Network error: {}", e),
+      Error::FileError(e) => write!(f, "File error: {}", e),
+      Error::SizeMismatch { expected, found } => write!(f, "Expected {} bytes but got {}", expected, found),
+      Error::Cancelled => write!(f, "Cancelled"),
+      Error::MuxError(e) => write!(f, "Failed to mux the streams: {}", e),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
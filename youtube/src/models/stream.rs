@@ -2,11 +2,15 @@ use futures::executor::block_on;
 
 extern crate reqwest;
 
+use crate::get_logger;
 use crate::http::get_video_streams;
 use crate::utils::parse_number;
 use crate::models::error::Error;
 
-#[derive(Debug, PartialEq)]
+// Video codecs by order of preference, the first ones are the cheapest to decode
+pub static VIDEO_CODECS: &[&str] = &["avc1", "vp9", "av01"];
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum StreamType {
   Audio,
   Video,
@@ -14,7 +18,7 @@ pub enum StreamType {
   Unknown,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum QualityType {
   Tiny,
   Small,
@@ -28,7 +32,7 @@ pub enum QualityType {
   HighRes,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Stream {
   // The stream url
   pub url: String,
@@ -64,6 +68,14 @@ pub struct StreamList {
   pub streams: Vec<Stream>,
 }
 
+#[derive(Debug, Clone)]
+pub struct StreamSelection {
+  // The video stream, without sound
+  pub video: Stream,
+  // The audio stream played along, if the video has one
+  pub audio: Option<Stream>,
+}
+
 impl Stream {
   pub fn from_json(json: serde_json::Value) -> Stream {
     let data = json.clone();
@@ -96,21 +108,32 @@ impl Stream {
     self.file_path = file_path.to_string();
   }
 
-  pub fn clone(&self) -> Stream {
-    Stream {
-      url: self.url.to_string(),
-      quality: self.quality.clone(),
-      quality_label: self.quality_label.to_string(),
-      bitrate: self.bitrate,
-      mime_type: self.mime_type.to_string(),
-      stream_type: self.stream_type.clone(),
-      width: self.width,
-      height: self.height,
-      extension: self.extension.to_string(),
-      fps: self.fps,
-      duration: self.duration,
-      content_length: self.content_length,
-      file_path: self.file_path.to_string(),
+  // The codec of the stream without its profile, e.g. `avc1` for `avc1.4d401e`
+  pub fn codec(&self) -> String {
+    let codec = self.mime_type.split("codecs=").nth(1).unwrap_or("")
+      .trim_matches(|c| c == '"' || c == ' ')
+      .split(['.', ',']).next().unwrap_or("");
+    match codec {
+      "vp09" => "vp9".to_string(),
+      codec => codec.to_string(),
+    }
+  }
+
+  // Rank of the codec in `VIDEO_CODECS`, the unknown ones come last
+  fn codec_rank(&self) -> usize {
+    let codec = self.codec();
+    VIDEO_CODECS.iter().position(|known| *known == codec).unwrap_or(VIDEO_CODECS.len())
+  }
+
+  // Whether the stream fills a `width` x `height` box without being scaled up
+  pub fn covers(&self, width: u32, height: u32) -> bool {
+    self.width >= width || self.height >= height
+  }
+
+  fn describe(&self) -> String {
+    match self.stream_type {
+      StreamType::Video => format!("{}x{} {} {} fps {} kbps", self.width, self.height, self.codec(), self.fps, self.bitrate / 1000),
+      _ => format!("{} {} kbps", self.codec(), self.bitrate / 1000),
     }
   }
 }
@@ -145,16 +168,57 @@ impl StreamList {
       StreamList { streams }
     }
 
-    pub fn get_best_stream(&self, quality: QualityType) -> Stream {
-      let mut best_stream = self.streams[0].clone();
+    // The smallest video stream filling a `width` x `height` box, or the largest
+    // one when none does. Known codecs are preferred, then the cheaper ones.
+    pub fn select_video(&self, width: u32, height: u32) -> Option<Stream> {
+      let videos = self.get_streams_by_type(StreamType::Video);
+      let known = videos.streams.iter().any(|stream| stream.codec_rank() < VIDEO_CODECS.len());
+      let candidates: Vec<&Stream> = videos.streams.iter()
+        .filter(|stream| !known || stream.codec_rank() < VIDEO_CODECS.len())
+        .collect();
+      get_logger().debug(format!(
+        "Looking for a video stream of at least {}x{} among {} candidates of {} video streams",
+        width, height, candidates.len(), videos.streams.len(),
+      ));
 
-      self.streams.iter().for_each(|stream| {
-          if stream.quality == quality && stream.extension == "mp4" {
-              best_stream = stream.clone();
-          }
-      });
+      let covering = candidates.iter().any(|stream| stream.covers(width, height));
+      let best = candidates.into_iter()
+        .filter(|stream| !covering || stream.covers(width, height))
+        .min_by_key(|stream| {
+          let area = stream.width as u64 * stream.height as u64;
+          // Smallest covering stream, or largest stream when none covers the box
+          let area = if covering { area } else { u64::MAX - area };
+          (area, stream.codec_rank(), stream.fps, stream.bitrate)
+        })?;
 
-      best_stream
+      get_logger().debug(format!(
+        "Picked the video stream {} ({}): {}",
+        best.describe(), best.quality_label,
+        if covering { "the smallest one filling the terminal" } else { "the largest one, none fills the terminal" },
+      ));
+      Some(best.clone())
+    }
+
+    // The audio stream going best with `video`: in the same container when there is
+    // one, so both can be muxed together, and with the highest bitrate
+    pub fn select_audio(&self, video: Option<&Stream>) -> Option<Stream> {
+      let audios = self.get_streams_by_type(StreamType::Audio);
+      let extension = video.map(|video| video.extension.as_str());
+      let best = audios.streams.iter()
+        .max_by_key(|stream| (Some(stream.extension.as_str()) == extension, stream.bitrate))?;
+
+      get_logger().debug(format!(
+        "Picked the audio stream {} ({}) among {} audio streams",
+        best.describe(), best.extension, audios.streams.len(),
+      ));
+      Some(best.clone())
+    }
+
+    // The video stream for a `width` x `height` box and the audio stream paired with it
+    pub fn select(&self, width: u32, height: u32) -> Result<StreamSelection, Error> {
+      let video = self.select_video(width, height).ok_or(Error::StreamNotFound)?;
+      let audio = self.select_audio(Some(&video));
+      Ok(StreamSelection { video, audio })
     }
 }
 
@@ -170,23 +234,16 @@ impl StreamType {
 
       StreamType::Unknown
     }
+}
 
-    pub fn to_string(&self) -> String {
-      match self {
+impl std::fmt::Display for StreamType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+      f.write_str(match self {
         StreamType::Video => "video",
         StreamType::Audio => "audio",
         StreamType::AudioVideo => "audiovideo",
         StreamType::Unknown => "unknown",
-      }.to_string()
-    }
-
-    pub fn clone(&self) -> StreamType {
-      match self {
-        StreamType::Video => StreamType::Video,
-        StreamType::Audio => StreamType::Audio,
-        StreamType::AudioVideo => StreamType::AudioVideo,
-        StreamType::Unknown => StreamType::Unknown,
-      }
+      })
     }
 }
 
@@ -206,9 +263,11 @@ impl QualityType {
       _ => QualityType::Medium,
     }
   }
+}
 
-  pub fn to_string(&self) -> String {
-    match self {
+impl std::fmt::Display for QualityType {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(match self {
       QualityType::Tiny => "tiny",
       QualityType::Small => "small",
       QualityType::Medium => "medium",
@@ -219,21 +278,6 @@ impl QualityType {
       QualityType::HD2160 => "hd2160",
       QualityType::HD2880 => "hd2880",
       QualityType::HighRes => "highres",
-    }.to_string()
-  }
-
-  pub fn clone(&self) -> QualityType {
-    match self {
-      QualityType::Tiny => QualityType::Tiny,
-      QualityType::Small => QualityType::Small,
-      QualityType::Medium => QualityType::Medium,
-      QualityType::Large => QualityType::Large,
-      QualityType::HD720 => QualityType::HD720,
-      QualityType::HD1080 => QualityType::HD1080,
-      QualityType::HD1440 => QualityType::HD1440,
-      QualityType::HD2160 => QualityType::HD2160,
-      QualityType::HD2880 => QualityType::HD2880,
-      QualityType::HighRes => QualityType::HighRes,
-    }
+    })
   }
 }
\ No newline at end of file
@@ -40,4 +40,9 @@ impl ThumbnailList {
       ]
     }
   }
-}
\ No newline at end of file
+
+  // The largest thumbnail the video has
+  pub fn best(&self) -> Option<&Thumbnail> {
+    self.thumbnails.iter().filter(|thumbnail| !thumbnail.url.is_empty()).max_by_key(|thumbnail| thumbnail.width)
+  }
+}
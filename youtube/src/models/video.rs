@@ -1,12 +1,17 @@
+use std::time::Duration;
+
 use futures::executor::block_on;
 
-use crate::http::{get_video_info, download_video_stream};
+use crate::download::{download_stream, DownloadOptions, Progress};
+use crate::get_logger;
+use crate::http::{get_bytes, get_video_info};
+use crate::mux::{mux, Container, Metadata};
 use crate::utils::parse_number;
 use crate::models::error::Error;
-use crate::models::stream::{StreamList, QualityType};
+use crate::models::stream::{Stream, StreamList, StreamSelection};
 use crate::models::thumbnail::ThumbnailList;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub struct VideoInfo {
   // The video view count
   pub view_count: u32,
@@ -55,15 +60,24 @@ impl VideoInfo {
 
     VideoInfo {
       tags: match snippet["tags"].as_array() {
-        Some(tags) => tags.iter().map(|t| t.to_string()).collect(),
+        Some(tags) => tags.iter().filter_map(|t| t.as_str()).map(str::to_string).collect(),
         None => vec![],
       },
       category_id: parse_number(snippet["categoryId"].clone()),
       comment_count: parse_number(statistics["commentCount"].clone()),
       likes: parse_number(statistics["likeCount"].clone()),
       view_count: parse_number(statistics["viewCount"].clone()),
-      upload_date: snippet["publishedAt"].as_str().unwrap().to_string(),
-      author: snippet["channelTitle"].as_str().unwrap().to_string(),
+      upload_date: snippet["publishedAt"].as_str().unwrap_or_default().to_string(),
+      author: snippet["channelTitle"].as_str().unwrap_or_default().to_string(),
+    }
+  }
+
+  // The snippet and the statistics of a video, without fetching its streams
+  pub fn fetch(id: &str) -> Result<VideoInfo, Error> {
+    let video_info = block_on(get_video_info(id)).map_err(|e| Error::NetworkError(Box::new(e)))?;
+    match video_info["items"].get(0) {
+      Some(item) => Ok(VideoInfo::from_json(item.clone())),
+      None => Err(Error::VideoNotFound),
     }
   }
 }
@@ -75,7 +89,7 @@ impl Video {
       Err(e) => return Err(Error::NetworkError(Box::new(e))),
     };
 
-    if video_info["items"].as_array().unwrap().len() == 0 {
+    if video_info["items"].as_array().is_none_or(|items| items.is_empty()) {
       return Err(Error::VideoNotFound);
     }
 
@@ -96,30 +110,92 @@ impl Video {
     }
   }
 
-  pub fn download(&self) -> String {
-    //crate::get_logger().info(format!("Downloading video {}", self.id));
+  // Where a stream of the video is downloaded, `data/<id>/<StreamType>/`
+  pub fn stream_path(&self, stream: &Stream) -> Result<String, Error> {
+    let path = format!("data/{}/{:?}", self.id, stream.stream_type);
+    std::fs::create_dir_all(path.clone()).map_err(Error::FileError)?;
+    // Audio streams have no quality label, their bitrate tells them apart
+    let label = match stream.quality_label.as_str() {
+      "" => format!("{}k", stream.bitrate / 1000),
+      label => label.to_string(),
+    };
+    Ok(format!("{}/{}.{}", path, label, stream.extension))
+  }
+
+  // Save the largest thumbnail next to the streams, for the exported files
+  fn download_thumbnail(&self) -> Result<String, Error> {
+    let thumbnail = self.thumbnails.best().ok_or(Error::FileError(std::io::ErrorKind::NotFound.into()))?;
+    let bytes = get_bytes(&thumbnail.url).map_err(|e| Error::NetworkError(Box::new(e)))?;
+    let path = format!("data/{}/thumbnail.jpg", self.id);
+    std::fs::write(&path, bytes).map_err(Error::FileError)?;
+    Ok(path)
+  }
 
-    let mut stream = self.streams.get_best_stream(QualityType::Small);
-    //crate::get_logger().info(format!("Downloading stream {}", stream.quality_label));
+  // Where `export` writes the video
+  pub fn export_path(&self, container: Container) -> String {
+    format!("data/{}/{}.{}", self.id, self.id, container.extension())
+  }
 
-    let path = format!("data/{}/{:?}", self.id, stream.stream_type);
-    std::fs::create_dir_all(path.clone())
-      .expect("Failed to create videos directory");
-
-    stream.set_file_path(format!("{}/{}.{}", path, stream.quality_label, stream.extension));
-
-    stream = match block_on(download_video_stream(stream)) {
-        Ok(r) => {
-          //crate::get_logger().info(format!("Downloaded video {}", self.id));
-          r
-        },
-        Err(e) => {
-          //crate::get_logger().error(format!("Failed to download video {}", self.id));
-          //crate::get_logger().error(format!("{:?}", e));
-          panic!();
-        }
+  // Download the streams of `selection`, see `StreamList::select`, and mux them into one
+  // `export_path` file with the title, author and thumbnail of the video.
+  // `on_progress` sees both streams as one download.
+  pub fn export(&self, selection: StreamSelection, container: Container, on_progress: &(dyn Fn(Progress) -> bool + Sync)) -> Result<String, Error> {
+    get_logger().info(format!("Exporting video {} to {}", self.id, container));
+    let mut streams: Vec<Stream> = [Some(selection.video), selection.audio].into_iter().flatten().collect();
+
+    let mut done = 0;
+    for index in 0..streams.len() {
+      let path = self.stream_path(&streams[index])?;
+      streams[index].set_file_path(path);
+      // Sizes of the streams left, the current one is known once its download started
+      let remaining: u64 = streams[index + 1..].iter().map(|stream| stream.content_length as u64).sum();
+      download_stream(&streams[index], &DownloadOptions::default(), &|progress| {
+        on_progress(Progress { downloaded: done + progress.downloaded, total: done + progress.total + remaining })
+      })?;
+      done += std::fs::metadata(&streams[index].file_path).map_err(Error::FileError)?.len();
+    }
+
+    // The export is still worth it without its cover
+    let thumbnail = match self.download_thumbnail() {
+      Ok(path) => Some(path),
+      Err(e) => {
+        get_logger().warn(format!("Exporting {} without its thumbnail: {}", self.id, e));
+        None
+      },
     };
+    let metadata = Metadata {
+      title: self.title.clone(),
+      author: self.info.author.clone(),
+      description: self.description.clone(),
+      date: self.info.upload_date.clone(),
+      thumbnail,
+      chapters: api::chapters::parse_description(&self.description),
+      duration: Some(Duration::from_millis(streams[0].duration as u64)).filter(|duration| !duration.is_zero()),
+    };
+
+    let output = self.export_path(container);
+    let audio = streams.get(1).map(|stream| stream.file_path.as_str());
+    mux(&streams[0].file_path, audio, &metadata, &output, container)?;
+    get_logger().info(format!("Exported video {} to {}", self.id, output));
+    Ok(output)
+  }
+
+  // Download the video stream fitting a `width` x `height` box, see `StreamList::select_video`.
+  // `on_progress` is called from the download threads as the bytes arrive, see `download_stream`.
+  pub fn download(&self, width: u32, height: u32, on_progress: &(dyn Fn(Progress) -> bool + Sync)) -> Result<String, Error> {
+    get_logger().info(format!("Downloading video {}", self.id));
+
+    let mut stream = self.streams.select_video(width, height).ok_or(Error::StreamNotFound)?;
+    get_logger().info(format!("Downloading stream {}", stream.quality_label));
+
+    stream.set_file_path(self.stream_path(&stream)?);
+
+    if let Err(e) = download_stream(&stream, &DownloadOptions::default(), on_progress) {
+      get_logger().error(format!("Failed to download video {}: {}", self.id, e));
+      return Err(e);
+    }
+    get_logger().info(format!("Downloaded video {}", self.id));
 
-    stream.file_path.clone()
+    Ok(stream.file_path.clone())
   }
 }
\ No newline at end of file
@@ -0,0 +1,189 @@
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use api::chapters::Chapter;
+
+use crate::get_logger;
+use crate::models::error::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+  Mkv,
+  Mp4,
+}
+
+// What is written in the container besides the streams
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+  pub title: String,
+  pub author: String,
+  pub description: String,
+  // Upload date of the video
+  pub date: String,
+  // A jpeg image embedded as the cover
+  pub thumbnail: Option<String>,
+  // Written as the chapters of the container, the last one running until `duration`
+  pub chapters: Vec<Chapter>,
+  pub duration: Option<Duration>,
+}
+
+impl Container {
+  pub fn extension(&self) -> &'static str {
+    match self {
+      Container::Mkv => "mkv",
+      Container::Mp4 => "mp4",
+    }
+  }
+}
+
+impl std::fmt::Display for Container {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.extension())
+  }
+}
+
+impl std::str::FromStr for Container {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Container, String> {
+    match value.to_ascii_lowercase().as_str() {
+      "mkv" => Ok(Container::Mkv),
+      "mp4" => Ok(Container::Mp4),
+      _ => Err(format!("Unknown container {}, expected mkv or mp4", value)),
+    }
+  }
+}
+
+// Escape the characters ffmetadata files give a meaning to
+fn escape_metadata(value: &str) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  for c in value.chars() {
+    if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
+// An ffmetadata file holding the chapters, read by ffmpeg as an input
+fn chapters_file(chapters: &[Chapter], duration: Option<Duration>) -> String {
+  let mut file = ";FFMETADATA1\n".to_string();
+  for (index, chapter) in chapters.iter().enumerate() {
+    let end = match chapters.get(index + 1) {
+      Some(next) => next.start,
+      None => duration.filter(|duration| *duration > chapter.start).unwrap_or(chapter.start + Duration::from_secs(1)),
+    };
+    file.push_str(&format!(
+      "[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+      chapter.start.as_millis(),
+      end.as_millis(),
+      escape_metadata(&chapter.title),
+    ));
+  }
+  file
+}
+
+// The ffmpeg arguments writing `metadata` into a `container`, `thumbnail_input`
+// being the index of the thumbnail among the inputs
+fn metadata_args(metadata: &Metadata, container: Container, thumbnail_input: usize) -> Vec<String> {
+  let mut args = vec![];
+  for (key, value) in [
+    ("title", &metadata.title),
+    ("artist", &metadata.author),
+    ("comment", &metadata.description),
+    ("date", &metadata.date),
+  ] {
+    if !value.is_empty() {
+      args.extend(["-metadata".to_string(), format!("{}={}", key, value)]);
+    }
+  }
+
+  if let Some(thumbnail) = &metadata.thumbnail {
+    match container {
+      // Matroska keeps the cover as an attachment
+      Container::Mkv => args.extend([
+        "-attach", thumbnail,
+        "-metadata:s:t", "mimetype=image/jpeg",
+        "-metadata:s:t", "filename=cover.jpg",
+      ].map(str::to_string)),
+      // MP4 keeps it as a video track flagged as the cover
+      Container::Mp4 => args.extend([
+        "-map".to_string(), thumbnail_input.to_string(),
+        "-disposition:v:1".to_string(), "attached_pic".to_string(),
+      ]),
+    }
+  }
+  args
+}
+
+// Copy the `video` and `audio` tracks into one `output` file with an ffmpeg process,
+// nothing is re-encoded
+pub fn mux(video: &str, audio: Option<&str>, metadata: &Metadata, output: &str, container: Container) -> Result<(), Error> {
+  let mut command = Command::new("ffmpeg");
+  command.args(["-hide_banner", "-loglevel", "error", "-y", "-i", video]);
+  if let Some(audio) = audio {
+    command.args(["-i", audio]);
+  }
+  // The attachments of Matroska are not inputs
+  let thumbnail_input = 1 + audio.iter().count();
+  if let Some(thumbnail) = metadata.thumbnail.as_ref().filter(|_| container == Container::Mp4) {
+    command.args(["-i", thumbnail]);
+  }
+  // The chapters come last, after the cover of an MP4
+  let chapters_input = thumbnail_input + usize::from(metadata.thumbnail.is_some() && container == Container::Mp4);
+  let chapters_path = format!("{}.chapters", output);
+  if !metadata.chapters.is_empty() {
+    std::fs::write(&chapters_path, chapters_file(&metadata.chapters, metadata.duration)).map_err(Error::FileError)?;
+    command.args(["-f", "ffmetadata", "-i", &chapters_path]);
+    command.args(["-map_chapters".to_string(), chapters_input.to_string()]);
+  }
+
+  command.args(["-map", "0:v:0"]);
+  if audio.is_some() {
+    command.args(["-map", "1:a:0"]);
+  }
+  command.args(["-c", "copy"]);
+  command.args(metadata_args(metadata, container, thumbnail_input));
+  if container == Container::Mp4 {
+    // Let the players start before the whole file is read
+    command.args(["-movflags", "+faststart"]);
+  }
+  command.args(["-f", if container == Container::Mkv { "matroska" } else { "mp4" }, output]);
+
+  get_logger().debug(format!("Muxing with {:?}", command));
+  let result = command.stdin(Stdio::null()).output();
+  if !metadata.chapters.is_empty() {
+    let _ = std::fs::remove_file(&chapters_path);
+  }
+  let result = result.map_err(Error::FileError)?;
+  if !result.status.success() {
+    let _ = std::fs::remove_file(output);
+    return Err(Error::MuxError(String::from_utf8_lossy(&result.stderr).trim().to_string()));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn chapter(title: &str, seconds: u64) -> Chapter {
+    Chapter { title: title.to_string(), start: Duration::from_secs(seconds) }
+  }
+
+  #[test]
+  fn writes_the_chapters_as_ffmetadata() {
+    let chapters = [chapter("Intro", 0), chapter("Q&A; part=2 #1", 90)];
+    assert_eq!(chapters_file(&chapters, Some(Duration::from_secs(120))), concat!(
+      ";FFMETADATA1\n",
+      "[CHAPTER]\nTIMEBASE=1/1000\nSTART=0\nEND=90000\ntitle=Intro\n",
+      "[CHAPTER]\nTIMEBASE=1/1000\nSTART=90000\nEND=120000\ntitle=Q&A\\; part\\=2 \\#1\n",
+    ));
+  }
+
+  #[test]
+  fn ends_the_last_chapter_without_a_duration() {
+    assert!(chapters_file(&[chapter("Outro", 60)], None).contains("START=60000\nEND=61000\n"));
+  }
+}
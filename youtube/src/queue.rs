@@ -0,0 +1,591 @@
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::download::{download_stream, DownloadOptions, Progress};
+use crate::get_logger;
+use crate::models::error::Error;
+use crate::models::video::Video;
+use crate::mux::Container;
+
+// How often the progress of the running jobs is written to disk
+static SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JobState {
+  Queued,
+  Running,
+  Paused,
+  Done,
+  Failed(String),
+  Cancelled,
+}
+
+// A video waiting in the queue, or downloaded from it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+  pub video_id: String,
+  // The playlist the video was queued with
+  pub playlist_id: Option<String>,
+  // The video title, once it was fetched
+  pub title: Option<String>,
+  pub state: JobState,
+  pub progress: Progress,
+  // Where the video is downloaded, once a stream was picked
+  pub path: Option<String>,
+  // The stream files muxed into `path`, when the streams are exported together
+  #[serde(default)]
+  pub parts: Vec<String>,
+  // Number of times the download was started
+  pub attempts: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct QueueOptions {
+  // The json file the queue is kept in
+  pub path: String,
+  // Number of videos downloaded at the same time
+  pub jobs: usize,
+  // Box the downloaded streams should fill, see `StreamList::select_video`
+  pub width: u32,
+  pub height: u32,
+  // Mux the video and audio streams into this container rather than keeping the video stream alone
+  pub container: Option<Container>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct QueueFile {
+  jobs: Vec<Job>,
+}
+
+struct QueueState {
+  jobs: Vec<Job>,
+  // Stop flags of the running downloads, by video id
+  running: HashMap<String, Arc<AtomicBool>>,
+  // The states last written to disk, to tell the changes made by another process
+  saved: HashMap<String, JobState>,
+  last_save: Instant,
+  stopped: bool,
+}
+
+// Downloads the video of a job, stopping with `Error::Cancelled` once the flag is set
+type Runner = dyn Fn(&DownloadManager, &str, &AtomicBool) -> Result<(), Error> + Send + Sync;
+
+struct Shared {
+  options: QueueOptions,
+  runner: Box<Runner>,
+  state: Mutex<QueueState>,
+  // Signaled when a job is queued or the manager stops
+  wake: Condvar,
+}
+
+// Downloads queued videos on worker threads. The queue is saved to disk so it
+// survives restarts and can be driven from another process, see `refresh`.
+#[derive(Clone)]
+pub struct DownloadManager {
+  shared: Arc<Shared>,
+}
+
+impl JobState {
+  // Whether the job is over, for now or for good
+  pub fn is_finished(&self) -> bool {
+    !matches!(self, JobState::Queued | JobState::Running)
+  }
+}
+
+impl std::fmt::Display for JobState {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      JobState::Queued => write!(f, "queued"),
+      JobState::Running => write!(f, "running"),
+      JobState::Paused => write!(f, "paused"),
+      JobState::Done => write!(f, "done"),
+      JobState::Failed(reason) => write!(f, "failed: {}", reason),
+      JobState::Cancelled => write!(f, "cancelled"),
+    }
+  }
+}
+
+impl Job {
+  fn new(video_id: String, playlist_id: Option<String>) -> Job {
+    Job {
+      video_id,
+      playlist_id,
+      title: None,
+      state: JobState::Queued,
+      progress: Progress::default(),
+      path: None,
+      parts: vec![],
+      attempts: 0,
+    }
+  }
+
+  // Whether `id` names the video or the playlist of the job, `None` names every job
+  fn matches(&self, id: Option<&str>) -> bool {
+    match id {
+      Some(id) => self.video_id == id || self.playlist_id.as_deref() == Some(id),
+      None => true,
+    }
+  }
+
+  // Delete what was downloaded
+  fn remove_files(&self) {
+    for path in self.path.iter().chain(&self.parts) {
+      let _ = std::fs::remove_file(path);
+      let _ = std::fs::remove_file(format!("{}.part", path));
+    }
+  }
+}
+
+fn read_queue(path: &str) -> Result<Vec<Job>, Error> {
+  match std::fs::read_to_string(path) {
+    Ok(json) => serde_json::from_str::<QueueFile>(&json)
+      .map(|file| file.jobs)
+      .map_err(|e| Error::FileError(std::io::Error::new(std::io::ErrorKind::InvalidData, e))),
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+    Err(e) => Err(Error::FileError(e)),
+  }
+}
+
+impl QueueState {
+  fn save(&mut self, path: &str) {
+    let json = serde_json::to_string_pretty(&QueueFile { jobs: self.jobs.clone() }).expect("Jobs are serializable");
+    // Written aside then renamed, so a reader never sees half a queue
+    let temp = format!("{}.tmp", path);
+    if let Some(parent) = std::path::Path::new(path).parent() {
+      let _ = std::fs::create_dir_all(parent);
+    }
+    match std::fs::write(&temp, json).and_then(|_| std::fs::rename(&temp, path)) {
+      Ok(()) => {
+        self.saved = self.jobs.iter().map(|job| (job.video_id.clone(), job.state.clone())).collect();
+        self.last_save = Instant::now();
+      },
+      Err(e) => get_logger().error(format!("Failed to save the download queue to {}: {}", path, e)),
+    }
+  }
+
+  // Move a job to `state`, stopping its download when it is running
+  fn transition(&mut self, index: usize, state: JobState) {
+    let job = &mut self.jobs[index];
+    if let Some(stop) = self.running.get(&job.video_id) {
+      stop.store(true, Ordering::Relaxed);
+    } else if state == JobState::Cancelled {
+      // Nobody downloads it, the files can go now
+      job.remove_files();
+      job.progress = Progress::default();
+    }
+    job.state = state;
+  }
+}
+
+impl DownloadManager {
+  // Load the queue kept at `options.path`. The jobs that were running when the
+  // last process stopped are queued again, their download resumes.
+  pub fn open(options: QueueOptions) -> Result<DownloadManager, Error> {
+    DownloadManager::with_runner(options, Box::new(|manager, video_id, stop| manager.download(video_id, stop)))
+  }
+
+  fn with_runner(options: QueueOptions, runner: Box<Runner>) -> Result<DownloadManager, Error> {
+    let mut jobs = read_queue(&options.path)?;
+    for job in jobs.iter_mut().filter(|job| job.state == JobState::Running) {
+      job.state = JobState::Queued;
+    }
+    let saved = jobs.iter().map(|job| (job.video_id.clone(), job.state.clone())).collect();
+
+    Ok(DownloadManager {
+      shared: Arc::new(Shared {
+        options,
+        runner,
+        state: Mutex::new(QueueState { jobs, running: HashMap::new(), saved, last_save: Instant::now(), stopped: false }),
+        wake: Condvar::new(),
+      }),
+    })
+  }
+
+  fn state(&self) -> MutexGuard<'_, QueueState> {
+    self.shared.state.lock().expect("Failed to lock DownloadManager")
+  }
+
+  fn save(&self, state: &mut QueueState) {
+    state.save(&self.shared.options.path);
+  }
+
+  // Queue a video, returns false when it is already in the queue
+  pub fn add_video(&self, video_id: &str) -> bool {
+    self.add(vec![video_id.to_string()], None) == 1
+  }
+
+  // Queue the videos of a playlist, returns how many were not in the queue yet
+  pub fn add_playlist(&self, playlist_id: &str, video_ids: Vec<String>) -> usize {
+    self.add(video_ids, Some(playlist_id))
+  }
+
+  fn add(&self, video_ids: Vec<String>, playlist_id: Option<&str>) -> usize {
+    let mut state = self.state();
+    let mut added = 0;
+    for video_id in video_ids {
+      if state.jobs.iter().any(|job| job.video_id == video_id) {
+        continue;
+      }
+      state.jobs.push(Job::new(video_id, playlist_id.map(str::to_string)));
+      added += 1;
+    }
+    self.save(&mut state);
+    self.shared.wake.notify_all();
+    added
+  }
+
+  // Apply `state` to the jobs matching `id` that are in one of the `from` states,
+  // returns how many jobs changed
+  fn set_state(&self, id: Option<&str>, from: &[fn(&JobState) -> bool], to: JobState) -> usize {
+    let mut state = self.state();
+    let indexes: Vec<usize> = state.jobs.iter().enumerate()
+      .filter(|(_, job)| job.matches(id) && from.iter().any(|from| from(&job.state)))
+      .map(|(index, _)| index)
+      .collect();
+    for index in &indexes {
+      state.transition(*index, to.clone());
+    }
+    self.save(&mut state);
+    self.shared.wake.notify_all();
+    indexes.len()
+  }
+
+  // Stop the matching downloads, keeping what was fetched
+  pub fn pause(&self, id: Option<&str>) -> usize {
+    self.set_state(id, &[|state| matches!(state, JobState::Queued | JobState::Running)], JobState::Paused)
+  }
+
+  pub fn resume(&self, id: Option<&str>) -> usize {
+    self.set_state(id, &[|state| *state == JobState::Paused], JobState::Queued)
+  }
+
+  // Stop the matching downloads and delete their files
+  pub fn cancel(&self, id: Option<&str>) -> usize {
+    self.set_state(id, &[|state| !matches!(state, JobState::Done | JobState::Cancelled)], JobState::Cancelled)
+  }
+
+  // Queue the failed and cancelled downloads again
+  pub fn retry(&self, id: Option<&str>) -> usize {
+    self.set_state(id, &[|state| matches!(state, JobState::Failed(_) | JobState::Cancelled)], JobState::Queued)
+  }
+
+  // Forget the done and cancelled jobs, returns how many were removed
+  pub fn clear(&self) -> usize {
+    let mut state = self.state();
+    let count = state.jobs.len();
+    state.jobs.retain(|job| !matches!(job.state, JobState::Done | JobState::Cancelled));
+    let removed = count - state.jobs.len();
+    self.save(&mut state);
+    removed
+  }
+
+  // A snapshot of the queue
+  pub fn jobs(&self) -> Vec<Job> {
+    self.state().jobs.clone()
+  }
+
+  // Whether some jobs are still queued or running
+  pub fn is_busy(&self) -> bool {
+    self.state().jobs.iter().any(|job| !job.state.is_finished())
+  }
+
+  // Pick up the changes another process made to the queue file: added jobs, and
+  // jobs it paused, resumed, cancelled or retried
+  pub fn refresh(&self) -> Result<(), Error> {
+    let jobs = read_queue(&self.shared.options.path)?;
+    let mut state = self.state();
+    let mut changed = false;
+    for job in jobs {
+      let index = match state.jobs.iter().position(|known| known.video_id == job.video_id) {
+        Some(index) => index,
+        None => {
+          state.jobs.push(Job { state: JobState::Queued, ..job });
+          changed = true;
+          continue;
+        },
+      };
+      let saved = state.saved.get(&job.video_id).cloned();
+      if saved.as_ref() != Some(&job.state) && state.jobs[index].state != job.state {
+        get_logger().info(format!("Download of {} was set to {} from outside", job.video_id, job.state));
+        state.transition(index, job.state);
+        changed = true;
+      }
+    }
+    if changed {
+      self.save(&mut state);
+      self.shared.wake.notify_all();
+    }
+    Ok(())
+  }
+
+  // Start the worker threads, they run until `stop` is called
+  pub fn start(&self) {
+    // The video infos are fetched with reqwest, which needs the tokio runtime of the caller
+    let runtime = tokio::runtime::Handle::try_current().ok();
+    for _ in 0..self.shared.options.jobs.max(1) {
+      let manager = self.clone();
+      let runtime = runtime.clone();
+      std::thread::spawn(move || {
+        let _guard = runtime.as_ref().map(|runtime| runtime.enter());
+        manager.work();
+      });
+    }
+  }
+
+  // Let the workers finish, the running downloads are paused and resumed on the next start
+  pub fn stop(&self) {
+    let mut state = self.state();
+    state.stopped = true;
+    for stop in state.running.values() {
+      stop.store(true, Ordering::Relaxed);
+    }
+    self.shared.wake.notify_all();
+  }
+
+  // Take the next queued job, waiting for one
+  fn next_job(&self) -> Option<(String, Arc<AtomicBool>)> {
+    let mut state = self.state();
+    loop {
+      if state.stopped {
+        return None;
+      }
+      if let Some(job) = state.jobs.iter_mut().find(|job| job.state == JobState::Queued) {
+        job.state = JobState::Running;
+        job.attempts += 1;
+        let video_id = job.video_id.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+        state.running.insert(video_id.clone(), stop.clone());
+        self.save(&mut state);
+        return Some((video_id, stop));
+      }
+      state = self.shared.wake.wait(state).expect("Failed to lock DownloadManager");
+    }
+  }
+
+  fn update(&self, video_id: &str, update: impl FnOnce(&mut Job)) {
+    let mut state = self.state();
+    if let Some(job) = state.jobs.iter_mut().find(|job| job.video_id == video_id) {
+      update(job);
+    }
+    if state.last_save.elapsed() > SAVE_INTERVAL {
+      self.save(&mut state);
+    }
+  }
+
+  fn work(&self) {
+    while let Some((video_id, stop)) = self.next_job() {
+      let result = std::panic::catch_unwind(AssertUnwindSafe(|| (self.shared.runner)(self, &video_id, &stop)))
+        .unwrap_or_else(|_| Err(Error::NetworkError("The download panicked".into())));
+
+      let mut state = self.state();
+      state.running.remove(&video_id);
+      let stopped = state.stopped;
+      if let Some(job) = state.jobs.iter_mut().find(|job| job.video_id == video_id) {
+        match result {
+          Ok(()) => {
+            get_logger().success(format!("Downloaded {}", video_id));
+            job.state = JobState::Done;
+          },
+          // Stopped on purpose, the job already has its new state
+          Err(Error::Cancelled) => match job.state {
+            JobState::Cancelled => {
+              job.remove_files();
+              job.progress = Progress::default();
+            },
+            JobState::Running if stopped => job.state = JobState::Queued,
+            _ => {},
+          },
+          Err(e) => {
+            get_logger().error(format!("Failed to download {}: {}", video_id, e));
+            job.state = JobState::Failed(e.to_string());
+          },
+        }
+      }
+      self.save(&mut state);
+    }
+  }
+
+  fn download(&self, video_id: &str, stop: &AtomicBool) -> Result<(), Error> {
+    let options = &self.shared.options;
+    let video = Video::new(video_id)?;
+    let on_progress = |progress| {
+      self.update(video_id, |job| job.progress = progress);
+      !stop.load(Ordering::Relaxed)
+    };
+
+    if let Some(container) = options.container {
+      let selection = video.streams.select(options.width, options.height)?;
+      let parts = [Some(&selection.video), selection.audio.as_ref()].into_iter().flatten()
+        .map(|stream| video.stream_path(stream))
+        .collect::<Result<Vec<String>, Error>>()?;
+      self.update(video_id, |job| {
+        job.title = Some(video.title.clone());
+        job.path = Some(video.export_path(container));
+        job.parts = parts;
+      });
+      return video.export(selection, container, &on_progress).map(|_| ());
+    }
+
+    let mut stream = video.streams.select_video(options.width, options.height).ok_or(Error::StreamNotFound)?;
+    stream.set_file_path(video.stream_path(&stream)?);
+    self.update(video_id, |job| {
+      job.title = Some(video.title.clone());
+      job.path = Some(stream.file_path.clone());
+    });
+    download_stream(&stream, &DownloadOptions::default(), &on_progress)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::atomic::AtomicUsize;
+
+  // A queue file of its own for each test
+  fn options(name: &str, jobs: usize) -> QueueOptions {
+    let path = std::env::temp_dir().join(format!("youtube-queue-{}-{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    QueueOptions { path: path.to_string_lossy().into_owned(), jobs, width: 0, height: 0, container: None }
+  }
+
+  fn state_of(manager: &DownloadManager, video_id: &str) -> JobState {
+    manager.jobs().into_iter().find(|job| job.video_id == video_id).expect("The job is queued").state
+  }
+
+  // Wait for the workers to bring the queue to `reached`
+  fn wait_for(manager: &DownloadManager, reached: impl Fn(&DownloadManager) -> bool) {
+    let start = Instant::now();
+    while !reached(manager) {
+      assert!(start.elapsed() < Duration::from_secs(5), "The queue did not settle: {:?}", manager.jobs());
+      std::thread::sleep(Duration::from_millis(5));
+    }
+  }
+
+  // Runs until the download is stopped
+  fn until_stopped(_: &DownloadManager, _: &str, stop: &AtomicBool) -> Result<(), Error> {
+    while !stop.load(Ordering::Relaxed) {
+      std::thread::sleep(Duration::from_millis(5));
+    }
+    Err(Error::Cancelled)
+  }
+
+  #[test]
+  fn pauses_resumes_cancels_and_retries() {
+    let manager = DownloadManager::with_runner(options("transitions", 1), Box::new(until_stopped)).unwrap();
+    assert!(manager.add_video("a"));
+    assert!(!manager.add_video("a"));
+    assert_eq!(manager.pause(Some("a")), 1);
+    assert_eq!(state_of(&manager, "a"), JobState::Paused);
+    assert_eq!(manager.retry(Some("a")), 0);
+    assert_eq!(manager.resume(Some("a")), 1);
+    assert_eq!(state_of(&manager, "a"), JobState::Queued);
+    assert_eq!(manager.cancel(Some("a")), 1);
+    assert_eq!(state_of(&manager, "a"), JobState::Cancelled);
+    assert_eq!(manager.retry(Some("a")), 1);
+    assert_eq!(state_of(&manager, "a"), JobState::Queued);
+    let _ = std::fs::remove_file(&manager.shared.options.path);
+  }
+
+  #[test]
+  fn retries_a_failed_download() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counted = attempts.clone();
+    let runner = Box::new(move |_: &DownloadManager, _: &str, _: &AtomicBool| match counted.fetch_add(1, Ordering::Relaxed) {
+      0 => Err(Error::NetworkError("Connection reset".into())),
+      _ => Ok(()),
+    });
+    let manager = DownloadManager::with_runner(options("retry", 1), runner).unwrap();
+    manager.add_video("a");
+    manager.start();
+    wait_for(&manager, |manager| matches!(state_of(manager, "a"), JobState::Failed(_)));
+    assert!(!manager.is_busy());
+
+    assert_eq!(manager.retry(Some("a")), 1);
+    wait_for(&manager, |manager| state_of(manager, "a") == JobState::Done);
+    assert_eq!(manager.jobs()[0].attempts, 2);
+    manager.stop();
+    let _ = std::fs::remove_file(&manager.shared.options.path);
+  }
+
+  #[test]
+  fn cancels_a_running_download_and_deletes_its_file() {
+    let file = std::env::temp_dir().join(format!("youtube-queue-cancelled-{}.mp4", std::process::id()));
+    let path = file.to_string_lossy().into_owned();
+    let runner = Box::new(move |manager: &DownloadManager, video_id: &str, stop: &AtomicBool| {
+      std::fs::write(&path, b"part of a video").unwrap();
+      manager.update(video_id, |job| job.path = Some(path.clone()));
+      until_stopped(manager, video_id, stop)
+    });
+    let manager = DownloadManager::with_runner(options("cancel", 1), runner).unwrap();
+    manager.add_video("a");
+    manager.start();
+    wait_for(&manager, |manager| manager.jobs()[0].path.is_some());
+    assert_eq!(state_of(&manager, "a"), JobState::Running);
+
+    assert_eq!(manager.cancel(Some("a")), 1);
+    wait_for(&manager, |manager| manager.state().running.is_empty());
+    assert_eq!(state_of(&manager, "a"), JobState::Cancelled);
+    assert!(!file.exists());
+    manager.stop();
+    let _ = std::fs::remove_file(&manager.shared.options.path);
+  }
+
+  #[test]
+  fn saves_and_loads_the_queue() {
+    let options = options("saved", 1);
+    let manager = DownloadManager::with_runner(options.clone(), Box::new(until_stopped)).unwrap();
+    assert_eq!(manager.add_playlist("PL", vec!["a".to_string(), "b".to_string(), "a".to_string()]), 2);
+    manager.add_video("c");
+    manager.pause(Some("b"));
+    manager.cancel(Some("c"));
+
+    let loaded = DownloadManager::open(options.clone()).unwrap();
+    let (saved, loaded) = (manager.jobs(), loaded.jobs());
+    assert_eq!(serde_json::to_value(&saved).unwrap(), serde_json::to_value(&loaded).unwrap());
+    assert_eq!(loaded[0].playlist_id.as_deref(), Some("PL"));
+    assert_eq!(loaded.iter().map(|job| job.state.clone()).collect::<Vec<_>>(), [JobState::Queued, JobState::Paused, JobState::Cancelled]);
+    let _ = std::fs::remove_file(&options.path);
+  }
+
+  #[test]
+  fn queues_the_downloads_left_running_again() {
+    let options = options("restarted", 1);
+    let manager = DownloadManager::with_runner(options.clone(), Box::new(until_stopped)).unwrap();
+    manager.add_video("a");
+    manager.start();
+    wait_for(&manager, |manager| state_of(manager, "a") == JobState::Running);
+
+    // As if the process was killed during the download
+    let loaded = DownloadManager::open(options.clone()).unwrap();
+    assert_eq!(state_of(&loaded, "a"), JobState::Queued);
+    manager.stop();
+    let _ = std::fs::remove_file(&options.path);
+  }
+
+  #[test]
+  fn runs_at_most_the_jobs_asked_at_once() {
+    let (running, most) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let (counted, highest) = (running.clone(), most.clone());
+    let runner = Box::new(move |_: &DownloadManager, _: &str, _: &AtomicBool| {
+      let now = counted.fetch_add(1, Ordering::SeqCst) + 1;
+      highest.fetch_max(now, Ordering::SeqCst);
+      std::thread::sleep(Duration::from_millis(20));
+      counted.fetch_sub(1, Ordering::SeqCst);
+      Ok(())
+    });
+    let manager = DownloadManager::with_runner(options("limit", 2), runner).unwrap();
+    manager.add_playlist("PL", (0..8).map(|index| index.to_string()).collect());
+    manager.start();
+    wait_for(&manager, |manager| !manager.is_busy());
+
+    assert!(manager.jobs().iter().all(|job| job.state == JobState::Done));
+    assert!((1..=2).contains(&most.load(Ordering::SeqCst)));
+    manager.stop();
+    let _ = std::fs::remove_file(&manager.shared.options.path);
+  }
+}
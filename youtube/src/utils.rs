@@ -2,6 +2,46 @@ pub fn get_ytb_url(id: &str) -> String {
   format!("https://www.youtube.com/watch?v={}", id)
 }
 
+// The video id of a YouTube url, or the id itself
+pub fn parse_video_id(input: &str) -> Option<String> {
+  let is_id = |id: &str| id.len() == 11 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+  if is_id(input) {
+    return Some(input.to_string());
+  }
+
+  let rest = input.split_once("://").map(|(_, rest)| rest).unwrap_or(input);
+  let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+  let host = host.trim_start_matches("www.").trim_start_matches("m.");
+  let id = match host {
+    "youtu.be" => path.split(['?', '&', '#']).next(),
+    "youtube.com" | "music.youtube.com" => match path.split_once('?') {
+      Some(("watch", query)) => query.split('&').find_map(|param| param.strip_prefix("v=")),
+      _ => ["shorts/", "embed/", "live/"].iter()
+        .find_map(|prefix| path.strip_prefix(prefix))
+        .and_then(|id| id.split(['?', '&', '#', '/']).next()),
+    },
+    _ => None,
+  }?;
+
+  if is_id(id) { Some(id.to_string()) } else { None }
+}
+
+// The playlist id of a YouTube url, or the id itself
+pub fn parse_playlist_id(input: &str) -> Option<String> {
+  let is_id = |id: &str| {
+    id.len() > 11
+      && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+      && ["PL", "UU", "FL", "LL", "RD", "OL"].iter().any(|prefix| id.starts_with(prefix))
+  };
+  if is_id(input) {
+    return Some(input.to_string());
+  }
+
+  let query = input.split_once('?')?.1;
+  let id = query.split(['&', '#']).find_map(|param| param.strip_prefix("list="))?;
+  if is_id(id) { Some(id.to_string()) } else { None }
+}
+
 pub fn get_ytb_url_info(id: &str) -> String {
   format!("https://www.youtube.com/get_video_info?video_id={}", id)
 }
@@ -12,7 +52,7 @@ pub fn get_ytb_api_info(id: &str, key: &str) -> String {
 
 pub fn parse_number(value: serde_json::Value) -> u32 {
   if value.is_null() {
-    0;
+    return 0;
   }
   if value.is_number() {
     value.as_u64().unwrap() as u32
@@ -21,4 +61,39 @@ pub fn parse_number(value: serde_json::Value) -> u32 {
   } else {
     0
   }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_video_ids() {
+    let id = Some("dQw4w9WgXcQ".to_string());
+    assert_eq!(parse_video_id("dQw4w9WgXcQ"), id);
+    assert_eq!(parse_video_id("https://www.youtube.com/watch?v=dQw4w9WgXcQ"), id);
+    assert_eq!(parse_video_id("https://m.youtube.com/watch?feature=share&v=dQw4w9WgXcQ&t=42"), id);
+    assert_eq!(parse_video_id("youtube.com/watch?v=dQw4w9WgXcQ"), id);
+    assert_eq!(parse_video_id("https://youtu.be/dQw4w9WgXcQ?t=42"), id);
+    assert_eq!(parse_video_id("https://www.youtube.com/shorts/dQw4w9WgXcQ"), id);
+    assert_eq!(parse_video_id("https://www.youtube.com/embed/dQw4w9WgXcQ?start=10"), id);
+    assert_eq!(parse_video_id("https://music.youtube.com/watch?v=dQw4w9WgXcQ&list=RDdQw4w9WgXcQ"), id);
+  }
+
+  #[test]
+  fn rejects_what_is_not_a_video() {
+    assert_eq!(parse_video_id("dQw4w9WgXc"), None);
+    assert_eq!(parse_video_id("never gonna"), None);
+    assert_eq!(parse_video_id("https://www.youtube.com/watch?v=short"), None);
+    assert_eq!(parse_video_id("https://www.youtube.com/@channel"), None);
+    assert_eq!(parse_video_id("https://vimeo.com/dQw4w9WgXcQ"), None);
+  }
+
+  #[test]
+  fn parses_playlist_ids() {
+    let id = Some("PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf".to_string());
+    assert_eq!(parse_playlist_id("PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf"), id);
+    assert_eq!(parse_playlist_id("https://www.youtube.com/playlist?list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf"), id);
+    assert_eq!(parse_playlist_id("https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf&index=2"), id);
+    assert_eq!(parse_playlist_id("dQw4w9WgXcQ"), None);
+  }
+}
@@ -0,0 +1,130 @@
+use std::io::{ErrorKind, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::audio::sink::{AudioSink, NullSink};
+use crate::audio::AudioFormat;
+use crate::get_logger;
+
+// The players tried in turn, reading raw samples on stdin
+static PLAYERS: &[&str] = &["aplay", "ffplay"];
+
+// A player child process and the pipe its samples go through
+struct Pipe {
+  child: Child,
+  stdin: ChildStdin,
+}
+
+// Play the samples through an `aplay` or `ffplay` child process, for the builds without cpal.
+// The samples are paced like the null sink, the player only keeps what it was sent in its buffer.
+pub struct CommandSink {
+  program: &'static str,
+  // Times the playback like the device would
+  clock: NullSink,
+  // Respawned after a clear, dropping what the player buffered
+  pipe: Mutex<Option<Pipe>>,
+  // The player failed, it is not restarted
+  stopped: AtomicBool,
+  // Gain, as f32 bits
+  volume: AtomicU32,
+}
+
+fn arguments(program: &str, format: AudioFormat) -> Vec<String> {
+  let (rate, channels) = (format.sample_rate.to_string(), format.channels.to_string());
+  let arguments: &[&str] = match program {
+    "aplay" => &["-q", "-t", "raw", "-f", "FLOAT_LE", "-r", &rate, "-c", &channels, "-"],
+    _ => &[
+      "-loglevel", "error", "-nodisp", "-autoexit", "-fflags", "nobuffer", "-probesize", "32",
+      "-f", "f32le", "-ar", &rate, "-ch_layout", if format.channels == 1 { "mono" } else { "stereo" }, "-i", "-",
+    ],
+  };
+  arguments.iter().map(|argument| argument.to_string()).collect()
+}
+
+fn spawn(program: &str, format: AudioFormat) -> std::io::Result<Pipe> {
+  let mut child = Command::new(program)
+    .args(arguments(program, format))
+    .stdin(Stdio::piped())
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .spawn()?;
+  let stdin = child.stdin.take().ok_or_else(|| std::io::Error::other(format!("{} has no stdin", program)))?;
+  Ok(Pipe { child, stdin })
+}
+
+impl Drop for Pipe {
+  fn drop(&mut self) {
+    let _ = self.child.kill();
+    let _ = self.child.wait();
+  }
+}
+
+impl CommandSink {
+  // The first player found on the path
+  pub fn new(format: AudioFormat) -> std::io::Result<CommandSink> {
+    for program in PLAYERS {
+      match spawn(program, format) {
+        Ok(pipe) => {
+          get_logger().debug(format!("Playing the audio through {}", program));
+          return Ok(CommandSink {
+            program,
+            clock: NullSink::new(format),
+            pipe: Mutex::new(Some(pipe)),
+            stopped: AtomicBool::new(false),
+            volume: AtomicU32::new(1.0f32.to_bits()),
+          });
+        },
+        Err(e) if e.kind() == ErrorKind::NotFound => continue,
+        Err(e) => return Err(e),
+      }
+    }
+    Err(std::io::Error::new(ErrorKind::NotFound, format!("None of {} is installed", PLAYERS.join(", "))))
+  }
+
+  fn pipe(&self) -> std::sync::MutexGuard<'_, Option<Pipe>> {
+    self.pipe.lock().expect("Failed to lock CommandSink")
+  }
+}
+
+impl AudioSink for CommandSink {
+  fn format(&self) -> AudioFormat {
+    self.clock.format()
+  }
+
+  fn write(&self, samples: &[f32]) -> std::io::Result<()> {
+    let volume = f32::from_bits(self.volume.load(Ordering::Relaxed));
+    let bytes: Vec<u8> = samples.iter().flat_map(|sample| (sample * volume).to_le_bytes()).collect();
+    {
+      let mut pipe = self.pipe();
+      if pipe.is_none() && !self.stopped.load(Ordering::Relaxed) {
+        *pipe = spawn(self.program, self.format()).ok();
+      }
+      if let Some(Err(e)) = pipe.as_mut().map(|pipe| pipe.stdin.write_all(&bytes)) {
+        // The player went away, the playback goes on muted
+        get_logger().warn(format!("{} stopped, audio is muted: {}", self.program, e));
+        self.stopped.store(true, Ordering::Relaxed);
+        *pipe = None;
+      }
+    }
+    self.clock.write(samples)
+  }
+
+  fn played(&self) -> Duration {
+    self.clock.played()
+  }
+
+  fn clear(&self) {
+    *self.pipe() = None;
+    self.clock.clear();
+  }
+
+  fn set_paused(&self, paused: bool) {
+    self.clock.set_paused(paused);
+  }
+
+  fn set_volume(&self, volume: f32) {
+    self.volume.store(volume.to_bits(), Ordering::Relaxed);
+  }
+}
@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+use std::io::Error;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample};
+
+use crate::audio::sink::{AudioSink, SINK_BUFFER};
+use crate::audio::AudioFormat;
+use crate::get_logger;
+
+// Samples shared between the writer and the device callback
+struct Shared {
+  queue: Mutex<VecDeque<f32>>,
+  // Notified when the callback made room in the queue
+  space: Condvar,
+  // Number of samples the device consumed
+  played: AtomicU64,
+  paused: AtomicBool,
+  // Gain, as f32 bits
+  volume: AtomicU32,
+}
+
+// Play the samples on the default output device, through cpal
+pub struct CpalSink {
+  format: AudioFormat,
+  shared: Arc<Shared>,
+  // Dropping the sender ends the thread owning the stream
+  _stop: mpsc::Sender<()>,
+}
+
+fn to_io<E: std::fmt::Display>(e: E) -> Error {
+  Error::other(e.to_string())
+}
+
+fn build_stream<T>(device: &cpal::Device, config: &cpal::StreamConfig, shared: Arc<Shared>) -> std::io::Result<cpal::Stream>
+where
+  T: SizedSample + FromSample<f32>,
+{
+  device.build_output_stream(
+    config,
+    move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+      if shared.paused.load(Ordering::Relaxed) {
+        data.iter_mut().for_each(|out| *out = T::EQUILIBRIUM);
+        return;
+      }
+
+      let volume = f32::from_bits(shared.volume.load(Ordering::Relaxed));
+      let mut queue = shared.queue.lock().expect("Failed to lock the audio queue");
+      let mut played = 0;
+      for out in data.iter_mut() {
+        *out = match queue.pop_front() {
+          Some(sample) => {
+            played += 1;
+            T::from_sample(sample * volume)
+          },
+          None => T::EQUILIBRIUM,
+        };
+      }
+      shared.played.fetch_add(played, Ordering::Relaxed);
+      shared.space.notify_all();
+    },
+    |e| get_logger().error(format!("Audio stream error: {}", e)),
+    None,
+  ).map_err(to_io)
+}
+
+impl CpalSink {
+  pub fn new() -> std::io::Result<CpalSink> {
+    let shared = Arc::new(Shared {
+      queue: Mutex::new(VecDeque::new()),
+      space: Condvar::new(),
+      played: AtomicU64::new(0),
+      paused: AtomicBool::new(false),
+      volume: AtomicU32::new(1.0f32.to_bits()),
+    });
+
+    // cpal streams can not move between threads, one is kept alive on its own thread
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    let (ready_tx, ready_rx) = mpsc::channel::<std::io::Result<AudioFormat>>();
+    let stream_shared = shared.clone();
+    std::thread::spawn(move || {
+      let stream = (|| {
+        let device = cpal::default_host()
+          .default_output_device()
+          .ok_or_else(|| Error::new(std::io::ErrorKind::NotFound, "No default output device"))?;
+        let supported = device.default_output_config().map_err(to_io)?;
+        let config = supported.config();
+        let stream = match supported.sample_format() {
+          SampleFormat::F32 => build_stream::<f32>(&device, &config, stream_shared)?,
+          SampleFormat::I16 => build_stream::<i16>(&device, &config, stream_shared)?,
+          SampleFormat::U16 => build_stream::<u16>(&device, &config, stream_shared)?,
+          format => return Err(Error::other(format!("Unsupported sample format {}", format))),
+        };
+        stream.play().map_err(to_io)?;
+        Ok((stream, AudioFormat { sample_rate: config.sample_rate.0, channels: config.channels }))
+      })();
+
+      match stream {
+        Ok((stream, format)) => {
+          let _ = ready_tx.send(Ok(format));
+          let _ = stop_rx.recv();
+          drop(stream);
+        },
+        Err(e) => {
+          let _ = ready_tx.send(Err(e));
+        },
+      }
+    });
+
+    let format = ready_rx.recv().map_err(to_io)??;
+    get_logger().debug(format!("Audio device opened with {:?}", format));
+    Ok(CpalSink { format, shared, _stop: stop_tx })
+  }
+}
+
+impl AudioSink for CpalSink {
+  fn format(&self) -> AudioFormat {
+    self.format
+  }
+
+  fn write(&self, samples: &[f32]) -> std::io::Result<()> {
+    let capacity = (SINK_BUFFER.as_secs_f64() * self.format.sample_rate as f64 * self.format.channels as f64) as usize;
+    let mut queue = self.shared.queue.lock().expect("Failed to lock the audio queue");
+    // Wait for the device to make room, with a timeout in case it stalled
+    while queue.len() + samples.len() > capacity.max(samples.len()) {
+      let (guard, timeout) = self.shared.space
+        .wait_timeout(queue, Duration::from_millis(500))
+        .expect("Failed to lock the audio queue");
+      queue = guard;
+      if timeout.timed_out() {
+        break;
+      }
+    }
+    queue.extend(samples.iter().copied());
+    Ok(())
+  }
+
+  fn played(&self) -> Duration {
+    self.format.duration(self.shared.played.load(Ordering::Relaxed))
+  }
+
+  fn clear(&self) {
+    self.shared.queue.lock().expect("Failed to lock the audio queue").clear();
+    self.shared.played.store(0, Ordering::Relaxed);
+    self.shared.space.notify_all();
+  }
+
+  fn set_paused(&self, paused: bool) {
+    self.shared.paused.store(paused, Ordering::Relaxed);
+  }
+
+  fn set_volume(&self, volume: f32) {
+    self.shared.volume.store(volume.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+  }
+}
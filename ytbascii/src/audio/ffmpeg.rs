@@ -0,0 +1,167 @@
+use std::io::{Error, ErrorKind, Read};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::time::Duration;
+
+use crate::audio::{AudioFormat, AudioSource};
+use crate::get_logger;
+use crate::media::MediaInput;
+
+// Number of samples read at once, per channel
+static CHUNK_FRAMES: usize = 2048;
+
+// Decode an audio track with an `ffmpeg` child process, reading f32le samples on its stdout
+pub struct FfmpegAudioSource {
+  input: MediaInput,
+  format: AudioFormat,
+  child: Child,
+  stdout: ChildStdout,
+}
+
+// atempo only handles factors from 0.5 to 2.0, larger changes are chained
+fn tempo_filter(speed: f64) -> String {
+  let mut speed = speed;
+  let mut filters = vec![];
+  while speed < 0.5 {
+    filters.push("atempo=0.5".to_string());
+    speed /= 0.5;
+  }
+  while speed > 2.0 {
+    filters.push("atempo=2.0".to_string());
+    speed /= 2.0;
+  }
+  filters.push(format!("atempo={}", speed));
+  filters.join(",")
+}
+
+// The ffmpeg command decoding the audio of `input` from `start`, `speed` times faster
+fn command(input: &MediaInput, format: AudioFormat, start: Duration, speed: f64) -> std::io::Result<Command> {
+  let mut command = Command::new("ffmpeg");
+  command.args(["-hide_banner", "-loglevel", "error", "-nostdin"]);
+  if !start.is_zero() {
+    command.arg("-ss").arg(format!("{:.3}", start.as_secs_f64()));
+  }
+  match input {
+    MediaInput::File(path) => {
+      command.arg("-i").arg(path);
+      command.stdin(Stdio::null());
+    },
+    MediaInput::Stdin => return Err(Error::new(ErrorKind::Unsupported, "Audio cannot be read from stdin")),
+    // As for the video, the fetched bytes are piped from the start only
+    MediaInput::Stream(stream) if start.is_zero() && !stream.is_read() => {
+      command.args(["-i", "pipe:0"]);
+      command.stdin(Stdio::piped());
+    },
+    MediaInput::Stream(stream) => {
+      command.arg("-i").arg(stream.url());
+      command.stdin(Stdio::null());
+    },
+  }
+  command.args(["-vn", "-f", "f32le", "-acodec", "pcm_f32le"]);
+  if speed != 1.0 {
+    command.arg("-af").arg(tempo_filter(speed));
+  }
+  command.arg("-ac").arg(format.channels.to_string());
+  command.arg("-ar").arg(format.sample_rate.to_string());
+  command.arg("pipe:1");
+  command.stdout(Stdio::piped());
+  command.stderr(Stdio::null());
+  Ok(command)
+}
+
+fn spawn(input: &MediaInput, format: AudioFormat, start: Duration, speed: f64) -> std::io::Result<(Child, ChildStdout)> {
+  let mut command = command(input, format, start, speed)?;
+  get_logger().debug(format!("Spawning {:?}", command));
+  let mut child = command.spawn().map_err(|e| match e.kind() {
+    ErrorKind::NotFound => Error::new(ErrorKind::NotFound, "ffmpeg was not found in PATH"),
+    _ => e,
+  })?;
+  if let (MediaInput::Stream(stream), Some(stdin)) = (input, child.stdin.take()) {
+    stream.pipe_to(stdin)?;
+  }
+  let stdout = child.stdout.take().expect("ffmpeg stdout is piped");
+  Ok((child, stdout))
+}
+
+impl FfmpegAudioSource {
+  fn stop(&mut self) {
+    let _ = self.child.kill();
+    let _ = self.child.wait();
+  }
+}
+
+impl AudioSource for FfmpegAudioSource {
+  fn open(input: &MediaInput, format: AudioFormat, start: Duration) -> std::io::Result<FfmpegAudioSource> {
+    let (child, stdout) = spawn(input, format, start, 1.0)?;
+    Ok(FfmpegAudioSource { input: input.clone(), format, child, stdout })
+  }
+
+  fn next_samples(&mut self) -> std::io::Result<Option<Vec<f32>>> {
+    let mut bytes = vec![0u8; CHUNK_FRAMES * self.format.channels as usize * 4];
+    let mut filled = 0;
+    while filled < bytes.len() {
+      match self.stdout.read(&mut bytes[filled..]) {
+        Ok(0) => break,
+        Ok(n) => filled += n,
+        Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+        Err(e) => return Err(e),
+      }
+    }
+
+    if filled == 0 {
+      return match self.child.wait()? {
+        status if status.success() => Ok(None),
+        status => Err(Error::other(format!("ffmpeg exited with {}", status))),
+      };
+    }
+
+    let samples = bytes[..filled - filled % 4]
+      .chunks_exact(4)
+      .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+      .collect();
+    Ok(Some(samples))
+  }
+
+  fn seek(&mut self, position: Duration, speed: f64) -> std::io::Result<()> {
+    self.stop();
+    let (child, stdout) = spawn(&self.input, self.format, position, speed)?;
+    self.child = child;
+    self.stdout = stdout;
+    Ok(())
+  }
+}
+
+impl Drop for FfmpegAudioSource {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::streaming::RemoteStream;
+
+  static FORMAT: AudioFormat = AudioFormat { sample_rate: 48000, channels: 2 };
+
+  fn arguments(input: &MediaInput, start: Duration, speed: f64) -> Vec<String> {
+    let command = command(input, FORMAT, start, speed).unwrap();
+    command.get_args().map(|argument| argument.to_string_lossy().to_string()).collect()
+  }
+
+  #[test]
+  fn chains_atempo_filters() {
+    assert_eq!(tempo_filter(1.5), "atempo=1.5");
+    assert_eq!(tempo_filter(4.0), "atempo=2.0,atempo=2");
+    assert_eq!(tempo_filter(0.25), "atempo=0.5,atempo=0.5");
+  }
+
+  #[test]
+  fn seeks_and_speeds_up_a_stream() {
+    let stream = RemoteStream::open("test".to_string(), "http://127.0.0.1:9/audio".to_string(), None, None, 0);
+    let input = MediaInput::Stream(stream);
+    assert!(arguments(&input, Duration::ZERO, 1.0).windows(2).any(|pair| pair == ["-i", "pipe:0"]));
+    let arguments = arguments(&input, Duration::from_secs(90), 1.5);
+    assert!(arguments.windows(4).any(|window| window == ["-ss", "90.000", "-i", "http://127.0.0.1:9/audio"]));
+    assert!(arguments.windows(2).any(|pair| pair == ["-af", "atempo=1.5"]));
+  }
+}
@@ -0,0 +1,185 @@
+pub mod command;
+#[cfg(feature = "cpal")]
+pub mod device;
+#[cfg(feature = "ffmpeg-cli")]
+pub mod ffmpeg;
+pub mod sink;
+
+use std::io::{Error, ErrorKind};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::get_logger;
+use crate::media::MediaInput;
+
+pub use sink::{AudioSink, SinkKind};
+
+// Layout of the interleaved f32 samples exchanged between the source and the sink
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioFormat {
+  pub sample_rate: u32,
+  pub channels: u16,
+}
+
+// A source of decoded audio samples
+pub trait AudioSource {
+  // Open `input` at `start`, samples are converted to `format`
+  fn open(input: &MediaInput, format: AudioFormat, start: Duration) -> std::io::Result<Self> where Self: Sized;
+  // The next interleaved samples, `None` once the track is over
+  fn next_samples(&mut self) -> std::io::Result<Option<Vec<f32>>>;
+  // Move to `position`, the next samples returned start there and are
+  // played `speed` times faster without changing the pitch
+  fn seek(&mut self, position: Duration, speed: f64) -> std::io::Result<()>;
+}
+
+enum Command {
+  Seek { position: Duration, speed: f64 },
+}
+
+// State shared with the decoding thread
+struct Shared {
+  // Duration of the samples handed to the sink since the last seek, in microseconds
+  written: AtomicU64,
+  // Set by the thread once there is nothing left to write
+  ended: AtomicBool,
+  // Media position of the first sample after the last seek, in microseconds
+  base: AtomicU64,
+  // Playback speed, as f64 bits
+  speed: AtomicU64,
+}
+
+// Feeds a sink from a decoding thread, the sink being the clock of the playback
+pub struct AudioPlayback {
+  sink: Arc<dyn AudioSink>,
+  shared: Arc<Shared>,
+  commands: Sender<Command>,
+}
+
+impl AudioFormat {
+  pub fn duration(&self, samples: u64) -> Duration {
+    Duration::from_secs_f64(samples as f64 / (self.sample_rate as f64 * self.channels as f64))
+  }
+}
+
+// Open the audio track of `input` at `start` with the first available backend
+pub fn open(input: &MediaInput, format: AudioFormat, start: Duration) -> std::io::Result<Box<dyn AudioSource>> {
+  #[cfg(feature = "ffmpeg-cli")]
+  return Ok(Box::new(ffmpeg::FfmpegAudioSource::open(input, format, start)?));
+
+  #[cfg(not(feature = "ffmpeg-cli"))]
+  {
+    let _ = (input, format, start);
+    Err(Error::new(ErrorKind::Unsupported, "Audio decoding needs the ffmpeg-cli feature"))
+  }
+}
+
+// Decode `input` into the sink until the commands sender is dropped
+fn feed(input: &MediaInput, sink: &Arc<dyn AudioSink>, shared: &Shared, commands: Receiver<Command>) -> std::io::Result<()> {
+  let format = sink.format();
+  let start = Duration::from_micros(shared.base.load(Ordering::Relaxed));
+  let mut source = open(input, format, start)?;
+  let mut samples_written = 0;
+
+  loop {
+    // Once the track is over there is nothing to do but wait for a seek
+    let command = if shared.ended.load(Ordering::Relaxed) {
+      match commands.recv() {
+        Ok(command) => Some(command),
+        Err(_) => return Ok(()),
+      }
+    } else {
+      match commands.try_recv() {
+        Ok(command) => Some(command),
+        Err(TryRecvError::Empty) => None,
+        Err(TryRecvError::Disconnected) => return Ok(()),
+      }
+    };
+
+    if let Some(Command::Seek { position, speed }) = command {
+      source.seek(position, speed)?;
+      // Samples of the old position may have been written meanwhile
+      sink.clear();
+      samples_written = 0;
+      shared.written.store(0, Ordering::Relaxed);
+      shared.ended.store(false, Ordering::Relaxed);
+      continue;
+    }
+
+    match source.next_samples()? {
+      Some(samples) => {
+        sink.write(&samples)?;
+        samples_written += samples.len() as u64;
+        shared.written.store(format.duration(samples_written).as_micros() as u64, Ordering::Relaxed);
+      },
+      None => shared.ended.store(true, Ordering::Relaxed),
+    }
+  }
+}
+
+impl AudioPlayback {
+  // Play `input` on `sink` from `start`
+  pub fn start(input: &MediaInput, sink: Arc<dyn AudioSink>, start: Duration) -> std::io::Result<AudioPlayback> {
+    // Stdin is already read by the video decoder
+    if *input == MediaInput::Stdin {
+      return Err(Error::new(ErrorKind::Unsupported, "Audio cannot be played from stdin"));
+    }
+
+    let shared = Arc::new(Shared {
+      written: AtomicU64::new(0),
+      ended: AtomicBool::new(false),
+      base: AtomicU64::new(start.as_micros() as u64),
+      speed: AtomicU64::new(1.0f64.to_bits()),
+    });
+    let (commands, receiver) = mpsc::channel();
+
+    let input = input.clone();
+    let thread_sink = sink.clone();
+    let thread_shared = shared.clone();
+    std::thread::spawn(move || {
+      if let Err(e) = feed(&input, &thread_sink, &thread_shared, receiver) {
+        get_logger().warn(format!("Audio playback stopped: {}", e));
+      }
+      thread_shared.ended.store(true, Ordering::Relaxed);
+    });
+
+    Ok(AudioPlayback { sink, shared, commands })
+  }
+
+  // Media position of the audio that was heard, `None` once the whole track was played
+  pub fn position(&self) -> Option<Duration> {
+    let played = self.sink.played();
+    let written = Duration::from_micros(self.shared.written.load(Ordering::Relaxed));
+    if self.shared.ended.load(Ordering::Relaxed) && played >= written {
+      return None;
+    }
+    let base = Duration::from_micros(self.shared.base.load(Ordering::Relaxed));
+    let speed = f64::from_bits(self.shared.speed.load(Ordering::Relaxed));
+    Some(base + played.mul_f64(speed))
+  }
+
+  // Restart the audio at `position`, played `speed` times faster
+  pub fn seek(&self, position: Duration, speed: f64) {
+    self.shared.base.store(position.as_micros() as u64, Ordering::Relaxed);
+    self.shared.speed.store(speed.to_bits(), Ordering::Relaxed);
+    self.sink.clear();
+    let _ = self.commands.send(Command::Seek { position, speed });
+  }
+
+  pub fn set_paused(&self, paused: bool) {
+    self.sink.set_paused(paused);
+  }
+
+  pub fn set_volume(&self, volume: f32) {
+    self.sink.set_volume(volume);
+  }
+}
+
+impl Drop for AudioPlayback {
+  fn drop(&mut self) {
+    // Unblock the thread if it waits for space in the sink, it stops once
+    // it sees the commands sender is gone
+    self.sink.clear();
+  }
+}
@@ -0,0 +1,157 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+
+use crate::audio::AudioFormat;
+use crate::get_logger;
+
+// How much audio a sink buffers ahead of what is heard
+pub static SINK_BUFFER: Duration = Duration::from_millis(250);
+
+// Where the decoded audio goes
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum SinkKind {
+  // The default sound card, through cpal (feature `cpal`) or else an `aplay` or `ffplay` process
+  Device,
+  // Discard the samples at the playback rate, for headless runs
+  Null,
+}
+
+pub trait AudioSink: Send + Sync {
+  // The samples layout `write` expects
+  fn format(&self) -> AudioFormat;
+  // Queue interleaved samples, blocking while the sink buffer is full
+  fn write(&self, samples: &[f32]) -> std::io::Result<()>;
+  // How much of the written audio was played
+  fn played(&self) -> Duration;
+  // Drop the queued samples and restart the played count
+  fn clear(&self);
+  // Stop consuming the samples while paused
+  fn set_paused(&self, paused: bool);
+  // Gain applied to the samples, from 0.0 to 1.0
+  fn set_volume(&self, volume: f32);
+}
+
+// A sink playing nothing, it only consumes the samples in real time
+pub struct NullSink {
+  format: AudioFormat,
+  state: Mutex<NullState>,
+}
+
+struct NullState {
+  // Played duration before the last resume
+  played: Duration,
+  // When the samples started being consumed again, `None` while stopped
+  resumed: Option<Instant>,
+  // Number of samples written since the last clear
+  written: u64,
+  paused: bool,
+}
+
+impl NullState {
+  fn played(&self, format: AudioFormat) -> Duration {
+    let running = self.resumed.map(|resumed| resumed.elapsed()).unwrap_or_default();
+    (self.played + running).min(format.duration(self.written))
+  }
+}
+
+impl NullSink {
+  pub fn new(format: AudioFormat) -> NullSink {
+    NullSink {
+      format,
+      state: Mutex::new(NullState { played: Duration::ZERO, resumed: None, written: 0, paused: false }),
+    }
+  }
+
+  fn state(&self) -> std::sync::MutexGuard<'_, NullState> {
+    self.state.lock().expect("Failed to lock NullSink")
+  }
+}
+
+impl AudioSink for NullSink {
+  fn format(&self) -> AudioFormat {
+    self.format
+  }
+
+  fn write(&self, samples: &[f32]) -> std::io::Result<()> {
+    let mut ahead = {
+      let mut state = self.state();
+      if state.resumed.is_none() && !state.paused {
+        state.resumed = Some(Instant::now());
+      }
+      // Ran out of samples, the time spent waiting for these ones was not played
+      let written = self.format.duration(state.written);
+      if state.resumed.is_some() && state.played(self.format) >= written {
+        state.played = written;
+        state.resumed = Some(Instant::now());
+      }
+      state.written += samples.len() as u64;
+      self.format.duration(state.written).saturating_sub(state.played(self.format))
+    };
+    while ahead > SINK_BUFFER {
+      std::thread::sleep((ahead - SINK_BUFFER).min(Duration::from_millis(50)));
+      let state = self.state();
+      // Cleared meanwhile, what was written is not wanted anymore
+      if state.written == 0 {
+        break;
+      }
+      ahead = self.format.duration(state.written).saturating_sub(state.played(self.format));
+    }
+    Ok(())
+  }
+
+  fn played(&self) -> Duration {
+    self.state().played(self.format)
+  }
+
+  fn clear(&self) {
+    let mut state = self.state();
+    state.played = Duration::ZERO;
+    state.resumed = None;
+    state.written = 0;
+  }
+
+  fn set_paused(&self, paused: bool) {
+    let mut state = self.state();
+    if paused && state.resumed.is_some() {
+      state.played = state.played(self.format);
+      state.resumed = None;
+    }
+    if !paused && state.paused && state.written > 0 {
+      state.resumed = Some(Instant::now());
+    }
+    state.paused = paused;
+  }
+
+  fn set_volume(&self, _volume: f32) {}
+}
+
+// The sound card through a player process, or the null sink when none is installed
+fn open_command(format: AudioFormat) -> Arc<dyn AudioSink> {
+  match crate::audio::command::CommandSink::new(format) {
+    Ok(sink) => Arc::new(sink),
+    Err(e) => {
+      get_logger().warn(format!("No audio player, audio is muted: {}", e));
+      Arc::new(NullSink::new(format))
+    },
+  }
+}
+
+// Open the sink, falling back to a player process, then to the null sink, when the device is not usable
+pub fn open(kind: SinkKind) -> Arc<dyn AudioSink> {
+  let format = AudioFormat { sample_rate: 48000, channels: 2 };
+  match kind {
+    #[cfg(feature = "cpal")]
+    SinkKind::Device => match crate::audio::device::CpalSink::new() {
+      Ok(sink) => Arc::new(sink),
+      Err(e) => {
+        get_logger().warn(format!("No audio device, trying a player process: {}", e));
+        open_command(format)
+      },
+    },
+    #[cfg(not(feature = "cpal"))]
+    SinkKind::Device => open_command(format),
+    SinkKind::Null => Arc::new(NullSink::new(format)),
+  }
+}
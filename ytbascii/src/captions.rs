@@ -0,0 +1,254 @@
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+// Where the active cue is drawn
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum CaptionPlacement {
+  // Rows kept under the video, the frame is made smaller
+  Band,
+  // Over the bottom of the video
+  Overlay,
+}
+
+// A run of text sharing the same style
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Span {
+  pub text: String,
+  pub bold: bool,
+  pub italic: bool,
+  pub underline: bool,
+}
+
+// A caption shown from `start` to `end`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+  pub start: Duration,
+  pub end: Duration,
+  // The lines of the caption, each made of styled spans
+  pub lines: Vec<Vec<Span>>,
+}
+
+// A caption track, in one language
+#[derive(Debug, Clone)]
+pub struct Captions {
+  // The name shown when switching tracks, e.g. "English (auto-generated)"
+  pub label: String,
+  // The cues, by start time
+  pub cues: Vec<Cue>,
+}
+
+// Parse `hh:mm:ss.mmm`, `mm:ss.mmm` or the SRT `hh:mm:ss,mmm`
+fn parse_timestamp(value: &str) -> Option<Duration> {
+  let (clock, millis) = value.trim().rsplit_once(['.', ','])?;
+  let parts: Vec<u64> = clock.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+  let seconds = match parts.as_slice() {
+    [hours, minutes, seconds] => hours * 3600 + minutes * 60 + seconds,
+    [minutes, seconds] => minutes * 60 + seconds,
+    _ => return None,
+  };
+  let millis: u64 = millis.get(..3.min(millis.len()))?.parse().ok()?;
+  Some(Duration::from_secs(seconds) + Duration::from_millis(millis))
+}
+
+// Parse a `start --> end [settings]` line
+fn parse_timing(line: &str) -> Option<(Duration, Duration)> {
+  let (start, rest) = line.split_once("-->")?;
+  let end = rest.split_whitespace().next()?;
+  Some((parse_timestamp(start)?, parse_timestamp(end)?))
+}
+
+fn decode_entities(text: &str) -> String {
+  text.replace("&lt;", "<")
+    .replace("&gt;", ">")
+    .replace("&nbsp;", " ")
+    .replace("&quot;", "\"")
+    .replace("&#39;", "'")
+    .replace("&amp;", "&")
+}
+
+// Split a caption line on its `<b>`, `<i>` and `<u>` tags. The other tags, like the
+// voices, classes, karaoke timestamps or SRT fonts, are dropped with their styling.
+fn parse_line(line: &str) -> Vec<Span> {
+  let mut spans: Vec<Span> = vec![];
+  let mut style = Span::default();
+  let mut rest = line;
+
+  let mut push = |text: &str, style: &Span| {
+    if text.is_empty() {
+      return;
+    }
+    let text = decode_entities(text);
+    match spans.last_mut() {
+      Some(last) if (last.bold, last.italic, last.underline) == (style.bold, style.italic, style.underline) => last.text.push_str(&text),
+      _ => spans.push(Span { text, ..style.clone() }),
+    }
+  };
+
+  while let Some(open) = rest.find('<') {
+    push(&rest[..open], &style);
+    let Some(close) = rest[open..].find('>') else {
+      break;
+    };
+    let tag = rest[open + 1..open + close].trim().to_ascii_lowercase();
+    let (closing, name) = match tag.strip_prefix('/') {
+      Some(name) => (true, name),
+      None => (false, tag.as_str()),
+    };
+    match name.split(['.', ' ']).next().unwrap_or("") {
+      "b" => style.bold = !closing,
+      "i" => style.italic = !closing,
+      "u" => style.underline = !closing,
+      _ => {},
+    }
+    rest = &rest[open + close + 1..];
+  }
+  push(rest, &style);
+  spans
+}
+
+impl Captions {
+  // Parse a WebVTT or SRT track, told apart by the `WEBVTT` header
+  pub fn parse(label: String, text: &str) -> std::io::Result<Captions> {
+    let text = text.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let is_vtt = text.starts_with("WEBVTT");
+
+    let mut cues = vec![];
+    for block in text.split("\n\n") {
+      let mut lines = block.lines().skip_while(|line| line.trim().is_empty());
+      // The block may start with a cue identifier, or the SRT counter
+      let timing = match lines.next() {
+        Some(line) if line.contains("-->") => line,
+        Some(_) => match lines.next() {
+          Some(line) if line.contains("-->") => line,
+          // The header, a NOTE, a STYLE or a REGION block
+          _ => continue,
+        },
+        None => continue,
+      };
+      let (start, end) = match parse_timing(timing) {
+        Some(timing) => timing,
+        None => continue,
+      };
+      let lines: Vec<Vec<Span>> = lines.filter(|line| !line.trim().is_empty()).map(parse_line).collect();
+      if !lines.is_empty() {
+        cues.push(Cue { start, end, lines });
+      }
+    }
+
+    if cues.is_empty() && !is_vtt {
+      return Err(Error::new(ErrorKind::InvalidData, "No WebVTT or SRT cue found"));
+    }
+    cues.sort_by_key(|cue| cue.start);
+    Ok(Captions { label, cues })
+  }
+
+  // Read a `.vtt` or `.srt` file
+  pub fn load(path: &std::path::Path) -> std::io::Result<Captions> {
+    let label = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    Captions::parse(label, &std::fs::read_to_string(path)?)
+  }
+
+  // The cues shown at `position`, the latest last
+  pub fn active(&self, position: Duration) -> impl Iterator<Item = &Cue> {
+    let started = self.cues.partition_point(|cue| cue.start <= position);
+    self.cues[..started].iter().filter(move |cue| cue.end > position)
+  }
+}
+
+impl Span {
+  // The text with the escape codes of its style
+  pub fn to_ansi(&self) -> String {
+    let mut codes = vec![];
+    if self.bold {
+      codes.push("1");
+    }
+    if self.italic {
+      codes.push("3");
+    }
+    if self.underline {
+      codes.push("4");
+    }
+    match codes.is_empty() {
+      true => self.text.clone(),
+      false => format!("\x1b[{}m{}\x1b[22;23;24m", codes.join(";"), self.text),
+    }
+  }
+}
+
+// The last `rows` lines of the active cues drawn light on dark, with the column
+// centering them in `width` columns
+pub fn render_lines(captions: &Captions, position: Duration, width: u16, rows: usize) -> Vec<(u16, String)> {
+  let lines: Vec<&Vec<Span>> = captions.active(position).flat_map(|cue| cue.lines.iter()).collect();
+  lines[lines.len().saturating_sub(rows)..].iter().map(|spans| {
+    let mut length = 0;
+    let mut text = String::new();
+    // Cut what does not fit rather than wrapping over the video
+    for span in spans.iter() {
+      let room = (width as usize).saturating_sub(length + 2);
+      let visible: String = span.text.chars().take(room).collect();
+      length += visible.chars().count();
+      text.push_str(&Span { text: visible, ..(*span).clone() }.to_ansi());
+    }
+    let column = (width as usize).saturating_sub(length + 2) / 2;
+    (column as u16 + 1, format!("\x1b[97;48;2;0;0;0m {} \x1b[0m", text))
+  }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn text(cue: &Cue) -> Vec<String> {
+    cue.lines.iter().map(|spans| spans.iter().map(|span| span.text.as_str()).collect()).collect()
+  }
+
+  #[test]
+  fn parses_timestamps() {
+    assert_eq!(parse_timestamp("00:01:02.345"), Some(Duration::from_millis(62_345)));
+    assert_eq!(parse_timestamp("01:02.5"), Some(Duration::from_millis(62_005)));
+    assert_eq!(parse_timestamp("1:00:00,000"), Some(Duration::from_secs(3600)));
+    assert_eq!(parse_timestamp("12"), None);
+    assert_eq!(parse_timestamp("a:b.c"), None);
+  }
+
+  #[test]
+  fn parses_webvtt() {
+    let vtt = "\u{feff}WEBVTT Kind: captions\r\n\r\nNOTE made by hand\r\n\r\nintro\r\n00:00:01.000 --> 00:00:03.500 align:start position:0%\r\nHello <b>bold</b>\r\n<v Roger>and &amp; you\r\n\r\n00:04.000 --> 00:05.000\r\nBye\r\n";
+    let captions = Captions::parse("English".to_string(), vtt).unwrap();
+    assert_eq!(captions.cues.len(), 2);
+    let cue = &captions.cues[0];
+    assert_eq!((cue.start, cue.end), (Duration::from_secs(1), Duration::from_millis(3500)));
+    assert_eq!(text(cue), vec!["Hello bold", "and & you"]);
+    assert_eq!(cue.lines[0][1], Span { text: "bold".to_string(), bold: true, ..Span::default() });
+    assert_eq!(text(&captions.cues[1]), vec!["Bye"]);
+  }
+
+  #[test]
+  fn parses_srt() {
+    let srt = "2\n00:00:05,000 --> 00:00:06,000\n<i>Later</i>\n\n1\n00:00:01,000 --> 00:00:02,000\n<font color=\"red\">First</font>\nline two\n";
+    let captions = Captions::parse("srt".to_string(), srt).unwrap();
+    // Sorted by start time
+    assert_eq!(text(&captions.cues[0]), vec!["First", "line two"]);
+    assert!(captions.cues[1].lines[0][0].italic);
+  }
+
+  #[test]
+  fn rejects_what_is_not_captions() {
+    assert!(Captions::parse("none".to_string(), "just some text").is_err());
+    // An empty WebVTT track is still a track
+    assert!(Captions::parse("empty".to_string(), "WEBVTT\n").unwrap().cues.is_empty());
+  }
+
+  #[test]
+  fn finds_the_active_cues() {
+    let srt = "1\n00:00:01,000 --> 00:00:04,000\nA\n\n2\n00:00:02,000 --> 00:00:03,000\nB\n";
+    let captions = Captions::parse("srt".to_string(), srt).unwrap();
+    let active = |ms| captions.active(Duration::from_millis(ms)).flat_map(text).collect::<Vec<_>>();
+    assert!(active(500).is_empty());
+    assert_eq!(active(2500), vec!["A", "B"]);
+    assert_eq!(active(3000), vec!["A"]);
+    assert!(active(4000).is_empty());
+  }
+}
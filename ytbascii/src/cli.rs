@@ -0,0 +1,140 @@
+use clap::{Parser, Subcommand};
+
+use crate::audio::SinkKind;
+use crate::captions::CaptionPlacement;
+use crate::decoder::Backend;
+use crate::export::raster::Palette;
+use crate::export::ExportFormat;
+use crate::renderer::RenderMode;
+
+#[derive(Parser, Debug)]
+#[command(name = "ytbascii", version, about = "A Youtube client in your terminal")]
+pub struct Cli {
+  #[command(subcommand)]
+  pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+  /// Play a local video file, `-` to read the video from stdin, or stream a YouTube video
+  Play {
+    /// Path of the video file, `-` for stdin, or a YouTube video id or url
+    input: String,
+    /// Frame rate used for the playback
+    #[arg(long, default_value_t = crate::defaults::DEFAULT_FPS, value_parser = clap::value_parser!(u32).range(1..))]
+    fps: u32,
+    /// How the frames are drawn in the terminal
+    #[arg(long, value_enum, default_value_t = RenderMode::Color)]
+    mode: RenderMode,
+    /// Decoder used to read the video, picked from the input when not set
+    #[arg(long, value_enum)]
+    decoder: Option<Backend>,
+    /// Play the video without its sound
+    #[arg(long)]
+    no_audio: bool,
+    /// Where the sound is played
+    #[arg(long, value_enum, default_value_t = SinkKind::Device)]
+    audio_sink: SinkKind,
+    /// Show captions: a `.vtt` or `.srt` file, or a language code like `en` for a YouTube video
+    #[arg(long)]
+    captions: Option<String>,
+    /// Where the captions are drawn
+    #[arg(long, value_enum, default_value_t = CaptionPlacement::Band)]
+    captions_placement: CaptionPlacement,
+  },
+  /// Render a local video file or a YouTube video to a file played without ytbascii
+  Export {
+    /// Path of the video file, `-` for stdin, or a YouTube video id or url
+    input: String,
+    /// Kind of file written
+    #[arg(long, value_enum, default_value_t = ExportFormat::Asciicast)]
+    format: ExportFormat,
+    /// Where the file is written, `<input name>.<format extension>` when not set
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Width of the frames in cells, the terminal width when not set
+    #[arg(long)]
+    cols: Option<u16>,
+    /// Height of the frames in cells, the terminal height when not set
+    #[arg(long)]
+    rows: Option<u16>,
+    /// Frame rate of the export
+    #[arg(long, default_value_t = crate::defaults::DEFAULT_FPS, value_parser = clap::value_parser!(u32).range(1..))]
+    fps: u32,
+    /// How the frames are drawn
+    #[arg(long, value_enum, default_value_t = RenderMode::Color)]
+    mode: RenderMode,
+    /// Decoder used to read the video, picked from the input when not set
+    #[arg(long, value_enum)]
+    decoder: Option<Backend>,
+    /// Colors of the `gif` and `apng` images
+    #[arg(long, value_enum, default_value_t = Palette::Adaptive)]
+    palette: Palette,
+    /// Where the export starts in the video, in seconds
+    #[arg(long, default_value_t = 0.0)]
+    start: f64,
+    /// How long the export lasts, in seconds, until the end of the video when not set
+    #[arg(long)]
+    duration: Option<f64>,
+  },
+  /// Search YouTube videos, channels and playlists
+  Search {
+    /// Words to search for
+    #[arg(required = true)]
+    query: Vec<String>,
+    /// Page of the results, from 1
+    #[arg(long, default_value_t = 1)]
+    page: u32,
+    /// How the thumbnails are drawn
+    #[arg(long, value_enum, default_value_t = RenderMode::Color)]
+    mode: RenderMode,
+    /// List the results without their thumbnails
+    #[arg(long)]
+    no_thumbnails: bool,
+  },
+  /// Manage the download queue, lists it when no action is given
+  Downloads {
+    #[command(subcommand)]
+    action: Option<DownloadsAction>,
+  },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DownloadsAction {
+  /// Queue videos or playlists, by id or url
+  Add {
+    #[arg(required = true)]
+    ids: Vec<String>,
+  },
+  /// Download the queued videos until the queue is empty
+  Run {
+    /// Number of videos downloaded at the same time
+    #[arg(long, default_value_t = crate::defaults::DEFAULT_DOWNLOAD_JOBS)]
+    jobs: usize,
+    /// Fetch the audio too and mux both streams into one `mkv` or `mp4` file, needs `ffmpeg` in PATH
+    #[arg(long)]
+    mux: Option<youtube::mux::Container>,
+  },
+  /// Show the queue
+  List,
+  /// Stop downloads, keeping what was fetched
+  Pause(JobTarget),
+  /// Queue paused downloads again
+  Resume(JobTarget),
+  /// Stop downloads and delete their files
+  Cancel(JobTarget),
+  /// Queue failed and cancelled downloads again
+  Retry(JobTarget),
+  /// Forget the finished and cancelled downloads
+  Clear,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct JobTarget {
+  /// Video or playlist id
+  #[arg(required_unless_present = "all", conflicts_with = "all")]
+  pub id: Option<String>,
+  /// Apply to every download
+  #[arg(long)]
+  pub all: bool,
+}
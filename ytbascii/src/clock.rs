@@ -0,0 +1,99 @@
+use std::time::{Duration, Instant};
+
+use crate::audio::AudioPlayback;
+
+// The time the video frames are presented against. It follows the audio that was
+// heard while there is some, and the wall clock otherwise.
+pub struct MediaClock {
+  audio: Option<AudioPlayback>,
+  paused: bool,
+  // Frozen while the media waits for the network, without being paused
+  stalled: bool,
+  speed: f64,
+  // Media position at `started`, the wall clock counts from there
+  started: Instant,
+  offset: Duration,
+}
+
+impl MediaClock {
+  // A clock starting at `start`, where the audio was opened
+  pub fn new(audio: Option<AudioPlayback>, start: Duration) -> MediaClock {
+    MediaClock {
+      audio,
+      paused: false,
+      stalled: false,
+      speed: 1.0,
+      started: Instant::now(),
+      offset: start,
+    }
+  }
+
+  pub fn has_audio(&self) -> bool {
+    self.audio.is_some()
+  }
+
+  pub fn is_paused(&self) -> bool {
+    self.paused
+  }
+
+  pub fn speed(&self) -> f64 {
+    self.speed
+  }
+
+  pub fn now(&mut self) -> Duration {
+    if self.paused || self.stalled {
+      return self.offset;
+    }
+    // Once the audio track is over the wall clock goes on from where it stopped
+    if let Some(position) = self.audio.as_ref().and_then(|audio| audio.position()) {
+      self.offset = position;
+      self.started = Instant::now();
+      return position;
+    }
+    self.offset + self.started.elapsed().mul_f64(self.speed)
+  }
+
+  pub fn set_paused(&mut self, paused: bool) {
+    self.freeze(paused, self.stalled);
+  }
+
+  pub fn set_stalled(&mut self, stalled: bool) {
+    self.freeze(self.paused, stalled);
+  }
+
+  fn freeze(&mut self, paused: bool, stalled: bool) {
+    let frozen = paused || stalled;
+    if frozen != (self.paused || self.stalled) {
+      if frozen {
+        self.offset = self.now();
+      } else {
+        self.started = Instant::now();
+      }
+      if let Some(audio) = &self.audio {
+        audio.set_paused(frozen);
+      }
+    }
+    self.paused = paused;
+    self.stalled = stalled;
+  }
+
+  pub fn seek(&mut self, position: Duration) {
+    self.offset = position;
+    self.started = Instant::now();
+    if let Some(audio) = &self.audio {
+      audio.seek(position, self.speed);
+    }
+  }
+
+  pub fn set_speed(&mut self, speed: f64) {
+    let position = self.now();
+    self.speed = speed;
+    self.seek(position);
+  }
+
+  pub fn set_volume(&self, volume: f32) {
+    if let Some(audio) = &self.audio {
+      audio.set_volume(volume);
+    }
+  }
+}
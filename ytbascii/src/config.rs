@@ -43,9 +43,9 @@ pub enum Configs {
 impl Server {
   fn new(url: String, last_checked: String, status: String) -> Server {
     Server {
-      url: url,
-      last_checked: last_checked,
-      status: status,
+      url,
+      last_checked,
+      status,
     }
   }
   async fn check_status(&mut self) {
@@ -129,7 +129,7 @@ pub trait Config: Send + Sync {
 impl InvidiousConfig {
   pub fn new(path: String) -> InvidiousConfig {
     let mut config = InvidiousConfig {
-      config_path: String::from(path.clone()),
+      config_path: path.clone(),
       servers: ServerList::new(),
     };
 
@@ -169,8 +169,9 @@ impl Config for InvidiousConfig {
     let servers = config["servers"].as_array().unwrap();
     for server in servers {
       let url = server["url"].as_str().unwrap();
-      let last_checked = server["last_checked"].as_str().unwrap();
-      let status = server["status"].as_str().unwrap();
+      // Servers that were never checked are checked on the next start
+      let last_checked = server["last_checked"].as_str().unwrap_or("1970-01-01 00:00:00");
+      let status = server["status"].as_str().unwrap_or("unknown");
       self.servers.add_server(Server::new(String::from(url), String::from(last_checked), String::from(status)));
     }
   }
@@ -191,6 +192,6 @@ impl Config for InvidiousConfig {
     let config = json!({
       "servers": servers,
     });
-    write!(config_file, "{}", config.to_string()).unwrap();
+    write!(config_file, "{}", config).unwrap();
   }
 }
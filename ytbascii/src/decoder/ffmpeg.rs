@@ -0,0 +1,298 @@
+use std::io::{Error, ErrorKind, Read};
+use std::path::Path;
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::time::Duration;
+
+use api::chapters::Chapter;
+
+use crate::decoder::{DecoderOptions, FrameSource, StreamInfo, VideoFrame};
+use crate::get_logger;
+use crate::media::MediaInput;
+use crate::renderer::Frame;
+
+// Decode a media with an `ffmpeg` child process, reading raw RGB24 frames on its stdout
+pub struct FfmpegSource {
+  input: MediaInput,
+  options: DecoderOptions,
+  info: StreamInfo,
+  child: Child,
+  stdout: ChildStdout,
+  // Position the running child started at
+  start: Duration,
+  // Frames read since the child started
+  index: u32,
+}
+
+fn spawn_error(e: Error, program: &str) -> Error {
+  match e.kind() {
+    ErrorKind::NotFound => Error::new(ErrorKind::NotFound, format!("{} was not found in PATH", program)),
+    _ => e,
+  }
+}
+
+// Ask `ffprobe` for the size and duration of the first video stream
+fn probe(input: &MediaInput, options: &DecoderOptions) -> std::io::Result<Option<StreamInfo>> {
+  let path = match input {
+    MediaInput::File(path) => path,
+    // Stdin can only be read once, it is kept for the decoder
+    MediaInput::Stdin => return Ok(None),
+    // The video metadata already tells the duration, and a seek opens the url again
+    MediaInput::Stream(stream) => return Ok(Some(StreamInfo {
+      width: options.max_width,
+      height: options.max_height,
+      fps: options.fps as f64,
+      duration: stream.duration(),
+      seekable: true,
+    })),
+  };
+
+  let output = Command::new("ffprobe")
+    .args(["-v", "error", "-select_streams", "v:0", "-show_entries"])
+    .args(["stream=width,height:format=duration", "-of", "json"])
+    .arg(path)
+    .stdin(Stdio::null())
+    .output()
+    .map_err(|e| spawn_error(e, "ffprobe"))?;
+  if !output.status.success() {
+    return Err(Error::new(ErrorKind::InvalidData, String::from_utf8_lossy(&output.stderr).trim().to_string()));
+  }
+
+  let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+  let stream = &json["streams"][0];
+  if stream.is_null() {
+    return Err(Error::new(ErrorKind::InvalidData, format!("{} has no video stream", input)));
+  }
+
+  let duration = json["format"]["duration"].as_str()
+    .and_then(|duration| duration.parse::<f64>().ok())
+    .map(Duration::from_secs_f64);
+
+  Ok(Some(StreamInfo {
+    width: stream["width"].as_u64().unwrap_or(0) as u32,
+    height: stream["height"].as_u64().unwrap_or(0) as u32,
+    // Frames are resampled to a constant rate by the `fps` filter
+    fps: options.fps as f64,
+    duration,
+    seekable: true,
+  }))
+}
+
+// The ffmpeg command decoding `input` from `start`
+fn command(input: &MediaInput, options: &DecoderOptions, start: Duration) -> Command {
+  // The output size has to be known to split the raw stream in frames, so the
+  // frame is padded to the box once scaled
+  let filters = format!(
+    "fps={fps},scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2",
+    fps = options.fps,
+    w = options.max_width,
+    h = options.max_height,
+  );
+
+  let mut command = Command::new("ffmpeg");
+  command.args(["-hide_banner", "-loglevel", "error", "-nostdin"]);
+  if !start.is_zero() {
+    command.arg("-ss").arg(format!("{:.3}", start.as_secs_f64()));
+  }
+  match input {
+    MediaInput::File(path) => {
+      command.arg("-i").arg(path);
+      command.stdin(Stdio::null());
+    },
+    MediaInput::Stdin => {
+      command.args(["-i", "pipe:0"]);
+      command.stdin(Stdio::inherit());
+    },
+    // The fetched bytes are piped while the stream plays from its start, ffmpeg reads
+    // the url itself from anywhere else
+    MediaInput::Stream(stream) if start.is_zero() && !stream.is_read() => {
+      command.args(["-i", "pipe:0"]);
+      command.stdin(Stdio::piped());
+    },
+    MediaInput::Stream(stream) => {
+      command.arg("-i").arg(stream.url());
+      command.stdin(Stdio::null());
+    },
+  }
+  command.args(["-an", "-vf", filters.as_str(), "-pix_fmt", "rgb24", "-f", "rawvideo", "pipe:1"]);
+  command.stdout(Stdio::piped());
+  command.stderr(Stdio::null());
+  command
+}
+
+// The chapters of an `ffprobe -show_chapters` json output, the untitled ones being numbered
+fn parse_chapters(json: &serde_json::Value) -> Vec<Chapter> {
+  let Some(chapters) = json["chapters"].as_array() else {
+    return vec![];
+  };
+  let mut chapters: Vec<Chapter> = chapters.iter().enumerate().filter_map(|(index, chapter)| {
+    let start = chapter["start_time"].as_str()?.parse::<f64>().ok().filter(|start| *start >= 0.0)?;
+    let title = match chapter["tags"]["title"].as_str() {
+      Some(title) if !title.trim().is_empty() => title.trim().to_string(),
+      _ => format!("Chapter {}", index + 1),
+    };
+    Some(Chapter { title, start: Duration::from_secs_f64(start) })
+  }).collect();
+  chapters.sort_by_key(|chapter| chapter.start);
+  chapters
+}
+
+// Ask `ffprobe` for the chapters written in a local file, e.g. an exported video
+pub fn chapters(path: &Path) -> std::io::Result<Vec<Chapter>> {
+  let output = Command::new("ffprobe")
+    .args(["-v", "error", "-show_chapters", "-of", "json"])
+    .arg(path)
+    .stdin(Stdio::null())
+    .output()
+    .map_err(|e| spawn_error(e, "ffprobe"))?;
+  if !output.status.success() {
+    return Err(Error::new(ErrorKind::InvalidData, String::from_utf8_lossy(&output.stderr).trim().to_string()));
+  }
+  Ok(parse_chapters(&serde_json::from_slice(&output.stdout)?))
+}
+
+fn spawn(input: &MediaInput, options: &DecoderOptions, start: Duration) -> std::io::Result<(Child, ChildStdout)> {
+  let mut command = command(input, options, start);
+  get_logger().debug(format!("Spawning {:?}", command));
+  let mut child = command.spawn().map_err(|e| spawn_error(e, "ffmpeg"))?;
+  if let (MediaInput::Stream(stream), Some(stdin)) = (input, child.stdin.take()) {
+    stream.pipe_to(stdin)?;
+  }
+  let stdout = child.stdout.take().expect("ffmpeg stdout is piped");
+  Ok((child, stdout))
+}
+
+impl FfmpegSource {
+  fn stop(&mut self) {
+    let _ = self.child.kill();
+    let _ = self.child.wait();
+  }
+}
+
+impl FrameSource for FfmpegSource {
+  fn open(input: &MediaInput, options: &DecoderOptions) -> std::io::Result<FfmpegSource> {
+    let info = probe(input, options)?.unwrap_or(StreamInfo {
+      width: options.max_width,
+      height: options.max_height,
+      fps: options.fps as f64,
+      duration: None,
+      seekable: false,
+    });
+    // ffmpeg seeks on its own, or decodes and drops what comes before on a pipe
+    let (child, stdout) = spawn(input, options, options.start)?;
+
+    Ok(FfmpegSource {
+      input: input.clone(),
+      options: *options,
+      info,
+      child,
+      stdout,
+      start: options.start,
+      index: 0,
+    })
+  }
+
+  fn info(&self) -> &StreamInfo {
+    &self.info
+  }
+
+  fn next_frame(&mut self) -> std::io::Result<Option<VideoFrame>> {
+    let (width, height) = (self.options.max_width, self.options.max_height);
+    let mut data = vec![0u8; (width * height * 3) as usize];
+    let mut filled = 0;
+    while filled < data.len() {
+      match self.stdout.read(&mut data[filled..]) {
+        Ok(0) => break,
+        Ok(n) => filled += n,
+        Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+        Err(e) => return Err(e),
+      }
+    }
+
+    if filled < data.len() {
+      return match self.child.wait()? {
+        status if status.success() => Ok(None),
+        status => Err(Error::other(format!("ffmpeg exited with {}", status))),
+      };
+    }
+
+    let pts = self.start + Duration::from_secs_f64(self.index as f64 / self.options.fps as f64);
+    self.index += 1;
+    Ok(Some(VideoFrame { pts, frame: Frame::new(width, height, data) }))
+  }
+
+  fn seek(&mut self, position: Duration) -> std::io::Result<()> {
+    if !self.info.seekable {
+      return Err(Error::new(ErrorKind::Unsupported, format!("Cannot seek in {}", self.input)));
+    }
+
+    // ffmpeg seeks on its own when started with `-ss`, so the child is simply restarted
+    self.stop();
+    let (child, stdout) = spawn(&self.input, &self.options, position)?;
+    self.child = child;
+    self.stdout = stdout;
+    self.start = position;
+    self.index = 0;
+    Ok(())
+  }
+}
+
+impl Drop for FfmpegSource {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::streaming::RemoteStream;
+
+  fn stream() -> MediaInput {
+    let duration = Some(Duration::from_secs(212));
+    MediaInput::Stream(RemoteStream::open("test".to_string(), "http://127.0.0.1:9/video".to_string(), None, duration, 0))
+  }
+
+  fn arguments(command: &Command) -> Vec<String> {
+    command.get_args().map(|argument| argument.to_string_lossy().to_string()).collect()
+  }
+
+  static OPTIONS: DecoderOptions = DecoderOptions { max_width: 80, max_height: 48, fps: 25, start: Duration::ZERO };
+
+  #[test]
+  fn parses_the_chapters_of_a_file() {
+    let json = serde_json::json!({"chapters": [
+      {"start_time": "90.500000", "tags": {"title": "Middle"}},
+      {"start_time": "0.000000", "tags": {"title": "Intro"}},
+      {"start_time": "200.000000"},
+      {"start_time": "N/A", "tags": {"title": "Broken"}},
+    ]});
+    let chapters = parse_chapters(&json);
+    let titles: Vec<&str> = chapters.iter().map(|chapter| chapter.title.as_str()).collect();
+    assert_eq!(titles, ["Intro", "Middle", "Chapter 3"]);
+    assert_eq!(chapters[1].start, Duration::from_millis(90_500));
+    assert!(parse_chapters(&serde_json::json!({})).is_empty());
+  }
+
+  #[test]
+  fn streams_are_seekable() {
+    let info = probe(&stream(), &OPTIONS).unwrap().unwrap();
+    assert!(info.seekable);
+    assert_eq!(info.duration, Some(Duration::from_secs(212)));
+  }
+
+  #[test]
+  fn pipes_a_stream_played_from_its_start() {
+    let arguments = arguments(&command(&stream(), &OPTIONS, Duration::ZERO));
+    assert!(!arguments.contains(&"-ss".to_string()));
+    assert!(arguments.windows(2).any(|pair| pair == ["-i", "pipe:0"]));
+  }
+
+  #[test]
+  fn opens_the_url_of_a_stream_to_seek() {
+    let arguments = arguments(&command(&stream(), &OPTIONS, Duration::from_millis(42_500)));
+    let ss = arguments.iter().position(|argument| argument == "-ss").unwrap();
+    assert_eq!(arguments[ss + 1], "42.500");
+    // Before the input, so ffmpeg seeks in the container rather than decoding up to there
+    assert_eq!(arguments[ss + 2..ss + 4], ["-i", "http://127.0.0.1:9/video"]);
+  }
+}
@@ -0,0 +1,186 @@
+extern crate ffmpeg_next as ffmpeg;
+
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+
+use ffmpeg::format::{context::Input, input, Pixel};
+use ffmpeg::media::Type;
+use ffmpeg::software::scaling::{context::Context, flag::Flags};
+use ffmpeg::util::frame::video::Video;
+
+use crate::decoder::{fit_size, DecoderOptions, FrameSource, StreamInfo, VideoFrame};
+use crate::media::MediaInput;
+use crate::renderer::Frame;
+
+// Decode a media in process with libav
+pub struct LibavSource {
+  ictx: Input,
+  stream_index: usize,
+  decoder: ffmpeg::decoder::Video,
+  scaler: Context,
+  // Seconds per stream timestamp unit
+  time_base: f64,
+  info: StreamInfo,
+  width: u32,
+  height: u32,
+  // Whether the end of the packets was sent to the decoder
+  flushed: bool,
+  // Frame decoded while seeking, returned before decoding further
+  pending: Option<VideoFrame>,
+}
+
+fn to_io(e: ffmpeg::Error) -> Error {
+  Error::other(e.to_string())
+}
+
+impl LibavSource {
+  // Move to `position`, decoding and dropping the frames before it when the input cannot seek
+  fn skip_to(&mut self, position: Duration) -> std::io::Result<()> {
+    if self.info.seekable {
+      return self.seek(position);
+    }
+    while let Some(frame) = self.next_frame()? {
+      if frame.pts >= position {
+        self.pending = Some(frame);
+        break;
+      }
+    }
+    Ok(())
+  }
+
+  // Scale a decoded frame to RGB24, dropping the line padding
+  fn convert(&mut self, decoded: &Video) -> std::io::Result<Frame> {
+    let mut rgb = Video::empty();
+    self.scaler.run(decoded, &mut rgb).map_err(to_io)?;
+
+    let stride = rgb.stride(0);
+    let line = (self.width * 3) as usize;
+    let mut data = Vec::with_capacity(line * self.height as usize);
+    for y in 0..self.height as usize {
+      data.extend_from_slice(&rgb.data(0)[y * stride..y * stride + line]);
+    }
+    Ok(Frame::new(self.width, self.height, data))
+  }
+}
+
+impl FrameSource for LibavSource {
+  fn open(input_media: &MediaInput, options: &DecoderOptions) -> std::io::Result<LibavSource> {
+    ffmpeg::init().map_err(to_io)?;
+
+    let ictx = match input_media {
+      MediaInput::File(path) => input(path),
+      MediaInput::Stdin => input(&"pipe:0"),
+      MediaInput::Stream(_) => {
+        return Err(Error::new(ErrorKind::Unsupported, "The libav decoder cannot play streams, use ffmpeg-cli"));
+      },
+    }.map_err(to_io)?;
+
+    let stream = ictx.streams().best(Type::Video)
+      .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("{} has no video stream", input_media)))?;
+    let stream_index = stream.index();
+    let time_base = f64::from(stream.time_base());
+    let fps = f64::from(stream.avg_frame_rate());
+
+    let context = ffmpeg::codec::context::Context::from_parameters(stream.parameters()).map_err(to_io)?;
+    let decoder = context.decoder().video().map_err(to_io)?;
+
+    let (width, height) = fit_size(decoder.width(), decoder.height(), options);
+    let scaler = Context::get(
+      decoder.format(),
+      decoder.width(),
+      decoder.height(),
+      Pixel::RGB24,
+      width,
+      height,
+      Flags::BILINEAR,
+    ).map_err(to_io)?;
+
+    // The container duration is in AV_TIME_BASE units, microseconds
+    let duration = match ictx.duration() {
+      d if d > 0 => Some(Duration::from_micros(d as u64)),
+      _ => None,
+    };
+
+    let info = StreamInfo {
+      width: decoder.width(),
+      height: decoder.height(),
+      fps: if fps > 0.0 { fps } else { options.fps as f64 },
+      duration,
+      seekable: matches!(input_media, MediaInput::File(_)),
+    };
+
+    let mut source = LibavSource {
+      ictx,
+      stream_index,
+      decoder,
+      scaler,
+      time_base,
+      info,
+      width,
+      height,
+      flushed: false,
+      pending: None,
+    };
+    if !options.start.is_zero() {
+      source.skip_to(options.start)?;
+    }
+    Ok(source)
+  }
+
+  fn info(&self) -> &StreamInfo {
+    &self.info
+  }
+
+  fn next_frame(&mut self) -> std::io::Result<Option<VideoFrame>> {
+    if let Some(frame) = self.pending.take() {
+      return Ok(Some(frame));
+    }
+
+    loop {
+      let mut decoded = Video::empty();
+      if self.decoder.receive_frame(&mut decoded).is_ok() {
+        let timestamp = decoded.timestamp().unwrap_or(0).max(0);
+        let pts = Duration::from_secs_f64(timestamp as f64 * self.time_base);
+        let frame = self.convert(&decoded)?;
+        return Ok(Some(VideoFrame { pts, frame }));
+      }
+      if self.flushed {
+        return Ok(None);
+      }
+
+      // Feed the decoder with the next packet of the video stream
+      let packet = self.ictx.packets().next().map(|(stream, packet)| (stream.index(), packet));
+      match packet {
+        Some((index, packet)) if index == self.stream_index => {
+          self.decoder.send_packet(&packet).map_err(to_io)?;
+        },
+        Some(_) => {},
+        None => {
+          self.decoder.send_eof().map_err(to_io)?;
+          self.flushed = true;
+        },
+      }
+    }
+  }
+
+  fn seek(&mut self, position: Duration) -> std::io::Result<()> {
+    if !self.info.seekable {
+      return Err(Error::new(ErrorKind::Unsupported, "Cannot seek in stdin"));
+    }
+
+    let timestamp = position.as_micros() as i64;
+    self.ictx.seek(timestamp, ..timestamp).map_err(to_io)?;
+    self.decoder.flush();
+    self.flushed = false;
+    self.pending = None;
+
+    // libav lands on the keyframe before the position, decode up to it
+    while let Some(frame) = self.next_frame()? {
+      if frame.pts >= position {
+        self.pending = Some(frame);
+        break;
+      }
+    }
+    Ok(())
+  }
+}
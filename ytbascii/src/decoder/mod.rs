@@ -0,0 +1,151 @@
+#[cfg(feature = "ffmpeg-cli")]
+pub mod ffmpeg;
+#[cfg(feature = "libav")]
+pub mod libav;
+pub mod y4m;
+
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+use crate::get_logger;
+use crate::media::MediaInput;
+use crate::renderer::Frame;
+
+// The decoders that can be compiled in
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Backend {
+  // An `ffmpeg` child process piping raw video (feature `ffmpeg-cli`)
+  FfmpegCli,
+  // In process decoding with libav (feature `libav`)
+  Libav,
+  // Pure Rust reader for YUV4MPEG2 files
+  Y4m,
+}
+
+// What the decoder knows about the video stream
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamInfo {
+  // The source width in pixels
+  pub width: u32,
+  // The source height in pixels
+  pub height: u32,
+  // The rate frames are produced at
+  pub fps: f64,
+  // The media duration, when it can be known
+  pub duration: Option<Duration>,
+  // Whether `seek` can be used
+  pub seekable: bool,
+}
+
+// What the renderer expects from the decoder
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecoderOptions {
+  // Frames are scaled down to fit in `max_width` x `max_height`
+  pub max_width: u32,
+  pub max_height: u32,
+  // Frame rate the decoder should resample to, when it is able to
+  pub fps: u32,
+  // Where the decoding starts, the first frame returned is the first one at or after it
+  pub start: Duration,
+}
+
+// A decoded frame and its presentation time
+#[derive(Debug, Clone)]
+pub struct VideoFrame {
+  pub pts: Duration,
+  pub frame: Frame,
+}
+
+// A source of RGB frames, decoupling the renderer from the decoder
+pub trait FrameSource {
+  fn open(input: &MediaInput, options: &DecoderOptions) -> std::io::Result<Self> where Self: Sized;
+  fn info(&self) -> &StreamInfo;
+  // The next frame, `None` once the media is over
+  fn next_frame(&mut self) -> std::io::Result<Option<VideoFrame>>;
+  // Move to `position`, the next frame returned is the first one at or after it
+  fn seek(&mut self, position: Duration) -> std::io::Result<()>;
+}
+
+impl Backend {
+  pub fn is_available(&self) -> bool {
+    match self {
+      Backend::FfmpegCli => cfg!(feature = "ffmpeg-cli"),
+      Backend::Libav => cfg!(feature = "libav"),
+      Backend::Y4m => true,
+    }
+  }
+
+  // The backend used when none is asked for
+  pub fn detect(input: &MediaInput) -> Option<Backend> {
+    match input {
+      MediaInput::File(path) if path.extension().is_some_and(|ext| ext == "y4m") => return Some(Backend::Y4m),
+      // libav cannot read from a pipe it did not open
+      MediaInput::Stream(_) => return Some(Backend::FfmpegCli).filter(|backend| backend.is_available()),
+      _ => {},
+    }
+    [Backend::Libav, Backend::FfmpegCli].into_iter().find(|backend| backend.is_available())
+  }
+}
+
+pub fn unavailable(backend: Backend) -> Error {
+  Error::new(
+    ErrorKind::Unsupported,
+    format!("The {:?} decoder is not compiled in, rebuild with its cargo feature", backend),
+  )
+}
+
+// Open `input` with `backend`, or the best available backend when `None`
+pub fn open(input: &MediaInput, options: &DecoderOptions, backend: Option<Backend>) -> std::io::Result<Box<dyn FrameSource>> {
+  let backend = match backend.or_else(|| Backend::detect(input)) {
+    Some(backend) => backend,
+    None => return Err(Error::new(ErrorKind::Unsupported, "No decoder backend is compiled in")),
+  };
+  get_logger().debug(format!("Opening {} with the {:?} decoder", input, backend));
+
+  match backend {
+    #[cfg(feature = "ffmpeg-cli")]
+    Backend::FfmpegCli => Ok(Box::new(ffmpeg::FfmpegSource::open(input, options)?)),
+    #[cfg(feature = "libav")]
+    Backend::Libav => Ok(Box::new(libav::LibavSource::open(input, options)?)),
+    Backend::Y4m => Ok(Box::new(y4m::Y4mSource::open(input, options)?)),
+    #[allow(unreachable_patterns)]
+    backend => Err(unavailable(backend)),
+  }
+}
+
+// Largest size fitting in the options box while keeping the source aspect ratio
+pub fn fit_size(width: u32, height: u32, options: &DecoderOptions) -> (u32, u32) {
+  if width == 0 || height == 0 {
+    return (options.max_width, options.max_height);
+  }
+  let scale = (options.max_width as f64 / width as f64).min(options.max_height as f64 / height as f64).min(1.0);
+  (((width as f64 * scale) as u32).max(1), ((height as f64 * scale) as u32).max(1))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::path::PathBuf;
+
+  #[test]
+  fn fits_the_frames_in_the_options_box() {
+    let options = DecoderOptions { max_width: 80, max_height: 48, fps: 25, start: Duration::ZERO };
+    assert_eq!(fit_size(1920, 1080, &options), (80, 45));
+    assert_eq!(fit_size(1080, 1920, &options), (27, 48));
+    // Never scaled up
+    assert_eq!(fit_size(16, 8, &options), (16, 8));
+    assert_eq!(fit_size(0, 0, &options), (80, 48));
+  }
+
+  #[test]
+  fn opens_y4m_files_without_ffmpeg() {
+    let input = MediaInput::File(PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/clip.y4m")));
+    assert_eq!(Backend::detect(&input), Some(Backend::Y4m));
+    let options = DecoderOptions { max_width: 16, max_height: 8, fps: 10, start: Duration::ZERO };
+    let mut source = open(&input, &options, None).unwrap();
+    assert_eq!(source.info().duration, Some(Duration::from_secs(1)));
+    assert!(source.next_frame().unwrap().is_some());
+  }
+}
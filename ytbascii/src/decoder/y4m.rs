@@ -0,0 +1,319 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Seek, SeekFrom};
+use std::time::Duration;
+
+use crate::decoder::{fit_size, DecoderOptions, FrameSource, StreamInfo, VideoFrame};
+use crate::media::MediaInput;
+use crate::renderer::Frame;
+
+// Chroma subsampling of the planes
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Chroma {
+  C420,
+  C422,
+  C444,
+  Mono,
+}
+
+// Reader for uncompressed YUV4MPEG2 streams, as written by `ffmpeg -f yuv4mpegpipe`
+pub struct Y4mSource {
+  input: MediaInput,
+  reader: BufReader<Box<dyn Read>>,
+  info: StreamInfo,
+  chroma: Chroma,
+  // Length of the stream header, in bytes
+  header_len: u64,
+  // Size of the output frames
+  width: u32,
+  height: u32,
+  index: u64,
+}
+
+fn invalid(message: String) -> Error {
+  Error::new(ErrorKind::InvalidData, message)
+}
+
+impl Chroma {
+  fn parse(tag: &str) -> std::io::Result<Chroma> {
+    match tag {
+      "420" | "420jpeg" | "420paldv" | "420mpeg2" => Ok(Chroma::C420),
+      "422" => Ok(Chroma::C422),
+      "444" => Ok(Chroma::C444),
+      "mono" => Ok(Chroma::Mono),
+      _ => Err(invalid(format!("Unsupported y4m colorspace C{}", tag))),
+    }
+  }
+
+  // Size of a chroma plane for a `width` x `height` frame
+  fn plane_size(&self, width: u32, height: u32) -> (u32, u32) {
+    match self {
+      Chroma::C420 => (width.div_ceil(2), height.div_ceil(2)),
+      Chroma::C422 => (width.div_ceil(2), height),
+      Chroma::C444 => (width, height),
+      Chroma::Mono => (0, 0),
+    }
+  }
+}
+
+fn yuv_to_rgb(y: u8, u: u8, v: u8) -> [u8; 3] {
+  // BT.601, limited range
+  let c = y as i32 - 16;
+  let d = u as i32 - 128;
+  let e = v as i32 - 128;
+  let clamp = |value: i32| (value >> 8).clamp(0, 255) as u8;
+  [
+    clamp(298 * c + 409 * e + 128),
+    clamp(298 * c - 100 * d - 208 * e + 128),
+    clamp(298 * c + 516 * d + 128),
+  ]
+}
+
+impl Y4mSource {
+  fn reader(input: &MediaInput) -> std::io::Result<BufReader<Box<dyn Read>>> {
+    let reader: Box<dyn Read> = match input {
+      MediaInput::File(path) => Box::new(File::open(path)?),
+      MediaInput::Stdin => Box::new(std::io::stdin()),
+      MediaInput::Stream(stream) => Box::new(stream.reader()?),
+    };
+    Ok(BufReader::new(reader))
+  }
+
+  fn frame_len(&self) -> u64 {
+    let (cw, ch) = self.chroma.plane_size(self.info.width, self.info.height);
+    self.info.width as u64 * self.info.height as u64 + 2 * cw as u64 * ch as u64
+  }
+
+  fn read_line(&mut self) -> std::io::Result<Option<String>> {
+    let mut line = Vec::new();
+    if self.reader.read_until(b'\n', &mut line)? == 0 {
+      return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&line).trim_end().to_string()))
+  }
+
+  // Move to `position`, reading and dropping the frames before it when the input cannot seek
+  fn skip_to(&mut self, position: Duration) -> std::io::Result<()> {
+    if self.info.seekable {
+      return self.seek(position);
+    }
+    let index = (position.as_secs_f64() * self.info.fps) as u64;
+    let mut data = vec![0u8; self.frame_len() as usize];
+    while self.index < index && self.read_line()?.is_some() {
+      self.reader.read_exact(&mut data)?;
+      self.index += 1;
+    }
+    Ok(())
+  }
+
+  fn convert(&self, data: &[u8]) -> Frame {
+    let (width, height) = (self.info.width, self.info.height);
+    let (cw, ch) = self.chroma.plane_size(width, height);
+    let luma = &data[..(width * height) as usize];
+    let (u_plane, v_plane) = data[(width * height) as usize..].split_at((cw * ch) as usize);
+
+    let mut rgb = Vec::with_capacity((self.width * self.height * 3) as usize);
+    for oy in 0..self.height {
+      let y = oy * height / self.height;
+      for ox in 0..self.width {
+        let x = ox * width / self.width;
+        let (u, v) = match self.chroma {
+          Chroma::Mono => (128, 128),
+          _ => {
+            let cx = x * cw / width;
+            let cy = y * ch / height;
+            let i = (cy * cw + cx) as usize;
+            (u_plane[i], v_plane[i])
+          },
+        };
+        rgb.extend_from_slice(&yuv_to_rgb(luma[(y * width + x) as usize], u, v));
+      }
+    }
+    Frame::new(self.width, self.height, rgb)
+  }
+}
+
+impl FrameSource for Y4mSource {
+  fn open(input: &MediaInput, options: &DecoderOptions) -> std::io::Result<Y4mSource> {
+    let mut reader = Y4mSource::reader(input)?;
+    let mut header = Vec::new();
+    reader.read_until(b'\n', &mut header)?;
+    let header_len = header.len() as u64;
+    let header = String::from_utf8_lossy(&header).trim_end().to_string();
+
+    let mut params = header.split(' ');
+    if params.next() != Some("YUV4MPEG2") {
+      return Err(invalid(format!("{} is not a y4m stream", input)));
+    }
+
+    let (mut width, mut height, mut fps, mut chroma) = (0, 0, 25.0, Chroma::C420);
+    for param in params.filter(|param| !param.is_empty()) {
+      let mut chars = param.chars();
+      let (tag, value) = match chars.next() {
+        Some(tag) if tag.is_ascii_alphabetic() => (tag, chars.as_str()),
+        _ => return Err(invalid(format!("Invalid y4m header parameter {:?}", param))),
+      };
+      match tag {
+        'W' => width = value.parse().map_err(|_| invalid(format!("Invalid y4m width {}", value)))?,
+        'H' => height = value.parse().map_err(|_| invalid(format!("Invalid y4m height {}", value)))?,
+        'F' => {
+          if let Some((num, den)) = value.split_once(':') {
+            let (num, den) = (num.parse::<f64>().unwrap_or(0.0), den.parse::<f64>().unwrap_or(0.0));
+            if num > 0.0 && den > 0.0 {
+              fps = num / den;
+            }
+          }
+        },
+        'C' => chroma = Chroma::parse(value)?,
+        _ => {},
+      }
+    }
+    if width == 0 || height == 0 {
+      return Err(invalid(format!("{} has no frame size", input)));
+    }
+
+    let mut source = Y4mSource {
+      input: input.clone(),
+      reader,
+      info: StreamInfo { width, height, fps, duration: None, seekable: false },
+      chroma,
+      header_len,
+      width: 0,
+      height: 0,
+      index: 0,
+    };
+    (source.width, source.height) = fit_size(width, height, options);
+
+    // Every frame has the same size as long as they carry no parameter, which is what encoders write
+    if let MediaInput::File(path) = input {
+      let frames = std::fs::metadata(path)?.len().saturating_sub(header_len) / (6 + source.frame_len());
+      source.info.duration = Some(Duration::from_secs_f64(frames as f64 / fps));
+      source.info.seekable = true;
+    }
+
+    if !options.start.is_zero() {
+      source.skip_to(options.start)?;
+    }
+    Ok(source)
+  }
+
+  fn info(&self) -> &StreamInfo {
+    &self.info
+  }
+
+  fn next_frame(&mut self) -> std::io::Result<Option<VideoFrame>> {
+    match self.read_line()? {
+      None => return Ok(None),
+      Some(line) if line.starts_with("FRAME") => {},
+      Some(line) => return Err(invalid(format!("Unexpected y4m frame header {:?}", line))),
+    }
+
+    let mut data = vec![0u8; self.frame_len() as usize];
+    match self.reader.read_exact(&mut data) {
+      Ok(()) => {},
+      Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+      Err(e) => return Err(e),
+    }
+
+    let pts = Duration::from_secs_f64(self.index as f64 / self.info.fps);
+    self.index += 1;
+    Ok(Some(VideoFrame { pts, frame: self.convert(&data) }))
+  }
+
+  fn seek(&mut self, position: Duration) -> std::io::Result<()> {
+    let path = match (&self.input, self.info.seekable) {
+      (MediaInput::File(path), true) => path,
+      _ => return Err(Error::new(ErrorKind::Unsupported, format!("Cannot seek in {}", self.input))),
+    };
+
+    let index = (position.as_secs_f64() * self.info.fps) as u64;
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(self.header_len + index * (6 + self.frame_len())))?;
+    self.reader = BufReader::new(Box::new(file));
+    self.index = index;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::renderer::{RenderMode, Renderer, Rgb};
+  use std::path::PathBuf;
+
+  // 10 gray frames of 16x8 at 10 fps, from black to white
+  fn clip() -> MediaInput {
+    MediaInput::File(PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/clip.y4m")))
+  }
+
+  fn options(max_width: u32, max_height: u32) -> DecoderOptions {
+    DecoderOptions { max_width, max_height, fps: 10, start: Duration::ZERO }
+  }
+
+  // A y4m file holding `header` and nothing else
+  fn header_file(name: &str, header: &str) -> MediaInput {
+    let path = std::env::temp_dir().join(format!("ytbascii-{}-{}.y4m", name, std::process::id()));
+    std::fs::write(&path, format!("{}\n", header)).unwrap();
+    MediaInput::File(path)
+  }
+
+  #[test]
+  fn reads_the_stream_header() {
+    let source = Y4mSource::open(&clip(), &options(64, 64)).unwrap();
+    let info = source.info();
+    assert_eq!((info.width, info.height, info.fps), (16, 8, 10.0));
+    assert_eq!(info.duration, Some(Duration::from_secs(1)));
+    assert!(info.seekable);
+  }
+
+  #[test]
+  fn decodes_every_frame_in_order() {
+    let mut source = Y4mSource::open(&clip(), &options(8, 8)).unwrap();
+    let mut index = 0;
+    while let Some(frame) = source.next_frame().unwrap() {
+      assert_eq!(frame.pts, Duration::from_millis(index * 100));
+      // Scaled down to the options box, keeping the aspect ratio
+      assert_eq!((frame.frame.width, frame.frame.height), (8, 4));
+      let [r, g, b] = yuv_to_rgb(16 + index as u8 * 24, 128, 128);
+      assert_eq!(frame.frame.pixel(3, 2), Rgb(r, g, b));
+      index += 1;
+    }
+    assert_eq!(index, 10);
+  }
+
+  #[test]
+  fn seeks_to_a_frame() {
+    let mut source = Y4mSource::open(&clip(), &options(16, 8)).unwrap();
+    source.seek(Duration::from_millis(500)).unwrap();
+    assert_eq!(source.next_frame().unwrap().unwrap().pts, Duration::from_millis(500));
+    source.seek(Duration::ZERO).unwrap();
+    assert_eq!(source.next_frame().unwrap().unwrap().pts, Duration::ZERO);
+  }
+
+  #[test]
+  fn opens_at_the_start_asked() {
+    let options = DecoderOptions { start: Duration::from_millis(700), ..options(16, 8) };
+    let mut source = Y4mSource::open(&clip(), &options).unwrap();
+    assert_eq!(source.next_frame().unwrap().unwrap().pts, Duration::from_millis(700));
+  }
+
+  #[test]
+  fn renders_the_decoded_frames() {
+    let mut source = Y4mSource::open(&clip(), &options(16, 8)).unwrap();
+    let renderer = Renderer::new(RenderMode::Ascii, 16, 4);
+    let first = renderer.render(&source.next_frame().unwrap().unwrap().frame);
+    assert!(first.to_plain().chars().all(|c| c == ' ' || c == '\n'));
+    source.seek(Duration::from_millis(900)).unwrap();
+    let last = renderer.render(&source.next_frame().unwrap().unwrap().frame);
+    assert!(last.cells.iter().all(|cell| cell.ch == '%'));
+  }
+
+  #[test]
+  fn rejects_malformed_headers() {
+    let open = |name, header| Y4mSource::open(&header_file(name, header), &options(16, 8));
+    assert!(open("signature", "YUV4MPEG W16 H8").is_err());
+    assert!(open("size", "YUV4MPEG2 F25:1").is_err());
+    assert!(open("width", "YUV4MPEG2 Wide H8").is_err());
+    assert!(open("colorspace", "YUV4MPEG2 W16 H8 C411").is_err());
+    assert_eq!(open("tag", "YUV4MPEG2 W16 H8 éx").err().map(|e| e.kind()), Some(ErrorKind::InvalidData));
+  }
+}
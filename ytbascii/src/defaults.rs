@@ -47,4 +47,34 @@ pub static DEFAULT_INVIDIOUS_CONFIG: &str = r#"{
       "url": "https://invidious.tube"
     }
   ]
-}"#;
\ No newline at end of file
+}"#;
+
+// Where the key bindings are read from, the default keys are used when it does not exist
+pub static DEFAULT_KEYMAP_PATH: &str = "keymap.json";
+
+// Where the colors are read from, the terminal background picks a built-in theme when it does not exist
+pub static DEFAULT_THEME_PATH: &str = "theme.json";
+
+// Default frame rate of the player
+pub static DEFAULT_FPS: u32 = 24;
+
+// Most bytes of a stream fetched ahead of the decoder
+pub static STREAM_BUFFER_SIZE: usize = 16 * 1024 * 1024;
+
+// Size of the range requests a stream is fetched with
+pub static STREAM_CHUNK_SIZE: u64 = 1024 * 1024;
+
+// Media duration fetched before the playback starts, and again after the network fell behind
+pub static STREAM_PREBUFFER: std::time::Duration = std::time::Duration::from_secs(2);
+
+// Where the download queue is kept
+pub static DEFAULT_DOWNLOADS_PATH: &str = "data/downloads.json";
+
+// Default number of videos downloaded at the same time
+pub static DEFAULT_DOWNLOAD_JOBS: usize = 2;
+
+// Where the fetched thumbnails are kept, by url
+pub static DEFAULT_THUMBNAILS_PATH: &str = "data/thumbnails";
+
+// Where the interface keeps the recent searches, the watch history and the subscriptions
+pub static DEFAULT_LIBRARY_PATH: &str = "data/library.json";
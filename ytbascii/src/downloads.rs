@@ -0,0 +1,152 @@
+use std::io::Write;
+use std::time::Duration;
+
+use youtube::mux::Container;
+use youtube::queue::{DownloadManager, Job, JobState, QueueOptions};
+
+use crate::cli::{DownloadsAction, JobTarget};
+use crate::renderer::RenderMode;
+use crate::{api_client, defaults, get_logger};
+
+// How often `downloads run` prints the progress and picks up the queue changes
+static REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+// The queue the CLI and the UI share
+pub fn open_queue(jobs: usize, container: Option<Container>) -> Result<DownloadManager, youtube::models::error::Error> {
+  // Videos are downloaded in the quality the player would stream in this terminal
+  let (cols, rows) = termion::terminal_size().unwrap_or((80, 24));
+  let (width, height) = RenderMode::Color.pixel_size(cols, rows.saturating_sub(1).max(1));
+  DownloadManager::open(QueueOptions { path: defaults::DEFAULT_DOWNLOADS_PATH.to_string(), jobs, width, height, container })
+}
+
+// The video ids of a playlist, fetched page by page from an Invidious server
+fn playlist_videos(playlist_id: &str) -> std::io::Result<Vec<String>> {
+  let client = api_client();
+
+  let mut videos = vec![];
+  for page in 1.. {
+    let playlist = client.get_playlist(playlist_id.to_string(), api::fetcher::PlaylistParams { page: Some(page) })?;
+    let count = videos.len();
+    for video in playlist.videos {
+      if !videos.contains(&video.videoId) {
+        videos.push(video.videoId);
+      }
+    }
+    // Invidious repeats the last page past the end
+    if videos.len() == count {
+      break;
+    }
+  }
+  Ok(videos)
+}
+
+fn add(manager: &DownloadManager, ids: Vec<String>) {
+  for input in ids {
+    if let Some(id) = youtube::utils::parse_video_id(&input) {
+      match manager.add_video(&id) {
+        true => get_logger().success(format!("Queued {}", id)),
+        false => get_logger().info(format!("{} is already queued", id)),
+      }
+    } else if let Some(id) = youtube::utils::parse_playlist_id(&input) {
+      match playlist_videos(&id) {
+        Ok(videos) => {
+          let count = videos.len();
+          let added = manager.add_playlist(&id, videos);
+          get_logger().success(format!("Queued {} of the {} videos of {}", added, count, id));
+        },
+        Err(e) => get_logger().error(format!("Failed to fetch the playlist {}: {}", id, e)),
+      }
+    } else {
+      get_logger().error(format!("{} is not a video or playlist id", input));
+    }
+  }
+}
+
+fn format_size(bytes: u64) -> String {
+  format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+fn list(jobs: &[Job]) {
+  if jobs.is_empty() {
+    println!("The download queue is empty");
+    return;
+  }
+  for job in jobs {
+    let progress = match job.progress.total {
+      0 => String::new(),
+      total => format!("{:5.1}% of {}", job.progress.fraction() * 100.0, format_size(total)),
+    };
+    println!(
+      "{:<11}  {:<20}  {:<20}  {}",
+      job.video_id,
+      job.state.to_string(),
+      progress,
+      job.title.as_deref().unwrap_or(""),
+    );
+  }
+}
+
+// A one line summary of the queue
+fn summary(jobs: &[Job]) -> String {
+  let count = |state: fn(&JobState) -> bool| jobs.iter().filter(|job| state(&job.state)).count();
+  let running: Vec<&Job> = jobs.iter().filter(|job| job.state == JobState::Running).collect();
+  let downloaded: u64 = running.iter().map(|job| job.progress.downloaded).sum();
+  let total: u64 = running.iter().map(|job| job.progress.total).sum();
+  format!(
+    "{} running ({} / {}), {} queued, {} done, {} failed",
+    running.len(),
+    format_size(downloaded),
+    format_size(total),
+    count(|state| *state == JobState::Queued),
+    count(|state| *state == JobState::Done),
+    count(|state| matches!(state, JobState::Failed(_))),
+  )
+}
+
+// Download until nothing is queued, following the changes other commands make to the queue
+fn run(manager: &DownloadManager) {
+  manager.start();
+  loop {
+    std::thread::sleep(REFRESH_INTERVAL);
+    if let Err(e) = manager.refresh() {
+      get_logger().warn(format!("Failed to reload the download queue: {}", e));
+    }
+    let jobs = manager.jobs();
+    print!("\r\x1b[K{}", summary(&jobs));
+    let _ = std::io::stdout().flush();
+    if !manager.is_busy() {
+      break;
+    }
+  }
+  println!();
+  manager.stop();
+}
+
+fn target(target: &JobTarget) -> Option<&str> {
+  target.id.as_deref().filter(|_| !target.all)
+}
+
+pub fn downloads(action: Option<DownloadsAction>) {
+  let (jobs, container) = match &action {
+    Some(DownloadsAction::Run { jobs, mux }) => (*jobs, *mux),
+    _ => (defaults::DEFAULT_DOWNLOAD_JOBS, None),
+  };
+  let manager = match open_queue(jobs, container) {
+    Ok(manager) => manager,
+    Err(e) => {
+      get_logger().error(format!("Failed to open the download queue: {}", e));
+      std::process::exit(1);
+    },
+  };
+
+  match action.unwrap_or(DownloadsAction::List) {
+    DownloadsAction::Add { ids } => add(&manager, ids),
+    DownloadsAction::Run { .. } => run(&manager),
+    DownloadsAction::List => list(&manager.jobs()),
+    DownloadsAction::Pause(job) => println!("Paused {} downloads", manager.pause(target(&job))),
+    DownloadsAction::Resume(job) => println!("Resumed {} downloads", manager.resume(target(&job))),
+    DownloadsAction::Cancel(job) => println!("Cancelled {} downloads", manager.cancel(target(&job))),
+    DownloadsAction::Retry(job) => println!("Queued {} downloads again", manager.retry(target(&job))),
+    DownloadsAction::Clear => println!("Removed {} downloads", manager.clear()),
+  }
+}
@@ -0,0 +1,52 @@
+use std::io::Write;
+use std::time::Duration;
+
+use crate::export::FrameWriter;
+use crate::renderer::AsciiFrame;
+
+// Every frame drawn over the previous one from the top left corner. There is no
+// timing in the file, `cat` plays it as fast as the terminal draws.
+pub struct AnsiWriter<W: Write> {
+  out: W,
+}
+
+impl<W: Write> AnsiWriter<W> {
+  pub fn new(mut out: W) -> std::io::Result<AnsiWriter<W>> {
+    // Hide the cursor and clear the screen
+    write!(out, "\x1b[?25l\x1b[2J")?;
+    Ok(AnsiWriter { out })
+  }
+}
+
+impl<W: Write> FrameWriter for AnsiWriter<W> {
+  fn write_frame(&mut self, _pts: Duration, frame: &AsciiFrame) -> std::io::Result<()> {
+    write!(self.out, "\x1b[H{}", frame.to_ansi())
+  }
+
+  fn finish(mut self: Box<Self>) -> std::io::Result<()> {
+    write!(self.out, "\x1b[0m\x1b[?25h\r\n")?;
+    self.out.flush()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::export::tests::frame;
+  use crate::renderer::Rgb;
+
+  #[test]
+  fn draws_each_frame_from_the_top_left_corner() {
+    let mut out = vec![];
+    let mut writer = Box::new(AnsiWriter::new(&mut out).unwrap());
+    let frames = [frame("ab", Rgb(255, 0, 0)), frame("cd", Rgb(0, 255, 0))];
+    for (index, frame) in frames.iter().enumerate() {
+      writer.write_frame(Duration::from_millis(index as u64 * 100), frame).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let text = String::from_utf8(out).unwrap();
+    let expected = format!("\x1b[?25l\x1b[2J\x1b[H{}\x1b[H{}\x1b[0m\x1b[?25h\r\n", frames[0].to_ansi(), frames[1].to_ansi());
+    assert_eq!(text, expected);
+  }
+}
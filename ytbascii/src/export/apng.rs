@@ -0,0 +1,190 @@
+use std::io::Write;
+use std::time::Duration;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use crate::export::raster::{rasterize, Palette};
+use crate::export::{ExportOptions, FrameWriter};
+use crate::renderer::AsciiFrame;
+
+static PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+// An animated PNG looping forever. The file starts with the number of frames, so the
+// compressed frames are kept until the last one and written by `finish`.
+pub struct ApngWriter<W: Write> {
+  out: W,
+  palette: Palette,
+  width: u32,
+  height: u32,
+  // Shown for this long when it is the last one
+  frame_duration: Duration,
+  // The zlib compressed scanlines of each frame, and when they are shown
+  frames: Vec<(Duration, Vec<u8>)>,
+}
+
+// Write a chunk: its length, type, data and the crc of the type and data
+fn write_chunk(out: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> std::io::Result<()> {
+  let mut crc = crc32fast::Hasher::new();
+  crc.update(kind);
+  crc.update(data);
+  out.write_all(&(data.len() as u32).to_be_bytes())?;
+  out.write_all(kind)?;
+  out.write_all(data)?;
+  out.write_all(&crc.finalize().to_be_bytes())
+}
+
+impl<W: Write> ApngWriter<W> {
+  pub fn new(out: W, options: &ExportOptions, width: u32, height: u32) -> ApngWriter<W> {
+    ApngWriter {
+      out,
+      palette: options.palette,
+      width,
+      height,
+      frame_duration: Duration::from_secs_f64(1.0 / options.fps.max(1) as f64),
+      frames: vec![],
+    }
+  }
+
+  // The control chunk of a frame shown for `delay`
+  fn frame_control(&self, sequence: u32, delay: Duration) -> Vec<u8> {
+    let mut data = Vec::with_capacity(26);
+    data.extend(sequence.to_be_bytes());
+    data.extend(self.width.to_be_bytes());
+    data.extend(self.height.to_be_bytes());
+    // Offsets of the frame
+    data.extend(0u32.to_be_bytes());
+    data.extend(0u32.to_be_bytes());
+    // The delay in milliseconds
+    data.extend((delay.as_millis().min(u16::MAX as u128) as u16).to_be_bytes());
+    data.extend(1000u16.to_be_bytes());
+    // Keep the frame when the next one is drawn, and replace the pixels rather than blending
+    data.extend([0, 0]);
+    data
+  }
+}
+
+impl<W: Write> FrameWriter for ApngWriter<W> {
+  fn write_frame(&mut self, pts: Duration, frame: &AsciiFrame) -> std::io::Result<()> {
+    let mut image = rasterize(frame);
+    self.palette.quantize(&mut image);
+
+    // Every scanline starts with its filter type, none here
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    for line in image.data.chunks_exact(image.width as usize * 3) {
+      encoder.write_all(&[0])?;
+      encoder.write_all(line)?;
+    }
+    self.frames.push((pts, encoder.finish()?));
+    Ok(())
+  }
+
+  fn finish(mut self: Box<Self>) -> std::io::Result<()> {
+    if self.frames.is_empty() {
+      return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "An APNG needs at least one frame"));
+    }
+    let mut header = Vec::with_capacity(13);
+    header.extend(self.width.to_be_bytes());
+    header.extend(self.height.to_be_bytes());
+    // 8 bits RGB, deflate, adaptive filtering, not interlaced
+    header.extend([8, 2, 0, 0, 0]);
+    let mut animation = Vec::with_capacity(8);
+    animation.extend((self.frames.len() as u32).to_be_bytes());
+    // Loop forever
+    animation.extend(0u32.to_be_bytes());
+
+    self.out.write_all(&PNG_SIGNATURE)?;
+    write_chunk(&mut self.out, b"IHDR", &header)?;
+    write_chunk(&mut self.out, b"acTL", &animation)?;
+
+    // The control and data chunks share one sequence
+    let mut sequence = 0;
+    for (index, (pts, data)) in self.frames.iter().enumerate() {
+      let delay = match self.frames.get(index + 1) {
+        Some((next, _)) => next.saturating_sub(*pts),
+        None => self.frame_duration,
+      };
+      let control = self.frame_control(sequence, delay);
+      write_chunk(&mut self.out, b"fcTL", &control)?;
+      sequence += 1;
+      // The first frame is the image shown by the viewers without animations
+      if index == 0 {
+        write_chunk(&mut self.out, b"IDAT", data)?;
+      } else {
+        let mut frame_data = Vec::with_capacity(data.len() + 4);
+        frame_data.extend(sequence.to_be_bytes());
+        frame_data.extend(data);
+        write_chunk(&mut self.out, b"fdAT", &frame_data)?;
+        sequence += 1;
+      }
+    }
+    write_chunk(&mut self.out, b"IEND", &[])?;
+    self.out.flush()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::export::raster::{CELL_HEIGHT, CELL_WIDTH};
+  use crate::export::tests::{frame, options};
+  use crate::export::ExportFormat;
+  use crate::renderer::Rgb;
+
+  // The type and data of each chunk, checking their crc
+  fn chunks(png: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+    let mut chunks = vec![];
+    let mut rest = png;
+    while !rest.is_empty() {
+      let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+      let (kind, data) = (&rest[4..8], &rest[8..8 + length]);
+      let crc = u32::from_be_bytes(rest[8 + length..12 + length].try_into().unwrap());
+      assert_eq!(crc, crc32fast::hash(&rest[4..8 + length]), "Wrong crc for {}", String::from_utf8_lossy(kind));
+      chunks.push((kind.try_into().unwrap(), data.to_vec()));
+      rest = &rest[12 + length..];
+    }
+    chunks
+  }
+
+  fn number(data: &[u8], at: usize) -> u32 {
+    u32::from_be_bytes(data[at..at + 4].try_into().unwrap())
+  }
+
+  #[test]
+  fn writes_an_animated_png() {
+    let mut out = vec![];
+    let (width, height) = (2 * CELL_WIDTH, CELL_HEIGHT);
+    let mut writer = Box::new(ApngWriter::new(&mut out, &options(ExportFormat::Apng), width, height));
+    for (index, text) in ["ab", "cd", "ef"].iter().enumerate() {
+      writer.write_frame(Duration::from_millis(index as u64 * 250), &frame(text, Rgb(255, 255, 255))).unwrap();
+    }
+    writer.finish().unwrap();
+
+    assert_eq!(out[..8], PNG_SIGNATURE);
+    let chunks = chunks(&out[8..]);
+    let kinds: Vec<&[u8]> = chunks.iter().map(|(kind, _)| &kind[..]).collect();
+    assert_eq!(kinds, [&b"IHDR"[..], b"acTL", b"fcTL", b"IDAT", b"fcTL", b"fdAT", b"fcTL", b"fdAT", b"IEND"]);
+    assert_eq!((number(&chunks[0].1, 0), number(&chunks[0].1, 4)), (width, height));
+    // Number of frames, then of loops
+    assert_eq!((number(&chunks[1].1, 0), number(&chunks[1].1, 4)), (3, 0));
+
+    // The control and data chunks share one sequence, the IDAT has no number
+    let sequence: Vec<u32> = chunks.iter()
+      .filter(|(kind, _)| kind == b"fcTL" || kind == b"fdAT")
+      .map(|(_, data)| number(data, 0))
+      .collect();
+    assert_eq!(sequence, (0..5).collect::<Vec<u32>>());
+    // The delays in milliseconds, the last frame lasting a frame at 10 fps
+    let delays: Vec<u16> = chunks.iter()
+      .filter(|(kind, _)| kind == b"fcTL")
+      .map(|(_, data)| u16::from_be_bytes([data[20], data[21]]))
+      .collect();
+    assert_eq!(delays, [250, 250, 100]);
+  }
+
+  #[test]
+  fn needs_a_frame() {
+    let writer = Box::new(ApngWriter::new(vec![], &options(ExportFormat::Apng), CELL_WIDTH, CELL_HEIGHT));
+    assert_eq!(writer.finish().unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+  }
+}
@@ -0,0 +1,77 @@
+use std::io::Write;
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::export::{ExportOptions, FrameWriter};
+use crate::renderer::AsciiFrame;
+
+// An asciinema v2 recording: a json header line, then one `[time, "o", data]`
+// output event per line
+pub struct AsciicastWriter<W: Write> {
+  out: W,
+  // Time of the last frame, the cursor is shown again then
+  last: Duration,
+}
+
+impl<W: Write> AsciicastWriter<W> {
+  pub fn new(mut out: W, options: &ExportOptions) -> std::io::Result<AsciicastWriter<W>> {
+    let header = json!({
+      "version": 2,
+      "width": options.cols,
+      "height": options.rows,
+      "timestamp": chrono::offset::Utc::now().timestamp(),
+      "title": options.title,
+      "env": { "TERM": "xterm-256color" },
+    });
+    writeln!(out, "{}", header)?;
+    // Hide the cursor and clear the screen before the first frame
+    writeln!(out, "{}", json!([0.0, "o", "\x1b[?25l\x1b[2J"]))?;
+    Ok(AsciicastWriter { out, last: Duration::ZERO })
+  }
+}
+
+impl<W: Write> FrameWriter for AsciicastWriter<W> {
+  fn write_frame(&mut self, pts: Duration, frame: &AsciiFrame) -> std::io::Result<()> {
+    let data = format!("\x1b[H{}", frame.to_ansi());
+    self.last = pts;
+    // Times are written to the microsecond, as asciinema does
+    writeln!(self.out, "[{:.6}, \"o\", {}]", pts.as_secs_f64(), serde_json::Value::from(data))
+  }
+
+  fn finish(mut self: Box<Self>) -> std::io::Result<()> {
+    writeln!(self.out, "[{:.6}, \"o\", {}]", self.last.as_secs_f64(), json!("\x1b[0m\x1b[?25h\r\n"))?;
+    self.out.flush()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::export::tests::{frame, options};
+  use crate::export::ExportFormat;
+  use crate::renderer::Rgb;
+
+  #[test]
+  fn writes_a_header_then_timed_output_events() {
+    let mut out = vec![];
+    let mut writer = Box::new(AsciicastWriter::new(&mut out, &options(ExportFormat::Asciicast)).unwrap());
+    for (index, text) in ["ab", "cd", "ef"].iter().enumerate() {
+      writer.write_frame(Duration::from_millis(index as u64 * 100), &frame(text, Rgb(255, 255, 255))).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let text = String::from_utf8(out).unwrap();
+    let mut lines = text.lines().map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap());
+    let header = lines.next().unwrap();
+    assert_eq!((header["version"].as_u64(), header["width"].as_u64(), header["height"].as_u64()), (Some(2), Some(2), Some(1)));
+    assert_eq!(header["title"], "clip");
+
+    let events: Vec<serde_json::Value> = lines.collect();
+    assert_eq!(events.len(), 5);
+    let times: Vec<f64> = events.iter().map(|event| event[0].as_f64().unwrap()).collect();
+    assert_eq!(times, [0.0, 0.0, 0.1, 0.2, 0.2]);
+    assert!(events.iter().all(|event| event[1] == "o"));
+    assert!(events[3][2].as_str().unwrap().starts_with("\x1b[H") && events[3][2].as_str().unwrap().contains("ef"));
+  }
+}
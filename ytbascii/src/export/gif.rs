@@ -0,0 +1,119 @@
+use std::io::Write;
+use std::time::Duration;
+
+use gif::{Encoder, Repeat};
+
+use crate::export::raster::{rasterize, Image, Palette};
+use crate::export::{ExportOptions, FrameWriter};
+use crate::renderer::{AsciiFrame, Rgb};
+
+// Speed of the adaptive palette quantizer, from 1 (best) to 30 (fastest)
+static QUANTIZER_SPEED: i32 = 10;
+
+// An animated GIF looping forever. A frame is written once the next one arrives,
+// its delay being the time between both.
+pub struct GifWriter<W: Write> {
+  encoder: Encoder<W>,
+  palette: Palette,
+  // Shown for this long when it is the last one
+  frame_duration: Duration,
+  pending: Option<(Duration, Image)>,
+}
+
+fn gif_error(error: gif::EncodingError) -> std::io::Error {
+  match error {
+    gif::EncodingError::Io(e) => e,
+    e => std::io::Error::other(e),
+  }
+}
+
+impl<W: Write> GifWriter<W> {
+  pub fn new(out: W, options: &ExportOptions, width: u32, height: u32) -> std::io::Result<GifWriter<W>> {
+    let too_large = || std::io::Error::new(std::io::ErrorKind::InvalidInput, "The frames are too large for a GIF");
+    let width = u16::try_from(width).map_err(|_| too_large())?;
+    let height = u16::try_from(height).map_err(|_| too_large())?;
+
+    let global_palette = options.palette.colors().unwrap_or_default();
+    let mut encoder = Encoder::new(out, width, height, &global_palette).map_err(gif_error)?;
+    encoder.set_repeat(Repeat::Infinite).map_err(gif_error)?;
+    Ok(GifWriter {
+      encoder,
+      palette: options.palette,
+      frame_duration: Duration::from_secs_f64(1.0 / options.fps.max(1) as f64),
+      pending: None,
+    })
+  }
+
+  fn encode(&mut self, image: &Image, delay: Duration) -> std::io::Result<()> {
+    let (width, height) = (image.width as u16, image.height as u16);
+    let mut frame = match self.palette {
+      Palette::Adaptive => gif::Frame::from_rgb_speed(width, height, &image.data, QUANTIZER_SPEED),
+      palette => {
+        let pixels: Vec<u8> = image.data.chunks_exact(3).map(|pixel| palette.index(Rgb(pixel[0], pixel[1], pixel[2]))).collect();
+        gif::Frame::from_indexed_pixels(width, height, pixels, None)
+      },
+    };
+    // GIF delays are in hundredths of a second
+    frame.delay = ((delay.as_millis() + 5) / 10).clamp(1, u16::MAX as u128) as u16;
+    self.encoder.write_frame(&frame).map_err(gif_error)
+  }
+}
+
+impl<W: Write> FrameWriter for GifWriter<W> {
+  fn write_frame(&mut self, pts: Duration, frame: &AsciiFrame) -> std::io::Result<()> {
+    if let Some((previous_pts, image)) = self.pending.take() {
+      self.encode(&image, pts.saturating_sub(previous_pts))?;
+    }
+    self.pending = Some((pts, rasterize(frame)));
+    Ok(())
+  }
+
+  fn finish(mut self: Box<Self>) -> std::io::Result<()> {
+    if let Some((_, image)) = self.pending.take() {
+      let delay = self.frame_duration;
+      self.encode(&image, delay)?;
+    }
+    let mut out = self.encoder.into_inner().map_err(gif_error)?;
+    out.flush()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::export::raster::{CELL_HEIGHT, CELL_WIDTH};
+  use crate::export::tests::{frame, options};
+  use crate::export::ExportFormat;
+
+  fn export(palette: Palette) -> Vec<u8> {
+    let mut out = vec![];
+    let options = ExportOptions { palette, ..options(ExportFormat::Gif) };
+    let mut writer = Box::new(GifWriter::new(&mut out, &options, 2 * CELL_WIDTH, CELL_HEIGHT).unwrap());
+    writer.write_frame(Duration::ZERO, &frame("ab", Rgb(255, 0, 0))).unwrap();
+    writer.write_frame(Duration::from_millis(300), &frame("cd", Rgb(0, 0, 255))).unwrap();
+    writer.finish().unwrap();
+    out
+  }
+
+  #[test]
+  fn writes_an_animated_gif() {
+    for palette in [Palette::Web, Palette::Gray, Palette::Adaptive] {
+      let gif = export(palette);
+      assert_eq!(&gif[..6], b"GIF89a");
+      let mut decoder = gif::DecodeOptions::new().read_info(&gif[..]).unwrap();
+      assert_eq!((decoder.width() as u32, decoder.height() as u32), (2 * CELL_WIDTH, CELL_HEIGHT));
+      let mut delays = vec![];
+      while let Some(frame) = decoder.read_next_frame().unwrap() {
+        delays.push(frame.delay);
+      }
+      // In hundredths of a second, the last frame lasting a frame at 10 fps
+      assert_eq!(delays, [30, 10], "{:?}", palette);
+    }
+  }
+
+  #[test]
+  fn refuses_frames_too_large() {
+    let error = GifWriter::new(vec![], &options(ExportFormat::Gif), 70_000, CELL_HEIGHT).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+  }
+}
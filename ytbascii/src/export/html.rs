@@ -0,0 +1,100 @@
+use std::io::Write;
+use std::time::Duration;
+
+use crate::export::{ExportOptions, FrameWriter};
+use crate::renderer::AsciiFrame;
+
+// Plays the `frames` array at their timestamps, a click pauses and resumes
+static PLAYER_SCRIPT: &str = r#"
+const screen = document.getElementById("screen");
+let index = 0, start = performance.now(), pausedAt = null;
+function show() {
+  if (pausedAt !== null) return;
+  const now = (performance.now() - start) / 1000;
+  while (index + 1 < frames.length && frames[index + 1][0] <= now) index++;
+  screen.innerHTML = frames[index][1];
+  if (index + 1 < frames.length) {
+    setTimeout(show, Math.max(0, (frames[index + 1][0] - now) * 1000));
+  } else {
+    // Start over once the last frame was shown for a second
+    setTimeout(() => { index = 0; start = performance.now(); show(); }, 1000);
+  }
+}
+screen.addEventListener("click", () => {
+  if (pausedAt === null) {
+    pausedAt = performance.now();
+  } else {
+    start += performance.now() - pausedAt;
+    pausedAt = null;
+    show();
+  }
+});
+if (frames.length > 0) show();
+"#;
+
+// A single page holding every frame as html, and the script playing them
+pub struct HtmlWriter<W: Write> {
+  out: W,
+}
+
+fn escape(text: &str) -> String {
+  text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+impl<W: Write> HtmlWriter<W> {
+  pub fn new(mut out: W, options: &ExportOptions) -> std::io::Result<HtmlWriter<W>> {
+    write!(
+      out,
+      concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n",
+        "body {{ margin: 0; background: #000; display: flex; justify-content: center; }}\n",
+        "#screen {{ margin: 0; font: 12px/1 monospace; color: #fff; cursor: pointer; }}\n",
+        "</style>\n</head>\n<body>\n<pre id=\"screen\" title=\"Click to pause\"></pre>\n<script>\nconst frames = [\n",
+      ),
+      escape(&options.title),
+    )?;
+    Ok(HtmlWriter { out })
+  }
+}
+
+impl<W: Write> FrameWriter for HtmlWriter<W> {
+  fn write_frame(&mut self, pts: Duration, frame: &AsciiFrame) -> std::io::Result<()> {
+    // A json string is a valid js string, `</` is escaped so no frame can end the script
+    let html = serde_json::to_string(&frame.to_html())?.replace("</", "<\\/");
+    writeln!(self.out, "[{:.6}, {}],", pts.as_secs_f64(), html)
+  }
+
+  fn finish(mut self: Box<Self>) -> std::io::Result<()> {
+    write!(self.out, "];\n{}</script>\n</body>\n</html>\n", PLAYER_SCRIPT)?;
+    self.out.flush()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::export::tests::{frame, options};
+  use crate::export::ExportFormat;
+  use crate::renderer::{Cell, Rgb};
+
+  #[test]
+  fn escapes_the_frames_in_colored_spans() {
+    let mut out = vec![];
+    let mut options = options(ExportFormat::Html);
+    options.title = "Tom & \"Jerry\"".to_string();
+    let mut writer = Box::new(HtmlWriter::new(&mut out, &options).unwrap());
+    let mut first = frame("<&\"", Rgb(255, 0, 0));
+    first.set(2, 0, Cell { ch: '"', fg: Rgb(0, 0, 255), bg: Some(Rgb(255, 255, 255)) });
+    writer.write_frame(Duration::ZERO, &first).unwrap();
+    writer.write_frame(Duration::from_millis(100), &frame("</b", Rgb(0, 255, 0))).unwrap();
+    writer.finish().unwrap();
+
+    let page = String::from_utf8(out).unwrap();
+    assert!(page.starts_with("<!DOCTYPE html>"));
+    assert!(page.contains("<title>Tom &amp; &quot;Jerry&quot;</title>"));
+    // The frames are json strings in the script, their quotes and `</` are escaped once more
+    assert!(page.contains(r#"[0.000000, "<span style=\"color:#ff0000\">&lt;&amp;<\/span><span style=\"color:#0000ff;background:#ffffff\">&quot;<\/span>\n"],"#));
+    assert!(page.contains(r#"[0.100000, "<span style=\"color:#00ff00\">&lt;/b<\/span>\n"],"#));
+    assert!(page.trim_end().ends_with("</html>"));
+  }
+}
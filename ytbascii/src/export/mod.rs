@@ -0,0 +1,145 @@
+pub mod ansi;
+pub mod apng;
+pub mod asciicast;
+pub mod gif;
+pub mod html;
+pub mod raster;
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+use crate::decoder::{self, Backend, DecoderOptions};
+use crate::get_logger;
+use crate::media::MediaInput;
+use crate::renderer::{AsciiFrame, RenderMode, Renderer};
+
+use raster::{Palette, CELL_HEIGHT, CELL_WIDTH};
+
+// The files a video can be pre-rendered to
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ExportFormat {
+  // An asciinema v2 recording, played with `asciinema play`
+  Asciicast,
+  // A self-contained web page playing the frames
+  Html,
+  // The frames as one stream of escape codes, played with `cat`
+  Ansi,
+  // An animated GIF of the frames drawn with a bitmap font
+  Gif,
+  // An animated PNG of the frames drawn with a bitmap font
+  Apng,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+  pub format: ExportFormat,
+  // How the frames are drawn
+  pub mode: RenderMode,
+  // Size of the rendered frames in cells
+  pub cols: u16,
+  pub rows: u16,
+  // Frame rate of the export
+  pub fps: u32,
+  // Decoder to use, detected from the input when `None`
+  pub decoder: Option<Backend>,
+  // Colors of the GIF and APNG images
+  pub palette: Palette,
+  // Part of the media exported
+  pub start: Duration,
+  pub duration: Option<Duration>,
+  // Shown by the players that have a title
+  pub title: String,
+}
+
+// Where the rendered frames go, one writer per format
+pub trait FrameWriter {
+  // Append a frame shown at `pts`, frames come in order
+  fn write_frame(&mut self, pts: Duration, frame: &AsciiFrame) -> std::io::Result<()>;
+  // Write what follows the last frame and flush the file
+  fn finish(self: Box<Self>) -> std::io::Result<()>;
+}
+
+impl ExportFormat {
+  pub fn extension(&self) -> &'static str {
+    match self {
+      ExportFormat::Asciicast => "cast",
+      ExportFormat::Html => "html",
+      ExportFormat::Ansi => "ans",
+      ExportFormat::Gif => "gif",
+      ExportFormat::Apng => "png",
+    }
+  }
+}
+
+// Open the writer of `options.format` on `output`
+pub fn create(options: &ExportOptions, output: &str) -> std::io::Result<Box<dyn FrameWriter>> {
+  let file = BufWriter::new(File::create(output)?);
+  let (width, height) = (options.cols as u32 * CELL_WIDTH, options.rows as u32 * CELL_HEIGHT);
+  Ok(match options.format {
+    ExportFormat::Asciicast => Box::new(asciicast::AsciicastWriter::new(file, options)?),
+    ExportFormat::Html => Box::new(html::HtmlWriter::new(file, options)?),
+    ExportFormat::Ansi => Box::new(ansi::AnsiWriter::new(file)?),
+    ExportFormat::Gif => Box::new(gif::GifWriter::new(file, options, width, height)?),
+    ExportFormat::Apng => Box::new(apng::ApngWriter::new(file, options, width, height)),
+  })
+}
+
+// Decode `input` as fast as possible and render the frames of the exported range to
+// `output`, returns the number of frames written. The times in the file start at the range.
+pub fn export(input: &MediaInput, options: &ExportOptions, output: &str) -> std::io::Result<u32> {
+  let renderer = Renderer::new(options.mode, options.cols, options.rows);
+  let (max_width, max_height) = renderer.frame_box();
+  let decoder_options = DecoderOptions { max_width, max_height, fps: options.fps, start: options.start };
+  let mut source = decoder::open(input, &decoder_options, options.decoder)?;
+  get_logger().info(format!("Exporting {} to {} as {:?}", input, output, options.format));
+  let end = options.duration.map(|duration| options.start + duration);
+
+  let mut writer = create(options, output)?;
+  let mut frames = 0;
+  while let Some(frame) = source.next_frame()? {
+    if frame.pts < options.start {
+      continue;
+    }
+    if end.is_some_and(|end| frame.pts >= end) {
+      break;
+    }
+    writer.write_frame(frame.pts - options.start, &renderer.render(&frame.frame))?;
+    frames += 1;
+  }
+  writer.finish()?;
+  get_logger().success(format!("Exported {} frames to {}", frames, output));
+  Ok(frames)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use super::*;
+  use crate::renderer::{Cell, Rgb};
+
+  pub fn options(format: ExportFormat) -> ExportOptions {
+    ExportOptions {
+      format,
+      mode: RenderMode::Ascii,
+      cols: 2,
+      rows: 1,
+      fps: 10,
+      decoder: None,
+      palette: Palette::Web,
+      start: Duration::ZERO,
+      duration: None,
+      title: "clip".to_string(),
+    }
+  }
+
+  // A single row frame holding `text` in `color`
+  pub fn frame(text: &str, color: Rgb) -> AsciiFrame {
+    let mut frame = AsciiFrame::new(text.chars().count() as u16, 1);
+    for (col, ch) in text.chars().enumerate() {
+      frame.set(col as u16, 0, Cell { ch, fg: color, bg: None });
+    }
+    frame
+  }
+}
@@ -0,0 +1,170 @@
+use clap::ValueEnum;
+use font8x8::{UnicodeFonts, BASIC_FONTS, BLOCK_FONTS};
+
+use crate::renderer::{AsciiFrame, Rgb};
+
+// Size of a cell in the images, the 8x8 glyphs are drawn twice as tall like a terminal cell
+pub static CELL_WIDTH: u32 = 8;
+pub static CELL_HEIGHT: u32 = 16;
+
+// Color of the cells without a background
+static BACKGROUND: Rgb = Rgb(0, 0, 0);
+
+// Levels of each channel in the web palette
+static WEB_LEVELS: u32 = 6;
+// Grays added after the web color cube, to fill the 256 colors
+static WEB_GRAYS: u32 = 40;
+
+// The colors the images are reduced to
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Palette {
+  // The 256 colors fitting each frame best, slower and larger
+  Adaptive,
+  // A fixed 6x6x6 color cube and 40 grays, shared by every frame
+  Web,
+  // 256 shades of gray
+  Gray,
+}
+
+// A frame drawn as RGB24 pixels
+pub struct Image {
+  pub width: u32,
+  pub height: u32,
+  // The packed RGB24 pixels, row by row
+  pub data: Vec<u8>,
+}
+
+impl Palette {
+  // The colors of a fixed palette as `[r, g, b, ...]`, `None` for the adaptive one
+  pub fn colors(&self) -> Option<Vec<u8>> {
+    match self {
+      Palette::Adaptive => None,
+      Palette::Web => {
+        let step = 255 / (WEB_LEVELS - 1);
+        let mut colors = Vec::with_capacity(256 * 3);
+        for r in 0..WEB_LEVELS {
+          for g in 0..WEB_LEVELS {
+            for b in 0..WEB_LEVELS {
+              colors.extend([(r * step) as u8, (g * step) as u8, (b * step) as u8]);
+            }
+          }
+        }
+        for gray in 0..WEB_GRAYS {
+          let value = (gray * 255 / (WEB_GRAYS - 1)) as u8;
+          colors.extend([value, value, value]);
+        }
+        Some(colors)
+      },
+      Palette::Gray => Some((0..=255).flat_map(|value| [value, value, value]).collect()),
+    }
+  }
+
+  // Index of the closest color of a fixed palette, the adaptive one has no fixed
+  // colors and is treated as gray
+  pub fn index(&self, color: Rgb) -> u8 {
+    match self {
+      Palette::Adaptive | Palette::Gray => color.luma(),
+      Palette::Web => {
+        let step = 255 / (WEB_LEVELS - 1);
+        let levels = [color.0, color.1, color.2].map(|value| (value as u32 + step / 2) / step);
+        let gray_level = (color.luma() as u32 * (WEB_GRAYS - 1) + 127) / 255;
+
+        let distance = |palette: [u32; 3]| {
+          [color.0, color.1, color.2].iter().zip(palette)
+            .map(|(value, palette)| (*value as i32 - palette as i32).pow(2))
+            .sum::<i32>()
+        };
+        let cube = distance(levels.map(|level| level * step));
+        let gray = distance([gray_level * 255 / (WEB_GRAYS - 1); 3]);
+        if gray < cube {
+          (WEB_LEVELS.pow(3) + gray_level) as u8
+        } else {
+          (levels[0] * WEB_LEVELS * WEB_LEVELS + levels[1] * WEB_LEVELS + levels[2]) as u8
+        }
+      },
+    }
+  }
+
+  // Replace every pixel by its closest color, the adaptive palette keeps them
+  pub fn quantize(&self, image: &mut Image) {
+    let colors = match self.colors() {
+      Some(colors) => colors,
+      None => return,
+    };
+    for pixel in image.data.chunks_exact_mut(3) {
+      let i = self.index(Rgb(pixel[0], pixel[1], pixel[2])) as usize * 3;
+      pixel.copy_from_slice(&colors[i..i + 3]);
+    }
+  }
+}
+
+// The 8x8 glyph of a character, rows from the top, the lowest bit being the leftmost pixel
+fn glyph(ch: char) -> [u8; 8] {
+  BASIC_FONTS.get(ch)
+    .or_else(|| BLOCK_FONTS.get(ch))
+    .or_else(|| BASIC_FONTS.get('?'))
+    .unwrap_or([0; 8])
+}
+
+// Draw the cells of `frame` with the embedded bitmap font
+pub fn rasterize(frame: &AsciiFrame) -> Image {
+  let width = frame.cols as u32 * CELL_WIDTH;
+  let height = frame.rows as u32 * CELL_HEIGHT;
+  let mut data = vec![0; (width * height * 3) as usize];
+
+  for row in 0..frame.rows {
+    for col in 0..frame.cols {
+      let cell = frame.cell(col, row);
+      let glyph = glyph(cell.ch);
+      let bg = cell.bg.unwrap_or(BACKGROUND);
+      for y in 0..CELL_HEIGHT {
+        let bits = glyph[(y * 8 / CELL_HEIGHT) as usize];
+        for x in 0..CELL_WIDTH {
+          let color = if bits >> (x * 8 / CELL_WIDTH) & 1 == 1 { cell.fg } else { bg };
+          let i = (((row as u32 * CELL_HEIGHT + y) * width + col as u32 * CELL_WIDTH + x) * 3) as usize;
+          data[i..i + 3].copy_from_slice(&[color.0, color.1, color.2]);
+        }
+      }
+    }
+  }
+
+  Image { width, height, data }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::renderer::Cell;
+
+  fn pixel(image: &Image, x: u32, y: u32) -> Rgb {
+    let i = ((y * image.width + x) * 3) as usize;
+    Rgb(image.data[i], image.data[i + 1], image.data[i + 2])
+  }
+
+  #[test]
+  fn draws_each_cell_in_a_glyph_box() {
+    let mut frame = AsciiFrame::new(3, 2);
+    frame.set(0, 0, Cell { ch: '█', fg: Rgb(255, 0, 0), bg: None });
+    frame.set(2, 1, Cell { ch: ' ', fg: Rgb(255, 0, 0), bg: Some(Rgb(0, 0, 255)) });
+    let image = rasterize(&frame);
+
+    assert_eq!((image.width, image.height), (3 * CELL_WIDTH, 2 * CELL_HEIGHT));
+    assert_eq!(image.data.len(), (image.width * image.height * 3) as usize);
+    // A full block fills its cell, a space shows its background
+    assert_eq!(pixel(&image, 0, 0), Rgb(255, 0, 0));
+    assert_eq!(pixel(&image, CELL_WIDTH - 1, CELL_HEIGHT - 1), Rgb(255, 0, 0));
+    assert_eq!(pixel(&image, CELL_WIDTH, 0), BACKGROUND);
+    assert_eq!(pixel(&image, 2 * CELL_WIDTH, CELL_HEIGHT), Rgb(0, 0, 255));
+    assert_eq!(pixel(&image, 3 * CELL_WIDTH - 1, 2 * CELL_HEIGHT - 1), Rgb(0, 0, 255));
+  }
+
+  #[test]
+  fn quantizes_to_the_fixed_palettes() {
+    let mut image = Image { width: 1, height: 1, data: vec![250, 10, 10] };
+    Palette::Web.quantize(&mut image);
+    assert_eq!(image.data, [255, 0, 0]);
+    assert_eq!(Palette::Web.colors().unwrap().len(), 256 * 3);
+    assert_eq!(Palette::Gray.index(Rgb(255, 255, 255)), 255);
+    assert!(Palette::Adaptive.colors().is_none());
+  }
+}
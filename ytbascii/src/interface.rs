@@ -0,0 +1,116 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use api::client::APIClient;
+use api::fetcher::{ChannelParams, ChannelTab, ChannelTabParams, CommentsParams, PlaylistParams, SearchParams, VideoParams};
+use api::models::{ChannelObject, ChannelPlaylistsObject, ChannelVideosObject, CommentsObject, CommunityObject, PlaylistObject, SearchItem, VideoObject};
+use ui::library::Library;
+use ui::provider::{MiniPlayer, Provider};
+use youtube::models::video::VideoInfo;
+use youtube::queue::DownloadManager;
+
+use crate::audio::SinkKind;
+use crate::downloads::open_queue;
+use crate::player::{mini, PlayerOptions};
+use crate::renderer::RenderMode;
+use crate::{api_client, defaults, get_logger, keymap, stream_player};
+
+// The interface backed by an Invidious server, and the player
+struct ClientProvider {
+  client: APIClient,
+  // The download queue, started with the first download asked
+  downloads: Mutex<Option<DownloadManager>>,
+}
+
+impl Provider for ClientProvider {
+  fn search(&self, params: SearchParams) -> std::io::Result<Vec<SearchItem>> {
+    self.client.search(params)
+  }
+
+  fn suggestions(&self, query: &str) -> std::io::Result<Vec<String>> {
+    self.client.get_suggestions(query.to_string())
+  }
+
+  fn video(&self, id: &str) -> std::io::Result<VideoObject> {
+    self.client.get_video(id.to_string(), VideoParams { region: None })
+  }
+
+  fn info(&self, id: &str) -> std::io::Result<VideoInfo> {
+    VideoInfo::fetch(id).map_err(std::io::Error::other)
+  }
+
+  fn comments(&self, id: &str, params: CommentsParams) -> std::io::Result<CommentsObject> {
+    self.client.get_comments(id.to_string(), params)
+  }
+
+  fn channel(&self, id: &str) -> std::io::Result<ChannelObject> {
+    self.client.get_channel(id.to_string(), ChannelParams { sort_by: None })
+  }
+
+  fn channel_videos(&self, id: &str, tab: ChannelTab, params: ChannelTabParams) -> std::io::Result<ChannelVideosObject> {
+    self.client.get_channel_videos(id.to_string(), tab, params)
+  }
+
+  fn channel_playlists(&self, id: &str, params: ChannelTabParams) -> std::io::Result<ChannelPlaylistsObject> {
+    self.client.get_channel_playlists(id.to_string(), params)
+  }
+
+  fn community(&self, id: &str, params: ChannelTabParams) -> std::io::Result<CommunityObject> {
+    self.client.get_channel_community(id.to_string(), params)
+  }
+
+  fn playlist(&self, id: &str, page: u32) -> std::io::Result<PlaylistObject> {
+    self.client.get_playlist(id.to_string(), PlaylistParams { page: Some(page) })
+  }
+
+  fn play(&self, id: &str, start: Option<Duration>) -> std::io::Result<Option<Box<dyn MiniPlayer>>> {
+    let options = PlayerOptions { fps: defaults::DEFAULT_FPS, mode: RenderMode::Color, decoder: None, audio: Some(SinkKind::Device) };
+    let player = stream_player(id, options)?;
+    match start {
+      Some(start) => mini::play(player.with_start(start)),
+      None => mini::play(player),
+    }
+  }
+
+  fn download(&self, id: &str) -> std::io::Result<()> {
+    let mut downloads = self.downloads.lock().expect("Failed to lock the download queue");
+    if downloads.is_none() {
+      let manager = open_queue(defaults::DEFAULT_DOWNLOAD_JOBS, None).map_err(std::io::Error::other)?;
+      manager.start();
+      *downloads = Some(manager);
+    }
+    let manager = downloads.as_ref().expect("The download queue is open");
+    match manager.add_video(id) {
+      true => get_logger().success(format!("Downloading {} in the background", id)),
+      false => get_logger().warn(format!("{} is already in the download queue", id)),
+    }
+    Ok(())
+  }
+
+  fn settings(&self) -> Vec<(String, String)> {
+    vec![
+      ("Version".to_string(), env!("CARGO_PKG_VERSION").to_string()),
+      ("Invidious server".to_string(), self.client.api_url().to_string()),
+      ("Servers".to_string(), defaults::DEFAULT_INVIDIOUS_CONFIG_PATH.to_string()),
+      ("Download queue".to_string(), defaults::DEFAULT_DOWNLOADS_PATH.to_string()),
+      ("Thumbnails".to_string(), defaults::DEFAULT_THUMBNAILS_PATH.to_string()),
+      ("Library".to_string(), defaults::DEFAULT_LIBRARY_PATH.to_string()),
+    ]
+  }
+}
+
+// Browse YouTube in the interface until the user quits
+pub fn browse() {
+  ui::init_module();
+  let provider = Arc::new(ClientProvider { client: api_client(), downloads: Mutex::new(None) });
+  let library = Library::load(defaults::DEFAULT_LIBRARY_PATH);
+  let result = ui::run(provider.clone(), keymap().clone(), library);
+  // The unfinished downloads resume with `downloads run`
+  if let Some(manager) = provider.downloads.lock().expect("Failed to lock the download queue").take() {
+    manager.stop();
+  }
+  if let Err(e) = result {
+    get_logger().error(format!("The interface failed: {}", e));
+    std::process::exit(1);
+  }
+}
@@ -1,134 +1,48 @@
+pub mod audio;
+pub mod captions;
+pub mod cli;
+pub mod clock;
 pub mod config;
+pub mod decoder;
 pub mod defaults;
+pub mod downloads;
+pub mod export;
+pub mod interface;
+pub mod media;
+pub mod player;
+pub mod renderer;
+pub mod search;
+pub mod storyboard;
+pub mod streaming;
+pub mod thumbnails;
+pub mod utils;
 
+use clap::Parser;
 use lazy_static::lazy_static;
 use std::sync::Mutex;
 
+use captions::{CaptionPlacement, Captions};
+use cli::{Cli, Command};
+use storyboard::Storyboard;
 use config::{InvidiousConfig, Config};
+use media::MediaInput;
+use export::ExportOptions;
+use player::{Player, PlayerOptions};
+use streaming::RemoteStream;
+use youtube::models::stream::Stream;
+use youtube::models::video::Video;
 use logger::{create_logger, ModuleType, Logger};
+use ui::keymap::Keymap;
+use ui::theme::{self, Theme};
 
 use api::init_module as init_api;
-/* use ffmpeg_next::{
-    format::{input, Pixel},
-    media::Type,
-    software::scaling::{Context, Flags},
-    frame::Video as Frame,
-};
-use std::io::{Write, stdout};
-pub mod utils;
-
-
-static mut LOGGER: Option<Logger> = None;
-
-pub fn init_logger(level: Level) {
-    unsafe {
-        LOGGER = Some(Logger::new(level, "core".to_string()));
-        LOGGER.as_ref().unwrap().info("Logger initialized".to_string());
-    }
-}
-
-pub fn get_logger() -> &'static Logger {
-    unsafe {
-        LOGGER.as_ref().unwrap()
-    }
-}
-
-pub fn init_loggers() {
-    init_logger(Level::Info);
-    youtube::init_logger(Level::Info);
-}
-
-fn save_frame(frame: &Frame, index: usize, output_path: &str) -> std::io::Result<()> {
-    std::fs::create_dir_all(output_path)?;
-    let mut file = std::fs::File::create(format!("{}/frame{}.jpg", output_path, index))?;
-    file.write_all(format!("P6\n{} {}\n255\n", frame.width(), frame.height()).as_bytes())?;
-    file.write_all(frame.data(0))
-}
-
-fn save_ascii_art(frame: &Frame, index: usize, output_path: &str) -> std::io::Result<()> {
+use youtube::init_module as init_youtube;
 
-    let (cols, rows) = utils::get_shell_dim();
-
-    std::fs::create_dir_all(output_path)?;
-    let mut file = std::fs::File::create(format!("{}/frame{}.txt", output_path, index))?;
-    let mut ascii_art = String::new();
-    for y in 0..frame.height() {
-        for x in 0..frame.width() {
-            let pixel = frame.data(0)[(y * 0 + x * 3) as usize];
-            let character = match pixel {
-                0..=63 => ' ',
-                64..=127 => '.',
-                128..=191 => '*',
-                192..=255 => '#',
-            };
-            ascii_art.push(character);
-        }
-        ascii_art.push('\n');
-    }
-
-    stdout().write_all(ascii_art.as_bytes())?;
-    file.write_all(ascii_art.as_bytes())
-}
-
-fn extract_frames(input_path: &str, output_path: &str) {
-    crate::get_logger().info(format!("Extracting frames from {}", input_path));
-    ffmpeg_next::init().unwrap();
-
-    match input(&input_path) {
-        Ok(mut ictx) => {
-            let input = ictx
-                .streams()
-                .best(Type::Video)
-                .ok_or(ffmpeg_next::Error::StreamNotFound)
-                .unwrap();
-            let video_stream_index = input.index();
-
-            let context_decoder =
-                ffmpeg_next::codec::context::Context::from_parameters(input.parameters()).unwrap();
-            let mut decoder = context_decoder.decoder().video().unwrap();
-
-            let mut scaler = Context::get(
-                decoder.format(),
-                decoder.width(),
-                decoder.height(),
-                Pixel::RGB24,
-                decoder.width(),
-                decoder.height(),
-                Flags::BILINEAR,
-            ).unwrap();
-            let mut frame_index: usize = 0;
-
-            let mut receive_and_process_decoded_frames =
-                |decoder: &mut ffmpeg_next::decoder::Video| -> Result<(), ffmpeg_next::Error> {
-                    let mut decoded = Frame::empty();
-                    while decoder.receive_frame(&mut decoded).is_ok() {
-                        let mut rgb_frame = Frame::empty();
-                        scaler.run(&decoded, &mut rgb_frame)?;
-                        save_frame(&rgb_frame, frame_index, output_path).unwrap();
-                        save_ascii_art(&rgb_frame, frame_index, output_path).unwrap();
-                        frame_index += 1;
-                    }
-                    Ok(())
-                };
-            for (stream, packet) in ictx.packets() {
-                if stream.index() == video_stream_index {
-                    let _ = decoder.send_packet(&packet);
-                    let _ = receive_and_process_decoded_frames(&mut decoder);
-                }
-            }
-            let _ = decoder.send_eof();
-            let _ = receive_and_process_decoded_frames(&mut decoder);
-        }
-        Err(e) => {
-            crate::get_logger().error(format!("Error opening input file: {}", e));
-        }
-    }
-}
-*/
 // Define a lazy_static global instance of the CoreLogger
 lazy_static! {
   static ref CORE_LOGGER: Mutex<Box<dyn Logger>> = Mutex::new(create_logger(ModuleType::Core));
   static ref INVIDIOUS_CONFIG: Mutex<Option<InvidiousConfig>> = Mutex::new(None);
+  static ref KEYMAP: Keymap = Keymap::load(defaults::DEFAULT_KEYMAP_PATH);
 }
 
 // Function to access the global CoreLogger instance
@@ -165,6 +79,15 @@ fn init_config() {
   let config = InvidiousConfig::new(defaults::DEFAULT_INVIDIOUS_CONFIG_PATH.to_string());
   *invidious_config = Some(config);
   get_logger().info("Invidious config initialized".to_string());
+  // Read now, its problems would be lost in the player
+  lazy_static::initialize(&KEYMAP);
+  // The log levels take the colors of the theme from here on
+  theme::set(Theme::load(defaults::DEFAULT_THEME_PATH));
+}
+
+// The keys of the interface and the player
+pub fn keymap() -> &'static Keymap {
+  &KEYMAP
 }
 
 fn get_invicous_config() -> std::sync::MutexGuard<'static, Option<InvidiousConfig>> {
@@ -175,52 +98,224 @@ fn init_modules() {
   get_logger().info("Initializing modules...".to_string());
   // Init modules
   init_api();
+  init_youtube();
 }
 
-#[tokio::main]
-async fn main() -> () {
-  // Print banner
-  print_banner();
+// A client on a random online Invidious server, the servers are checked at most once an hour
+fn api_client() -> api::client::APIClient {
+  let server = {
+    let mut config = get_invicous_config();
+    let config = config.as_mut().expect("The config is initialized");
+    config.load();
+    config.check_status();
+    config.save();
+    config.get_random_server().clone()
+  };
+  api::client::APIClient::new(server.url)
+}
 
-  // Init module
-  init_module();
-  
-  // Load config
-  get_invicous_config().as_mut().unwrap().load();
-  get_invicous_config().as_mut().unwrap().check_status();
-  get_invicous_config().as_mut().unwrap().save();
-
-  // Get random server
-  let server = get_invicous_config().as_mut().unwrap().get_random_server().clone();
-  get_logger().info(format!("Random server: {:#?}", server));
-
-  // Create api client
-  let api_client = api::client::APIClient::new(server.url);
-  let _ = api_client.search(api::fetcher::SearchParams {
-    q: "test".to_string(),
-    page: Some(1),
-    sort_by: Some("relevance".to_string()),
-    date: None,
-    duration: None,
-    ctype: None,
-    features: None,
-    region: None,
-  });
+// The caption tracks of a YouTube video, the ones in `language` first
+fn youtube_captions(id: &str, language: &str) -> std::io::Result<Vec<Captions>> {
+  let client = api_client();
+  let mut tracks = client.get_captions(id.to_string())?;
+  if tracks.is_empty() {
+    return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("{} has no captions", id)));
+  }
+  let language = language.to_lowercase();
+  let matches = |track: &api::models::CaptionObject| {
+    track.languageCode.to_lowercase().starts_with(&language) || track.label.to_lowercase().contains(&language)
+  };
+  if !tracks.iter().any(matches) {
+    let available: Vec<&str> = tracks.iter().map(|track| track.languageCode.as_str()).collect();
+    get_logger().warn(format!("No {} captions, available: {}", language, available.join(", ")));
+  }
+  // Stable, the matching tracks keep their order
+  tracks.sort_by_key(|track| !matches(track));
+
+  let mut captions = vec![];
+  for track in &tracks {
+    match client.get_caption_track(id.to_string(), track).and_then(|text| Captions::parse(track.label.clone(), &text)) {
+      Ok(track) => captions.push(track),
+      Err(e) => get_logger().warn(format!("Skipping the {} captions: {}", track.label, e)),
+    }
+  }
+  Ok(captions)
+}
 
-  let _ = api_client.get_video("YSNRcrzSul0".to_string(), api::fetcher::VideoParams {
-    region: Some("FR".to_string()),
-  });
+// Fetch a stream while it plays, starting once a few seconds of it arrived
+fn open_stream(video: &Video, stream: &Stream) -> RemoteStream {
+  let name = format!("{} {} stream of {}", stream.quality_label, stream.stream_type, video.id);
+  let length = Some(stream.content_length as u64).filter(|length| *length > 0);
+  let duration = Some(std::time::Duration::from_millis(stream.duration as u64)).filter(|duration| !duration.is_zero());
+  let prebuffer = (stream.bitrate as f64 / 8.0 * defaults::STREAM_PREBUFFER.as_secs_f64()) as usize;
+  get_logger().debug(format!("Streaming {} ({} bytes, {})", name, stream.content_length, stream.mime_type));
+  RemoteStream::open(name, stream.url.clone(), length, duration, prebuffer)
+}
+
+// Stream the adaptive video and audio tracks of a YouTube video, in the
+// smallest quality the terminal can show
+fn stream_player(id: &str, options: PlayerOptions) -> std::io::Result<Player> {
+  let video = Video::new(id)
+    .map_err(|e| std::io::Error::other(format!("Failed to fetch the video {}: {:?}", id, e)))?;
+  get_logger().info(format!("Streaming \"{}\"", video.title));
+
+  // The player keeps the last row for its status line
+  let (cols, rows) = utils::get_shell_dim();
+  let (width, height) = options.mode.pixel_size(cols, rows.saturating_sub(1).max(1));
+  let selection = video.streams.select(width, height)
+    .map_err(|_| std::io::Error::new(std::io::ErrorKind::NotFound, format!("No video stream for {}", id)))?;
+
+  let audio = selection.audio.filter(|_| options.audio.is_some());
+  let mut player = Player::new(MediaInput::Stream(open_stream(&video, &selection.video)), options)
+    .with_chapters(api::chapters::parse_description(&video.description));
+  if let Some(audio) = audio {
+    player = player.with_audio_input(MediaInput::Stream(open_stream(&video, &audio)));
+  }
+  match Storyboard::fetch(&api_client(), id) {
+    Ok(storyboard) => player = player.with_storyboard(storyboard),
+    Err(e) => get_logger().warn(format!("Seeking without previews: {}", e)),
+  }
+  Ok(player)
+}
+
+// The chapters written in a local file, e.g. a video exported with its chapters
+fn file_chapters(input: &MediaInput) -> Vec<api::chapters::Chapter> {
+  #[cfg(feature = "ffmpeg-cli")]
+  if let MediaInput::File(path) = input {
+    match decoder::ffmpeg::chapters(path) {
+      Ok(chapters) => return chapters,
+      Err(e) => get_logger().warn(format!("Playing without chapters: {}", e)),
+    }
+  }
+  #[cfg(not(feature = "ffmpeg-cli"))]
+  let _ = input;
+  vec![]
+}
+
+// Load the captions asked with `--captions`, a file or a language of the YouTube video `id`
+fn load_captions(captions: &str, id: Option<&str>) -> std::io::Result<Vec<Captions>> {
+  let path = std::path::Path::new(captions);
+  if path.exists() {
+    return Captions::load(path).map(|track| vec![track]);
+  }
+  match id {
+    Some(id) => youtube_captions(id, captions),
+    None => Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("No captions file at {}", captions))),
+  }
+}
 
-    // Get terminal dimensions
-    //let (cols, rows) = utils::get_shell_dim();
+fn play(input: String, options: PlayerOptions, captions: Option<String>, placement: CaptionPlacement) {
+  // A local file wins over a YouTube id with the same name
+  let id = youtube::utils::parse_video_id(&input).filter(|_| !std::path::Path::new(&input).exists());
+  let player = match &id {
+    Some(id) => stream_player(id, options),
+    None => MediaInput::from_arg(&input).map(|input| {
+      let chapters = file_chapters(&input);
+      Player::new(input, options).with_chapters(chapters)
+    }),
+  };
+  let player = match (player, captions) {
+    (Ok(player), Some(captions)) => match load_captions(&captions, id.as_deref()) {
+      Ok(tracks) => Ok(player.with_captions(tracks, placement)),
+      Err(e) => {
+        get_logger().warn(format!("Playing without captions: {}", e));
+        Ok(player)
+      },
+    },
+    (player, _) => player,
+  };
+  let player = match player {
+    Ok(player) => player,
+    Err(e) => {
+      get_logger().error(format!("{}", e));
+      std::process::exit(1);
+    }
+  };
 
-    //let video = Video::new("BAyrperws4c").unwrap();
+  if let Err(e) = player.play() {
+    get_logger().error(format!("Playback failed: {}", e));
+    std::process::exit(1);
+  }
+}
 
-    // Download the best stream
-    //let file_path = video.download();
+// Render `input` to a file, see `export::export`
+fn export_file(input: String, output: Option<String>, mut options: ExportOptions) {
+  let media = match youtube::utils::parse_video_id(&input) {
+    // A local file wins over a YouTube id with the same name
+    Some(id) if !std::path::Path::new(&input).exists() => Video::new(&id)
+      .map_err(|e| std::io::Error::other(format!("Failed to fetch the video {}: {:?}", id, e)))
+      .and_then(|video| {
+        let (width, height) = options.mode.pixel_size(options.cols, options.rows);
+        let stream = video.streams.select_video(width, height)
+          .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, format!("No video stream for {}", id)))?;
+        options.title = video.title.clone();
+        Ok(MediaInput::Stream(open_stream(&video, &stream)))
+      }),
+    _ => MediaInput::from_arg(&input),
+  };
+  let media = match media {
+    Ok(media) => media,
+    Err(e) => {
+      get_logger().error(format!("{}", e));
+      std::process::exit(1);
+    }
+  };
+
+  let output = output.unwrap_or_else(|| {
+    let name = youtube::utils::parse_video_id(&input)
+      .filter(|_| !std::path::Path::new(&input).exists())
+      .or_else(|| std::path::Path::new(&input).file_stem().map(|stem| stem.to_string_lossy().to_string()))
+      .filter(|name| name != "-")
+      .unwrap_or_else(|| "export".to_string());
+    format!("{}.{}", name, options.format.extension())
+  });
+  if options.title.is_empty() {
+    options.title = input.clone();
+  }
+
+  if let Err(e) = export::export(&media, &options, &output) {
+    get_logger().error(format!("Export failed: {}", e));
+    std::process::exit(1);
+  }
+}
 
-    //let output_path = format!("data/{}/frames", video.id);
+#[tokio::main]
+async fn main() {
+  let cli = Cli::parse();
+
+  // Playback skips the Invidious setup
+  if let Some(Command::Play { input, fps, mode, decoder, no_audio, audio_sink, captions, captions_placement }) = cli.command {
+    init_module();
+    let audio = if no_audio { None } else { Some(audio_sink) };
+    play(input, PlayerOptions { fps, mode, decoder, audio }, captions, captions_placement);
+    return;
+  }
+  if let Some(Command::Export { input, format, output, cols, rows, fps, mode, decoder, palette, start, duration }) = cli.command {
+    init_module();
+    let (shell_cols, shell_rows) = termion::terminal_size().unwrap_or((80, 24));
+    let (cols, rows) = (cols.unwrap_or(shell_cols), rows.unwrap_or(shell_rows));
+    let start = std::time::Duration::from_secs_f64(start.max(0.0));
+    let duration = duration.map(|duration| std::time::Duration::from_secs_f64(duration.max(0.0)));
+    let title = String::new();
+    export_file(input, output, ExportOptions { format, mode, cols, rows, fps, decoder, palette, start, duration, title });
+    return;
+  }
+  if let Some(Command::Search { query, page, mode, no_thumbnails }) = cli.command {
+    init_module();
+    search::search(query.join(" "), page, mode, no_thumbnails);
+    return;
+  }
+  if let Some(Command::Downloads { action }) = cli.command {
+    init_module();
+    downloads::downloads(action);
+    return;
+  }
+
+  // Print banner
+  print_banner();
+
+  // Init module
+  init_module();
 
-    // Extract frames
-    //extract_frames(file_path.as_str(), output_path.as_str());
+  interface::browse();
 }
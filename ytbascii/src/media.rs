@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use crate::streaming::RemoteStream;
+
+// Where the media played by the player comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaInput {
+  // A file on the local disk
+  File(PathBuf),
+  // The raw media bytes piped on stdin
+  Stdin,
+  // A media fetched over the network while it plays
+  Stream(RemoteStream),
+}
+
+impl MediaInput {
+  pub fn from_arg(arg: &str) -> std::io::Result<MediaInput> {
+    if arg == "-" {
+      return Ok(MediaInput::Stdin);
+    }
+
+    let path = PathBuf::from(arg);
+    if !path.is_file() {
+      return Err(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("No such file: {}", arg),
+      ));
+    }
+
+    Ok(MediaInput::File(path))
+  }
+}
+
+impl std::fmt::Display for MediaInput {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      MediaInput::File(path) => write!(f, "{}", path.display()),
+      MediaInput::Stdin => write!(f, "stdin"),
+      MediaInput::Stream(stream) => write!(f, "{}", stream),
+    }
+  }
+}
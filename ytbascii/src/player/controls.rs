@@ -0,0 +1,184 @@
+use std::os::fd::AsRawFd;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use termion::event::{Event, Key, MouseButton, MouseEvent};
+use termion::input::TermRead;
+use ui::keymap::{self, Chord, Keymap, Scope};
+
+use crate::{get_logger, keymap};
+
+// Speeds the playback steps through with `speed_down` and `speed_up`
+pub static SPEEDS: &[f64] = &[0.25, 0.5, 0.75, 1.0, 1.25, 1.5, 2.0, 3.0, 4.0];
+
+// Volume change of a single key press
+pub static VOLUME_STEP: f32 = 0.1;
+
+// How long the reader waits for input before checking whether the player stopped
+static INPUT_POLL: Duration = Duration::from_millis(50);
+
+// What the user asks the player to do
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+  TogglePause,
+  // Move by this many seconds, backward when negative
+  Seek(i64),
+  // Jump to this many tenths of the video
+  SeekTenth(u8),
+  SpeedDown,
+  SpeedUp,
+  VolumeDown,
+  VolumeUp,
+  ToggleMute,
+  // Show or hide the captions
+  ToggleCaptions,
+  // Switch to the next caption track
+  NextCaptions,
+  // Jump to the start of the previous or next chapter
+  PreviousChapter,
+  NextChapter,
+  // The left button pressed, moved while held then released, at a column and a row
+  // counted from 1
+  Press(u16, u16),
+  Drag(u16, u16),
+  Release,
+  // Keep playing in the mini player of the interface
+  Minimize,
+  Quit,
+}
+
+// The chord of a key read by termion
+fn chord(key: Key) -> Option<Chord> {
+  let chord = match key {
+    Key::Char('\n') => Chord::new(keymap::Key::Enter),
+    Key::Char('\t') => Chord::new(keymap::Key::Tab),
+    Key::Char(c) => Chord::new(keymap::Key::Char(c)),
+    Key::Ctrl(c) => Chord::ctrl(keymap::Key::Char(c)),
+    Key::Alt(c) => Chord::alt(keymap::Key::Char(c)),
+    Key::Esc => Chord::new(keymap::Key::Esc),
+    Key::Backspace => Chord::new(keymap::Key::Backspace),
+    Key::BackTab => Chord::new(keymap::Key::BackTab),
+    Key::Up => Chord::new(keymap::Key::Up),
+    Key::Down => Chord::new(keymap::Key::Down),
+    Key::Left => Chord::new(keymap::Key::Left),
+    Key::Right => Chord::new(keymap::Key::Right),
+    Key::Home => Chord::new(keymap::Key::Home),
+    Key::End => Chord::new(keymap::Key::End),
+    Key::PageUp => Chord::new(keymap::Key::PageUp),
+    Key::PageDown => Chord::new(keymap::Key::PageDown),
+    Key::Delete => Chord::new(keymap::Key::Delete),
+    Key::Insert => Chord::new(keymap::Key::Insert),
+    Key::F(n) => Chord::new(keymap::Key::F(n)),
+    _ => return None,
+  };
+  Some(chord)
+}
+
+impl Action {
+  // What `key` does in the player scope of `keymap`
+  pub fn from_key(key: Key, keymap: &Keymap) -> Option<Action> {
+    let action = match keymap.action(Scope::Player, chord(key)?) {
+      Some(keymap::Action::PlayPause) => Action::TogglePause,
+      Some(keymap::Action::SeekBackward) => Action::Seek(-5),
+      Some(keymap::Action::SeekForward) => Action::Seek(5),
+      Some(keymap::Action::SeekBackwardLong) => Action::Seek(-60),
+      Some(keymap::Action::SeekForwardLong) => Action::Seek(60),
+      Some(keymap::Action::SpeedDown) => Action::SpeedDown,
+      Some(keymap::Action::SpeedUp) => Action::SpeedUp,
+      Some(keymap::Action::VolumeDown) => Action::VolumeDown,
+      Some(keymap::Action::VolumeUp) => Action::VolumeUp,
+      Some(keymap::Action::ToggleMute) => Action::ToggleMute,
+      Some(keymap::Action::ToggleCaptions) => Action::ToggleCaptions,
+      Some(keymap::Action::NextCaptions) => Action::NextCaptions,
+      Some(keymap::Action::PreviousChapter) => Action::PreviousChapter,
+      Some(keymap::Action::NextChapter) => Action::NextChapter,
+      Some(keymap::Action::Minimize) => Action::Minimize,
+      Some(keymap::Action::Quit) => Action::Quit,
+      // The digits jump to a tenth of the video unless they are bound, and `ctrl-c` always quits
+      _ => match key {
+        Key::Char(c @ '0'..='9') => Action::SeekTenth(c as u8 - b'0'),
+        Key::Ctrl('c') => Action::Quit,
+        _ => return None,
+      },
+    };
+    Some(action)
+  }
+
+  // What the mouse does: the left button clicks and drags, the wheel seeks
+  pub fn from_mouse(mouse: MouseEvent) -> Option<Action> {
+    let action = match mouse {
+      MouseEvent::Press(MouseButton::Left, column, row) => Action::Press(column, row),
+      MouseEvent::Press(MouseButton::WheelUp, _, _) => Action::Seek(5),
+      MouseEvent::Press(MouseButton::WheelDown, _, _) => Action::Seek(-5),
+      MouseEvent::Hold(column, row) => Action::Drag(column, row),
+      MouseEvent::Release(_, _) => Action::Release,
+      _ => return None,
+    };
+    Some(action)
+  }
+}
+
+// The actions read from the terminal, until dropped. The reading stops with it, so the
+// interface gets the keys pressed after the player.
+pub struct Input {
+  pub actions: Receiver<Action>,
+  stop: Arc<AtomicBool>,
+  reader: Option<JoinHandle<()>>,
+}
+
+impl Drop for Input {
+  fn drop(&mut self) {
+    self.stop.store(true, Ordering::SeqCst);
+    if let Some(reader) = self.reader.take() {
+      let _ = reader.join();
+    }
+  }
+}
+
+// Whether `tty` has input to read within `timeout`
+fn readable(tty: &std::fs::File, timeout: Duration) -> bool {
+  let mut fd = libc::pollfd { fd: tty.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+  unsafe { libc::poll(&mut fd, 1, timeout.as_millis() as libc::c_int) > 0 }
+}
+
+// Read the keys and the mouse from the terminal on a thread and turn them into actions.
+// The tty is read rather than stdin, which may be the media being played.
+pub fn spawn_input_reader() -> std::io::Result<Input> {
+  let tty = termion::get_tty()?;
+  let mut events = tty.try_clone()?.events();
+  let (sender, actions) = mpsc::channel();
+  let stop = Arc::new(AtomicBool::new(false));
+  let stopped = stop.clone();
+  let reader = std::thread::spawn(move || {
+    while !stopped.load(Ordering::SeqCst) {
+      if !readable(&tty, INPUT_POLL) {
+        continue;
+      }
+      let Some(event) = events.next() else {
+        return;
+      };
+      match event {
+        Ok(event) => {
+          let action = match event {
+            Event::Key(key) => Action::from_key(key, keymap()),
+            Event::Mouse(mouse) => Action::from_mouse(mouse),
+            Event::Unsupported(_) => None,
+          };
+          if let Some(action) = action {
+            if sender.send(action).is_err() {
+              return;
+            }
+          }
+        },
+        Err(e) => {
+          get_logger().error(format!("Failed to read the keyboard: {}", e));
+          return;
+        },
+      }
+    }
+  });
+  Ok(Input { actions, stop, reader: Some(reader) })
+}
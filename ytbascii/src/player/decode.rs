@@ -0,0 +1,121 @@
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender, TryRecvError};
+use std::time::Duration;
+
+use crate::decoder::{self, Backend, DecoderOptions, FrameSource, StreamInfo, VideoFrame};
+use crate::media::MediaInput;
+
+// Frames decoded ahead of the one being shown
+static QUEUE_LENGTH: usize = 4;
+
+// Frames are tagged with the number of seeks they follow, to tell the stale ones apart
+type Decoded = (u64, std::io::Result<Option<VideoFrame>>);
+
+// What the decoding thread has to show
+pub enum Poll {
+  Frame(VideoFrame),
+  // The media is over
+  Ended,
+  // Nothing was decoded in time, the decoder may wait for the network
+  Pending,
+}
+
+// A frame source running on its own thread, so the player keeps drawing and
+// reading the keyboard while the decoder waits for data
+pub struct DecodeThread {
+  info: StreamInfo,
+  frames: Receiver<Decoded>,
+  seeks: Sender<(u64, Duration)>,
+  generation: u64,
+}
+
+// Decode until the player is gone, waiting for a seek once the media is over
+fn decode(source: &mut dyn FrameSource, frames: SyncSender<Decoded>, seeks: Receiver<(u64, Duration)>) {
+  let mut generation = 0;
+  let mut ended = false;
+
+  loop {
+    let seek = if ended {
+      match seeks.recv() {
+        Ok(seek) => Some(seek),
+        Err(_) => return,
+      }
+    } else {
+      match seeks.try_recv() {
+        Ok(seek) => Some(seek),
+        Err(TryRecvError::Empty) => None,
+        Err(TryRecvError::Disconnected) => return,
+      }
+    };
+
+    let decoded = match seek {
+      Some((seek_generation, position)) => {
+        generation = seek_generation;
+        match source.seek(position) {
+          Ok(()) => {
+            ended = false;
+            continue;
+          },
+          Err(e) => Err(e),
+        }
+      },
+      None => source.next_frame(),
+    };
+
+    ended = !matches!(decoded, Ok(Some(_)));
+    if frames.send((generation, decoded)).is_err() {
+      return;
+    }
+  }
+}
+
+impl DecodeThread {
+  // Open `input` on the decoding thread, the sources do not have to be `Send`
+  pub fn spawn(input: &MediaInput, options: &DecoderOptions, backend: Option<Backend>) -> std::io::Result<DecodeThread> {
+    let (infos, info) = mpsc::channel();
+    let (frames_sender, frames) = mpsc::sync_channel(QUEUE_LENGTH);
+    let (seeks, seeks_receiver) = mpsc::channel();
+
+    let input = input.clone();
+    let options = *options;
+    std::thread::spawn(move || {
+      let mut source = match decoder::open(&input, &options, backend) {
+        Ok(source) => source,
+        Err(e) => {
+          let _ = infos.send(Err(e));
+          return;
+        },
+      };
+      if infos.send(Ok(source.info().clone())).is_err() {
+        return;
+      }
+      decode(source.as_mut(), frames_sender, seeks_receiver);
+    });
+
+    let info = info.recv().map_err(|_| std::io::Error::other("The decoding thread stopped"))??;
+    Ok(DecodeThread { info, frames, seeks, generation: 0 })
+  }
+
+  pub fn info(&self) -> &StreamInfo {
+    &self.info
+  }
+
+  // Move to `position`, the frames decoded before are dropped
+  pub fn seek(&mut self, position: Duration) {
+    self.generation += 1;
+    let _ = self.seeks.send((self.generation, position));
+    // Make room in case the thread waits to hand a stale frame
+    while self.frames.try_recv().is_ok() {}
+  }
+
+  // The next frame at the current position, waiting at most `timeout` for it
+  pub fn poll(&self, timeout: Duration) -> std::io::Result<Poll> {
+    loop {
+      match self.frames.recv_timeout(timeout) {
+        Ok((generation, _)) if generation != self.generation => continue,
+        Ok((_, decoded)) => return Ok(decoded?.map(Poll::Frame).unwrap_or(Poll::Ended)),
+        Err(RecvTimeoutError::Timeout) => return Ok(Poll::Pending),
+        Err(RecvTimeoutError::Disconnected) => return Ok(Poll::Ended),
+      }
+    }
+  }
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use ui::provider::{MiniPlayer, VideoCell};
+
+use crate::get_logger;
+use crate::renderer::{RenderMode, Renderer, Rgb};
+
+use super::{controls, Player, Session};
+
+// The player shrunk in a corner of the interface: the decoding and the sound go on, and the
+// interface draws the frames small
+pub struct MiniPlayback {
+  player: Player,
+  session: Session,
+  // The decoder reached the end of the media
+  over: bool,
+}
+
+// Play full screen until the playback stops, or goes on in the mini player
+pub fn play(mut player: Player) -> std::io::Result<Option<Box<dyn MiniPlayer>>> {
+  get_logger().info(format!("Playing {}", player.input));
+  player.mini = true;
+  let input = controls::spawn_input_reader()?;
+  let session = player.open()?;
+  Ok(player.resume(session, &input.actions)?.map(|session| MiniPlayback::boxed(player, session)))
+}
+
+fn color(rgb: Rgb) -> (u8, u8, u8) {
+  (rgb.0, rgb.1, rgb.2)
+}
+
+impl MiniPlayback {
+  fn boxed(player: Player, session: Session) -> Box<dyn MiniPlayer> {
+    Box::new(MiniPlayback { player, session, over: false })
+  }
+}
+
+impl MiniPlayer for MiniPlayback {
+  fn frame(&mut self, cols: u16, rows: u16, colored: bool) -> std::io::Result<Option<Vec<Vec<VideoCell>>>> {
+    let playback = &mut self.session.playback;
+    playback.buffering = self.player.is_buffering();
+    if !playback.buffering {
+      playback.clock.set_stalled(false);
+    }
+    if !self.over {
+      self.over = self.session.catch_up()?;
+    }
+    let Some(frame) = &self.session.shown else {
+      return Ok(None);
+    };
+    // The half blocks are only told apart by their colors
+    let mode = if colored { RenderMode::Blocks } else { RenderMode::Ascii };
+    let ascii = Renderer::new(mode, cols, rows).render(frame);
+    let cells = (0..ascii.rows).map(|row| {
+      (0..ascii.cols).map(|col| {
+        let cell = ascii.cell(col, row);
+        VideoCell { ch: cell.ch, fg: color(cell.fg), bg: cell.bg.map(color) }
+      }).collect()
+    }).collect();
+    Ok(Some(cells))
+  }
+
+  fn position(&mut self) -> Duration {
+    self.session.playback.clock.now()
+  }
+
+  fn duration(&self) -> Option<Duration> {
+    self.session.playback.duration
+  }
+
+  fn is_paused(&self) -> bool {
+    self.session.playback.clock.is_paused()
+  }
+
+  fn toggle_pause(&mut self) {
+    let clock = &mut self.session.playback.clock;
+    clock.set_paused(!clock.is_paused());
+  }
+
+  fn is_over(&self) -> bool {
+    self.over
+  }
+
+  fn expand(self: Box<Self>) -> std::io::Result<Option<Box<dyn MiniPlayer>>> {
+    let MiniPlayback { player, session, .. } = *self;
+    let input = controls::spawn_input_reader()?;
+    Ok(player.resume(session, &input.actions)?.map(|session| MiniPlayback::boxed(player, session)))
+  }
+}
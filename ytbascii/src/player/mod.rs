@@ -0,0 +1,760 @@
+pub mod controls;
+pub mod decode;
+pub mod mini;
+pub mod status;
+
+use std::io::Write;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+
+use api::chapters::Chapter;
+use termion::input::MouseTerminal;
+use termion::raw::{IntoRawMode, RawTerminal};
+
+use crate::audio::{self, AudioPlayback, SinkKind};
+use crate::captions::{self, CaptionPlacement, Captions};
+use crate::clock::MediaClock;
+use crate::decoder::{Backend, DecoderOptions, VideoFrame};
+use crate::{get_logger, keymap};
+use crate::media::MediaInput;
+use crate::renderer::{AsciiFrame, Frame, RenderMode, Renderer};
+use crate::storyboard::Storyboard;
+use crate::utils::get_shell_dim;
+
+use controls::{Action, SPEEDS, VOLUME_STEP};
+use decode::{DecodeThread, Poll};
+use status::{Status, StatusLayout};
+
+// How long a message stays on the status line
+static MESSAGE_DURATION: Duration = Duration::from_secs(2);
+
+// Past this far into a chapter, going to the previous chapter restarts the current one
+static CHAPTER_RESTART: Duration = Duration::from_secs(3);
+
+// With a storyboard, the seek keys move a preview and the playback seeks once they
+// stopped for this long
+static SCRUB_DELAY: Duration = Duration::from_millis(700);
+
+// Narrowest seek preview, in cells
+static PREVIEW_MIN_COLS: u16 = 16;
+
+// Most caption lines shown at once, and the rows kept for them in the band placement
+static CAPTION_ROWS: u16 = 2;
+
+pub struct PlayerOptions {
+  // Frame rate of the playback
+  pub fps: u32,
+  // How the frames are drawn
+  pub mode: RenderMode,
+  // Decoder to use, detected from the input when `None`
+  pub decoder: Option<Backend>,
+  // Sink the audio is played on, `None` to play without sound
+  pub audio: Option<SinkKind>,
+}
+
+pub struct Player {
+  input: MediaInput,
+  // Where the audio track is, when it is not in `input`
+  audio_input: Option<MediaInput>,
+  options: PlayerOptions,
+  // Caption tracks to choose from, the first one is shown
+  captions: Vec<Captions>,
+  caption_placement: CaptionPlacement,
+  chapters: Vec<Chapter>,
+  storyboard: Option<Storyboard>,
+  // Where the playback starts
+  start: Option<Duration>,
+  // Whether the player can shrink to the mini player of the interface
+  mini: bool,
+}
+
+// Raw mode with a hidden cursor while playing, and the mouse reported unless the keymap
+// turns it off, restored when dropped
+struct TerminalGuard {
+  _raw: RawTerminal<std::fs::File>,
+  _mouse: Option<MouseTerminal<std::fs::File>>,
+}
+
+// What the left button holds while it moves
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Drag {
+  Progress,
+  Volume,
+}
+
+// Everything the user can change while playing
+struct Playback {
+  clock: MediaClock,
+  duration: Option<Duration>,
+  seekable: bool,
+  volume: f32,
+  muted: bool,
+  // A streamed input waits for the network
+  buffering: bool,
+  captions: Vec<Captions>,
+  // Index of the caption track shown
+  caption_track: usize,
+  show_captions: bool,
+  chapters: Vec<Chapter>,
+  storyboard: Option<Storyboard>,
+  // Where the preview is, and when it last moved
+  scrub: Option<(Duration, Instant)>,
+  message: Option<(String, Instant)>,
+  // The row of the status line and where its sliders were last drawn
+  status_row: u16,
+  status: StatusLayout,
+  drag: Option<Drag>,
+}
+
+// A playback going on, kept while the interface shows it in the mini player
+struct Session {
+  decoder: DecodeThread,
+  playback: Playback,
+  frame_duration: Duration,
+  // The next frame, waiting for the clock
+  pending: Option<VideoFrame>,
+  // The last frame drawn
+  shown: Option<Frame>,
+  frames: u32,
+  dropped: u32,
+}
+
+// How long a frame is waited for before the status line is refreshed
+static POLL_TIMEOUT: Duration = Duration::from_millis(20);
+
+impl TerminalGuard {
+  fn new() -> std::io::Result<TerminalGuard> {
+    let raw = termion::get_tty()?.into_raw_mode()?;
+    let mouse = match keymap().mouse {
+      true => Some(MouseTerminal::from(termion::get_tty()?)),
+      false => None,
+    };
+    print!("\x1b[?25l\x1b[2J");
+    Ok(TerminalGuard { _raw: raw, _mouse: mouse })
+  }
+}
+
+impl Drop for TerminalGuard {
+  fn drop(&mut self) {
+    print!("\x1b[0m\x1b[2J\x1b[H\x1b[?25h");
+    let _ = std::io::stdout().flush();
+  }
+}
+
+impl Playback {
+  fn notify(&mut self, message: String) {
+    self.message = Some((message, Instant::now()));
+  }
+
+  fn seek(&mut self, decoder: &mut DecodeThread, position: Duration) {
+    if !self.seekable {
+      self.notify("Cannot seek in this media".to_string());
+      return;
+    }
+    let position = match self.duration {
+      Some(duration) => position.min(duration),
+      None => position,
+    };
+    decoder.seek(position);
+    self.clock.seek(position);
+  }
+
+  // Where the seek keys move from, the preview while scrubbing
+  fn target(&mut self) -> Duration {
+    match self.scrub {
+      Some((position, _)) => position,
+      None => self.clock.now(),
+    }
+  }
+
+  // Seek, or with a storyboard move the preview and seek once the keys settle.
+  // Without seeking there is nothing to preview, the seek says so right away.
+  fn seek_or_scrub(&mut self, decoder: &mut DecodeThread, position: Duration) {
+    if self.storyboard.is_none() || !self.seekable {
+      return self.seek(decoder, position);
+    }
+    let position = match self.duration {
+      Some(duration) => position.min(duration),
+      None => position,
+    };
+    self.scrub = Some((position, Instant::now()));
+  }
+
+  // Seek to the preview once no seek key came for a while, returns whether it did
+  fn settle_scrub(&mut self, decoder: &mut DecodeThread) -> bool {
+    match self.scrub {
+      Some((position, moved)) if moved.elapsed() >= SCRUB_DELAY => {
+        self.scrub = None;
+        self.seek(decoder, position);
+        true
+      },
+      _ => false,
+    }
+  }
+
+  fn seek_chapter(&mut self, next: bool, decoder: &mut DecodeThread) {
+    let now = self.clock.now();
+    let Some(current) = self.chapters.iter().rposition(|chapter| chapter.start <= now) else {
+      self.notify("No chapters".to_string());
+      return;
+    };
+    let index = match next {
+      true if current + 1 < self.chapters.len() => current + 1,
+      true => {
+        self.notify("Last chapter".to_string());
+        return;
+      },
+      false if now - self.chapters[current].start > CHAPTER_RESTART => current,
+      false => current.saturating_sub(1),
+    };
+    let chapter = self.chapters[index].clone();
+    self.scrub = None;
+    self.seek(decoder, chapter.start);
+    self.notify(chapter.title);
+  }
+
+  fn set_speed_step(&mut self, step: isize) {
+    // The audio is restarted at the new speed, which a stream cannot do
+    if self.clock.has_audio() && !self.seekable {
+      self.notify("Cannot change the speed of this media".to_string());
+      return;
+    }
+    let current = SPEEDS.iter().position(|speed| *speed >= self.clock.speed()).unwrap_or(SPEEDS.len() - 1);
+    let index = (current as isize + step).clamp(0, SPEEDS.len() as isize - 1) as usize;
+    self.clock.set_speed(SPEEDS[index]);
+  }
+
+  fn set_volume(&mut self, volume: f32) {
+    self.volume = volume.clamp(0.0, 1.0);
+    self.muted = false;
+    self.clock.set_volume(self.volume);
+  }
+
+  // Seek to where `column` is on the progress bar, or set the volume from the slider
+  fn drag_to(&mut self, drag: Drag, column: u16, decoder: &mut DecodeThread) {
+    match (drag, self.status.bar, self.status.volume, self.duration) {
+      (Drag::Progress, Some(bar), _, Some(duration)) => self.seek_or_scrub(decoder, duration.mul_f64(StatusLayout::fraction(bar, column))),
+      (Drag::Volume, _, Some(volume), _) => self.set_volume(StatusLayout::fraction(volume, column) as f32),
+      _ => {},
+    }
+  }
+
+  // Apply an action, returns false when the playback should stop
+  fn apply(&mut self, action: Action, decoder: &mut DecodeThread) -> bool {
+    match action {
+      Action::TogglePause => {
+        let paused = !self.clock.is_paused();
+        self.clock.set_paused(paused);
+      },
+      Action::Seek(seconds) => {
+        let from = self.target();
+        let position = if seconds < 0 {
+          from.saturating_sub(Duration::from_secs(seconds.unsigned_abs()))
+        } else {
+          from + Duration::from_secs(seconds as u64)
+        };
+        self.seek_or_scrub(decoder, position);
+      },
+      Action::SeekTenth(tenth) => match self.duration {
+        Some(duration) => self.seek_or_scrub(decoder, duration.mul_f64(tenth as f64 / 10.0)),
+        None => self.notify("The duration is unknown".to_string()),
+      },
+      Action::SpeedDown => self.set_speed_step(-1),
+      Action::SpeedUp => self.set_speed_step(1),
+      Action::VolumeDown => self.set_volume(self.volume - VOLUME_STEP),
+      Action::VolumeUp => self.set_volume(self.volume + VOLUME_STEP),
+      Action::ToggleMute => {
+        self.muted = !self.muted;
+        self.clock.set_volume(if self.muted { 0.0 } else { self.volume });
+      },
+      Action::ToggleCaptions => match self.captions.get(self.caption_track) {
+        Some(track) => {
+          self.show_captions = !self.show_captions;
+          let message = if self.show_captions { format!("Captions: {}", track.label) } else { "Captions off".to_string() };
+          self.notify(message);
+        },
+        None => self.notify("No captions".to_string()),
+      },
+      Action::PreviousChapter | Action::NextChapter => self.seek_chapter(action == Action::NextChapter, decoder),
+      Action::NextCaptions => match self.captions.len() {
+        0 => self.notify("No captions".to_string()),
+        count => {
+          self.caption_track = (self.caption_track + 1) % count;
+          self.show_captions = true;
+          self.notify(format!("Captions: {}", self.captions[self.caption_track].label));
+        },
+      },
+      Action::Press(column, row) if row == self.status_row => {
+        // The progress bar only takes clicks when they can seek
+        self.drag = if self.seekable && StatusLayout::hits(self.status.bar, column) {
+          Some(Drag::Progress)
+        } else if StatusLayout::hits(self.status.volume, column) {
+          Some(Drag::Volume)
+        } else {
+          None
+        };
+        if let Some(drag) = self.drag {
+          self.drag_to(drag, column, decoder);
+        }
+      },
+      Action::Press(_, _) => self.drag = None,
+      Action::Drag(column, _) => {
+        if let Some(drag) = self.drag {
+          self.drag_to(drag, column, decoder);
+        }
+      },
+      Action::Release => self.drag = None,
+      Action::Minimize => self.notify("The mini player is in the interface only".to_string()),
+      Action::Quit => return false,
+    }
+    true
+  }
+
+  // Draw the captions and the seek preview over the frame
+  fn draw_overlays(&mut self, frame_rows: u16, band: u16, cols: u16, mode: RenderMode) -> std::io::Result<()> {
+    self.draw_captions(frame_rows, band, cols)?;
+    self.draw_preview(frame_rows, cols, mode)
+  }
+
+  // Draw the storyboard thumbnail of the scrubbed position at the bottom of the frame,
+  // under where it is on the progress bar
+  fn draw_preview(&self, frame_rows: u16, cols: u16, mode: RenderMode) -> std::io::Result<()> {
+    let (Some((position, _)), Some(storyboard)) = (self.scrub, &self.storyboard) else {
+      return Ok(());
+    };
+    let Some(thumbnail) = storyboard.thumbnail(position).filter(|thumbnail| thumbnail.width > 0) else {
+      return Ok(());
+    };
+    // A cell is twice as tall as it is wide
+    let preview_cols = (cols / 4).max(PREVIEW_MIN_COLS).min(cols);
+    let preview_rows = ((preview_cols as u32 * thumbnail.height / thumbnail.width / 2) as u16).clamp(1, frame_rows);
+    let ascii = Renderer::new(mode, preview_cols, preview_rows).render(&thumbnail);
+
+    let center = match self.duration {
+      Some(duration) if !duration.is_zero() => (position.as_secs_f64() / duration.as_secs_f64()).min(1.0) * cols as f64,
+      _ => cols as f64 / 2.0,
+    };
+    let column = (center as u16).saturating_sub(preview_cols / 2).min(cols - preview_cols) + 1;
+    let top = frame_rows - preview_rows + 1;
+
+    let mut stdout = std::io::stdout().lock();
+    for (row, line) in (top..).zip(ascii.to_ansi().split("\r\n")) {
+      write!(stdout, "\x1b[{};{}H{}", row, column, line)?;
+    }
+    stdout.flush()
+  }
+
+  // Draw the active cue in the `band` rows under the frame, or over the bottom of the frame without a band
+  fn draw_captions(&mut self, frame_rows: u16, band: u16, cols: u16) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    for row in 1..=band {
+      write!(stdout, "\x1b[{};1H\x1b[2K", frame_rows + row)?;
+    }
+    if let Some(track) = self.captions.get(self.caption_track).filter(|_| self.show_captions) {
+      let lines = captions::render_lines(track, self.clock.now(), cols, CAPTION_ROWS as usize);
+      let first = (frame_rows + band + 1).saturating_sub(lines.len() as u16);
+      for (row, (column, line)) in (first..).zip(lines) {
+        write!(stdout, "\x1b[{};{}H{}", row, column, line)?;
+      }
+    }
+    stdout.flush()
+  }
+
+  fn status_line(&mut self, width: u16) -> String {
+    if self.message.as_ref().is_some_and(|(_, at)| at.elapsed() > MESSAGE_DURATION) {
+      self.message = None;
+    }
+    let position = self.target();
+    let (line, layout) = Status {
+      position,
+      duration: self.duration,
+      paused: self.clock.is_paused(),
+      buffering: self.buffering,
+      speed: self.clock.speed(),
+      volume: self.volume,
+      muted: self.muted,
+      message: self.message.as_ref().map(|(message, _)| message.as_str()),
+      chapters: &self.chapters,
+    }.render(width);
+    self.status = layout;
+    line
+  }
+}
+
+// stdout is only locked while drawing, the other threads log on it too
+fn draw(ascii: &AsciiFrame, status: Option<(u16, &str)>) -> std::io::Result<()> {
+  let mut stdout = std::io::stdout().lock();
+  write!(stdout, "\x1b[H{}", ascii.to_ansi())?;
+  if let Some((row, status)) = status {
+    write!(stdout, "\x1b[{};1H{}", row, status)?;
+  }
+  stdout.flush()
+}
+
+fn draw_status(row: u16, status: &str) -> std::io::Result<()> {
+  let mut stdout = std::io::stdout().lock();
+  write!(stdout, "\x1b[{};1H{}", row, status)?;
+  stdout.flush()
+}
+
+impl Player {
+  pub fn new(input: MediaInput, options: PlayerOptions) -> Player {
+    Player { input, audio_input: None, options, captions: vec![], caption_placement: CaptionPlacement::Band, chapters: vec![], storyboard: None, start: None, mini: false }
+  }
+
+  // Show captions, `tracks` being switched with the captions key
+  pub fn with_captions(mut self, tracks: Vec<Captions>, placement: CaptionPlacement) -> Player {
+    self.captions = tracks;
+    self.caption_placement = placement;
+    self
+  }
+
+  // Mark the chapters on the progress bar, and jump between them with the chapter keys
+  pub fn with_chapters(mut self, chapters: Vec<Chapter>) -> Player {
+    self.chapters = chapters;
+    self
+  }
+
+  // Preview the seeks with the thumbnails of a storyboard
+  pub fn with_storyboard(mut self, storyboard: Storyboard) -> Player {
+    self.storyboard = Some(storyboard);
+    self
+  }
+
+  // Play the audio track of another input, e.g. a separate audio stream
+  pub fn with_audio_input(mut self, input: MediaInput) -> Player {
+    self.audio_input = Some(input);
+    self
+  }
+
+  // Start playing at `position` rather than at the beginning
+  pub fn with_start(mut self, position: Duration) -> Player {
+    self.start = Some(position);
+    self
+  }
+
+  pub fn play(&self) -> std::io::Result<()> {
+    get_logger().info(format!("Playing {}", self.input));
+    let input = controls::spawn_input_reader()?;
+    self.run(&input.actions)
+  }
+
+  // Whether a streamed input waits for the network
+  fn is_buffering(&self) -> bool {
+    [Some(&self.input), self.audio_input.as_ref()].into_iter().flatten().any(|input| match input {
+      MediaInput::Stream(stream) => stream.is_buffering(),
+      _ => false,
+    })
+  }
+
+  // Play until the media is over or a quit action is received
+  pub fn run(&self, actions: &Receiver<Action>) -> std::io::Result<()> {
+    let session = self.open()?;
+    self.resume(session, actions).map(|_| ())
+  }
+
+  // Rows kept under the frame for the captions band
+  fn band(&self) -> u16 {
+    match self.caption_placement {
+      CaptionPlacement::Band if !self.captions.is_empty() => CAPTION_ROWS,
+      _ => 0,
+    }
+  }
+
+  // Start decoding and playing the sound, from the start asked
+  fn open(&self) -> std::io::Result<Session> {
+    let (cols, rows) = get_shell_dim();
+    self.open_sized(cols, rows)
+  }
+
+  // Open the session for a terminal of `cols` x `rows`
+  fn open_sized(&self, cols: u16, rows: u16) -> std::io::Result<Session> {
+    // The last row is kept for the status line, and the ones above for the captions band
+    let frame_rows = rows.saturating_sub(1 + self.band()).max(1);
+    let (max_width, max_height) = Renderer::new(self.options.mode, cols, frame_rows).frame_box();
+    // The decoders and the audio open at the start rather than seeking once they played from 0:00
+    let start = self.start.unwrap_or_default();
+    let options = DecoderOptions { max_width, max_height, fps: self.options.fps, start };
+    let decoder = DecodeThread::spawn(&self.input, &options, self.options.decoder)?;
+    get_logger().debug(format!("Stream info: {:?}", decoder.info()));
+
+    let audio = match self.options.audio {
+      Some(kind) => match AudioPlayback::start(self.audio_input.as_ref().unwrap_or(&self.input), audio::sink::open(kind), start) {
+        Ok(audio) => Some(audio),
+        Err(e) => {
+          get_logger().warn(format!("Playing without audio: {}", e));
+          None
+        },
+      },
+      None => None,
+    };
+
+    let playback = Playback {
+      clock: MediaClock::new(audio, start),
+      duration: decoder.info().duration,
+      seekable: decoder.info().seekable,
+      volume: 1.0,
+      muted: false,
+      buffering: false,
+      captions: self.captions.clone(),
+      caption_track: 0,
+      show_captions: !self.captions.is_empty(),
+      chapters: self.chapters.clone(),
+      storyboard: self.storyboard.clone(),
+      scrub: None,
+      message: None,
+      status_row: rows,
+      status: StatusLayout::default(),
+      drag: None,
+    };
+
+    let frame_duration = Duration::from_secs_f64(1.0 / decoder.info().fps);
+    Ok(Session { decoder, playback, frame_duration, pending: None, shown: None, frames: 0, dropped: 0 })
+  }
+
+  // Draw the playback full screen until the media is over or a quit action is received. The
+  // session is given back when the player is shrunk to the mini player.
+  fn resume(&self, session: Session, actions: &Receiver<Action>) -> std::io::Result<Option<Session>> {
+    let Session { mut decoder, mut playback, frame_duration, mut pending, mut shown, mut frames, mut dropped } = session;
+    let (cols, rows) = get_shell_dim();
+    let band = self.band();
+    let frame_rows = rows.saturating_sub(1 + band).max(1);
+    let renderer = Renderer::new(self.options.mode, cols, frame_rows);
+    playback.status_row = rows;
+    playback.drag = None;
+
+    let guard = TerminalGuard::new()?;
+    // Where the mini player was, until the next frame
+    if let Some(frame) = &shown {
+      draw(&renderer.render(frame), None)?;
+    }
+    // Set when a seek happened while paused, to show where it landed
+    let mut refresh = false;
+    let mut minimized = false;
+
+    'playback: loop {
+      while let Ok(action) = actions.try_recv() {
+        if action == Action::Minimize && self.mini {
+          minimized = true;
+          break 'playback;
+        }
+        if !playback.apply(action, &mut decoder) {
+          break 'playback;
+        }
+        let seeking = match action {
+          Action::Seek(_) | Action::SeekTenth(_) | Action::PreviousChapter | Action::NextChapter => true,
+          Action::Press(_, _) | Action::Drag(_, _) => playback.drag == Some(Drag::Progress),
+          _ => false,
+        };
+        if seeking && playback.scrub.is_none() {
+          pending = None;
+          refresh = playback.clock.is_paused();
+        }
+      }
+      if playback.settle_scrub(&mut decoder) {
+        pending = None;
+        refresh = playback.clock.is_paused();
+      }
+
+      playback.buffering = self.is_buffering();
+      if !playback.buffering {
+        playback.clock.set_stalled(false);
+      }
+
+      if playback.clock.is_paused() {
+        if refresh {
+          match decoder.poll(POLL_TIMEOUT)? {
+            Poll::Frame(frame) => {
+              draw(&renderer.render(&frame.frame), None)?;
+              playback.draw_overlays(frame_rows, band, cols, self.options.mode)?;
+              shown = Some(frame.frame.clone());
+              pending = Some(frame);
+              refresh = false;
+            },
+            Poll::Ended => refresh = false,
+            Poll::Pending => {},
+          }
+        } else {
+          std::thread::sleep(POLL_TIMEOUT);
+        }
+        draw_status(rows, &playback.status_line(cols))?;
+        playback.draw_overlays(frame_rows, band, cols, self.options.mode)?;
+        continue;
+      }
+
+      let VideoFrame { pts, frame } = match pending.take() {
+        Some(frame) => frame,
+        None => match decoder.poll(POLL_TIMEOUT)? {
+          Poll::Frame(frame) => frame,
+          Poll::Ended => break,
+          Poll::Pending => {
+            // Out of frames, the clock waits for the network rather than running past them
+            playback.clock.set_stalled(playback.buffering);
+            draw_status(rows, &playback.status_line(cols))?;
+            playback.draw_overlays(frame_rows, band, cols, self.options.mode)?;
+            continue;
+          },
+        },
+      };
+
+      // Skip the frames we are already late for
+      let now = playback.clock.now();
+      if now > pts + frame_duration {
+        dropped += 1;
+        continue;
+      }
+      // Wait for the clock to reach the frame, in small steps to stay responsive.
+      // The audio clock does not tick evenly either.
+      if pts > now {
+        std::thread::sleep((pts - now).min(Duration::from_millis(10)));
+        pending = Some(VideoFrame { pts, frame });
+        continue;
+      }
+
+      draw(&renderer.render(&frame), Some((rows, &playback.status_line(cols))))?;
+      playback.draw_overlays(frame_rows, band, cols, self.options.mode)?;
+      shown = Some(frame);
+      frames += 1;
+    }
+
+    drop(guard);
+    if minimized {
+      return Ok(Some(Session { decoder, playback, frame_duration, pending, shown, frames, dropped }));
+    }
+    get_logger().info(format!("Played {} frames ({} dropped)", frames, dropped));
+    Ok(None)
+  }
+}
+
+impl Session {
+  // Take the frames the clock reached without waiting for the next one, the last one taken
+  // being the one to show. Returns whether the media is over.
+  fn catch_up(&mut self) -> std::io::Result<bool> {
+    let now = self.playback.clock.now();
+    loop {
+      let frame = match self.pending.take() {
+        Some(frame) => frame,
+        None => match self.decoder.poll(Duration::ZERO)? {
+          Poll::Frame(frame) => frame,
+          Poll::Ended => return Ok(true),
+          Poll::Pending => {
+            // Out of frames, as in the full screen player
+            self.playback.clock.set_stalled(self.playback.buffering);
+            return Ok(false);
+          },
+        },
+      };
+      if frame.pts > now {
+        self.pending = Some(frame);
+        return Ok(false);
+      }
+      self.shown = Some(frame.frame);
+      self.frames += 1;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use api::client::APIClient;
+  use api::models::StoryboardObject;
+  use std::path::PathBuf;
+
+  // 10 gray frames of 16x8 at 10 fps
+  fn player() -> Player {
+    let input = MediaInput::File(PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/clip.y4m")));
+    Player::new(input, PlayerOptions { fps: 10, mode: RenderMode::Ascii, decoder: None, audio: None })
+  }
+
+  fn first_frame(session: &Session) -> VideoFrame {
+    loop {
+      match session.decoder.poll(Duration::from_secs(1)).unwrap() {
+        Poll::Frame(frame) => return frame,
+        Poll::Pending => continue,
+        Poll::Ended => panic!("The clip ended before its first frame"),
+      }
+    }
+  }
+
+  #[test]
+  fn plays_from_the_beginning() {
+    let mut session = player().open_sized(40, 20).unwrap();
+    assert!(session.playback.clock.now() < Duration::from_millis(100));
+    assert_eq!(first_frame(&session).pts, Duration::ZERO);
+  }
+
+  #[test]
+  fn honours_the_start_position() {
+    let start = Duration::from_millis(600);
+    let mut session = player().with_start(start).open_sized(40, 20).unwrap();
+    let now = session.playback.clock.now();
+    assert!(now >= start && now < start + Duration::from_millis(100));
+    assert_eq!(first_frame(&session).pts, start);
+  }
+
+  #[test]
+  fn jumps_to_the_chapters() {
+    let chapters = vec![
+      Chapter { title: "Intro".to_string(), start: Duration::ZERO },
+      Chapter { title: "Middle".to_string(), start: Duration::from_millis(500) },
+    ];
+    let mut session = player().with_chapters(chapters).open_sized(40, 20).unwrap();
+    session.playback.seek_chapter(true, &mut session.decoder);
+    assert_eq!(session.playback.message.as_ref().map(|(message, _)| message.as_str()), Some("Middle"));
+    assert_eq!(first_frame(&session).pts, Duration::from_millis(500));
+  }
+
+  #[test]
+  fn seeks_in_a_seekable_input() {
+    let mut session = player().open_sized(40, 20).unwrap();
+    session.playback.seek(&mut session.decoder, Duration::from_millis(300));
+    assert!(session.playback.message.is_none());
+    assert_eq!(first_frame(&session).pts, Duration::from_millis(300));
+  }
+
+  fn storyboard() -> Storyboard {
+    Storyboard::new(APIClient::new(String::new()), StoryboardObject::default())
+  }
+
+  #[test]
+  fn seeks_once_the_preview_settles() {
+    let mut session = player().with_storyboard(storyboard()).open_sized(40, 20).unwrap();
+    session.playback.seek_or_scrub(&mut session.decoder, Duration::from_millis(400));
+    assert!(!session.playback.settle_scrub(&mut session.decoder));
+    std::thread::sleep(SCRUB_DELAY);
+    assert!(session.playback.settle_scrub(&mut session.decoder));
+    assert!(session.playback.message.is_none());
+    assert_eq!(first_frame(&session).pts, Duration::from_millis(400));
+  }
+
+  #[test]
+  fn does_not_preview_what_cannot_seek() {
+    let mut session = player().with_storyboard(storyboard()).open_sized(40, 20).unwrap();
+    session.playback.seekable = false;
+    session.playback.seek_or_scrub(&mut session.decoder, Duration::from_millis(400));
+    assert!(session.playback.scrub.is_none());
+    assert_eq!(session.playback.message.as_ref().map(|(message, _)| message.as_str()), Some("Cannot seek in this media"));
+  }
+
+  #[test]
+  fn seeks_where_the_progress_bar_is_clicked() {
+    let mut session = player().open_sized(40, 20).unwrap();
+    session.playback.status = StatusLayout { bar: Some((11, 11)), volume: None };
+    let row = session.playback.status_row;
+    assert!(session.playback.apply(Action::Press(16, row), &mut session.decoder));
+    assert_eq!(session.playback.drag, Some(Drag::Progress));
+    assert_eq!(first_frame(&session).pts, Duration::from_millis(500));
+  }
+
+  #[test]
+  fn ignores_clicks_on_the_progress_bar_of_what_cannot_seek() {
+    let mut session = player().open_sized(40, 20).unwrap();
+    session.playback.seekable = false;
+    session.playback.status = StatusLayout { bar: Some((11, 11)), volume: None };
+    let row = session.playback.status_row;
+    assert!(session.playback.apply(Action::Press(16, row), &mut session.decoder));
+    assert_eq!(session.playback.drag, None);
+    assert!(session.playback.message.is_none());
+  }
+}
@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use api::chapters::Chapter;
+
+use crate::utils::format_duration;
+
+// What the status line shows
+pub struct Status<'a> {
+  pub position: Duration,
+  pub duration: Option<Duration>,
+  pub paused: bool,
+  // Waiting for the network
+  pub buffering: bool,
+  pub speed: f64,
+  pub volume: f32,
+  pub muted: bool,
+  // A short lived message, e.g. an error from the last action
+  pub message: Option<&'a str>,
+  // Marked on the progress bar, the current one is named next to the time
+  pub chapters: &'a [Chapter],
+}
+
+// Longest chapter title shown, the progress bar needs the room
+static CHAPTER_TITLE_WIDTH: usize = 24;
+
+// Cells of the volume slider
+static VOLUME_WIDTH: usize = 10;
+
+// Where the progress bar and the volume slider are on the status line, to find what a
+// click hits: the column of their first cell counted from 1 and how many cells they take
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StatusLayout {
+  pub bar: Option<(u16, u16)>,
+  pub volume: Option<(u16, u16)>,
+}
+
+impl StatusLayout {
+  // How far into the cells of `slider` is `column`, from 0 to 1
+  pub fn fraction(slider: (u16, u16), column: u16) -> f64 {
+    let (start, width) = slider;
+    (column.saturating_sub(start) as f64 / width.saturating_sub(1).max(1) as f64).min(1.0)
+  }
+
+  // Whether `column` is on `slider`, its brackets included
+  pub fn hits(slider: Option<(u16, u16)>, column: u16) -> bool {
+    slider.is_some_and(|(start, width)| column + 1 >= start && column <= start + width)
+  }
+}
+
+impl Status<'_> {
+  // The status as a single line of `width` columns, and where its sliders are
+  pub fn render(&self, width: u16) -> (String, StatusLayout) {
+    let state = if self.paused { "❚❚" } else { "▶ " };
+    let time = match self.duration {
+      Some(duration) => format!("{} / {}", format_duration(self.position), format_duration(duration)),
+      None => format_duration(self.position),
+    };
+    let filled = (self.volume * VOLUME_WIDTH as f32).round() as usize;
+    let slider = format!("[{}{}]", "#".repeat(filled), "-".repeat(VOLUME_WIDTH - filled));
+    let volume = if self.muted { format!("muted {}", slider) } else { format!("vol {} {:>3.0}%", slider, self.volume * 100.0) };
+    let speed = format!(" {:.2}x  ", self.speed);
+    let label = if self.muted { "muted " } else { "vol " };
+    let right = match self.message {
+      Some(message) => format!(" {} ", message),
+      None if self.buffering => " buffering... ".to_string(),
+      None => format!("{}{} ", speed, volume),
+    };
+    let has_slider = self.message.is_none() && !self.buffering;
+    let left = match current_chapter(self.chapters, self.position) {
+      Some(chapter) => {
+        let mut title: String = chapter.title.chars().take(CHAPTER_TITLE_WIDTH).collect();
+        if chapter.title.chars().count() > CHAPTER_TITLE_WIDTH {
+          title.pop();
+          title.push('…');
+        }
+        format!(" {} {} · {} ", state, time, title)
+      },
+      None => format!(" {} {} ", state, time),
+    };
+
+    // The progress bar takes the room left between both sides
+    let used = left.chars().count() + right.chars().count();
+    let bar_width = (width as usize).saturating_sub(used + 2);
+    let bar = match self.duration {
+      Some(duration) if bar_width > 0 && !duration.is_zero() => {
+        let column = |position: Duration| ((position.as_secs_f64() / duration.as_secs_f64()).min(1.0) * bar_width as f64) as usize;
+        let filled = column(self.position);
+        let mut bar: Vec<char> = "=".repeat(filled).chars().chain("-".repeat(bar_width - filled).chars()).collect();
+        // The first chapter starts with the bar
+        for chapter in self.chapters.iter().skip(1) {
+          if let Some(cell) = bar.get_mut(column(chapter.start)) {
+            *cell = '|';
+          }
+        }
+        format!("[{}]", bar.into_iter().collect::<String>())
+      },
+      _ => " ".repeat(bar_width + 2),
+    };
+
+    let mut layout = StatusLayout::default();
+    let left_width = left.chars().count();
+    if bar_width > 0 && self.duration.is_some_and(|duration| !duration.is_zero()) {
+      layout.bar = Some((left_width as u16 + 2, bar_width as u16));
+    }
+    // Past the bracket of the slider
+    let volume_start = left_width + bar_width + 2 + speed.chars().count() + label.len() + 2;
+    if has_slider && volume_start + VOLUME_WIDTH <= width as usize {
+      layout.volume = Some((volume_start as u16, VOLUME_WIDTH as u16));
+    }
+
+    let line = format!("{}{}{}", left, bar, right);
+    let line: String = line.chars().take(width as usize).collect();
+    (format!("\x1b[0m\x1b[7m{:<width$}\x1b[0m", line, width = width as usize), layout)
+  }
+}
+
+// The chapter playing at `position`
+pub fn current_chapter(chapters: &[Chapter], position: Duration) -> Option<&Chapter> {
+  chapters.iter().rev().find(|chapter| chapter.start <= position)
+}
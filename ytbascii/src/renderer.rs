@@ -0,0 +1,293 @@
+use clap::ValueEnum;
+
+// Characters used for the ascii modes, from the darkest to the brightest
+static ASCII_RAMP: &[char] = &[' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+// Upper half block, the foreground colors the top pixel and the background the bottom one
+static HALF_BLOCK: char = '▀';
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum RenderMode {
+  // Grayscale ascii characters
+  Ascii,
+  // Ascii characters colored with the pixel color
+  Color,
+  // Colored half blocks, two pixels per cell
+  Blocks,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+// A decoded RGB24 frame
+#[derive(Debug, Clone)]
+pub struct Frame {
+  // The frame width in pixels
+  pub width: u32,
+  // The frame height in pixels
+  pub height: u32,
+  // The packed RGB24 pixels, row by row
+  pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+  // The character drawn in the cell
+  pub ch: char,
+  // The character color
+  pub fg: Rgb,
+  // The cell background, if any
+  pub bg: Option<Rgb>,
+}
+
+// A frame converted to terminal cells
+#[derive(Debug, Clone)]
+pub struct AsciiFrame {
+  // The frame width in cells
+  pub cols: u16,
+  // The frame height in cells
+  pub rows: u16,
+  // The cells, row by row
+  pub cells: Vec<Cell>,
+}
+
+pub struct Renderer {
+  mode: RenderMode,
+  cols: u16,
+  rows: u16,
+}
+
+impl RenderMode {
+  // How many pixels a single cell draws, horizontally and vertically
+  pub fn samples_per_cell(&self) -> (u32, u32) {
+    match self {
+      RenderMode::Ascii | RenderMode::Color => (1, 1),
+      RenderMode::Blocks => (1, 2),
+    }
+  }
+
+  // The pixel size needed to fill a grid of `cols` x `rows` cells
+  pub fn pixel_size(&self, cols: u16, rows: u16) -> (u32, u32) {
+    let (sx, sy) = self.samples_per_cell();
+    (cols as u32 * sx, rows as u32 * sy)
+  }
+}
+
+impl Rgb {
+  pub fn luma(&self) -> u8 {
+    ((self.0 as u32 * 299 + self.1 as u32 * 587 + self.2 as u32 * 114) / 1000) as u8
+  }
+
+  // The color as a css `#rrggbb` value
+  pub fn to_hex(&self) -> String {
+    format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+  }
+}
+
+impl Frame {
+  pub fn new(width: u32, height: u32, data: Vec<u8>) -> Frame {
+    Frame { width, height, data }
+  }
+
+  pub fn pixel(&self, x: u32, y: u32) -> Rgb {
+    let i = ((y * self.width + x) * 3) as usize;
+    Rgb(self.data[i], self.data[i + 1], self.data[i + 2])
+  }
+
+  // Average color of the pixels in [x0, x1) x [y0, y1)
+  fn average(&self, x0: u32, y0: u32, x1: u32, y1: u32) -> Rgb {
+    let x1 = x1.max(x0 + 1).min(self.width);
+    let y1 = y1.max(y0 + 1).min(self.height);
+    let (mut r, mut g, mut b, mut count) = (0u32, 0u32, 0u32, 0u32);
+    for y in y0..y1 {
+      for x in x0..x1 {
+        let pixel = self.pixel(x, y);
+        r += pixel.0 as u32;
+        g += pixel.1 as u32;
+        b += pixel.2 as u32;
+        count += 1;
+      }
+    }
+    if count == 0 {
+      return Rgb(0, 0, 0);
+    }
+    Rgb((r / count) as u8, (g / count) as u8, (b / count) as u8)
+  }
+}
+
+impl Cell {
+  pub fn blank() -> Cell {
+    Cell { ch: ' ', fg: Rgb(0, 0, 0), bg: None }
+  }
+}
+
+impl AsciiFrame {
+  pub fn new(cols: u16, rows: u16) -> AsciiFrame {
+    AsciiFrame {
+      cols,
+      rows,
+      cells: vec![Cell::blank(); cols as usize * rows as usize],
+    }
+  }
+
+  pub fn cell(&self, col: u16, row: u16) -> &Cell {
+    &self.cells[row as usize * self.cols as usize + col as usize]
+  }
+
+  pub fn set(&mut self, col: u16, row: u16, cell: Cell) {
+    let index = row as usize * self.cols as usize + col as usize;
+    self.cells[index] = cell;
+  }
+
+  // The frame as plain text, without any color
+  pub fn to_plain(&self) -> String {
+    let mut text = String::with_capacity((self.cols as usize + 1) * self.rows as usize);
+    for row in 0..self.rows {
+      for col in 0..self.cols {
+        text.push(self.cell(col, row).ch);
+      }
+      text.push('\n');
+    }
+    text
+  }
+
+  // The frame with truecolor escape codes, one line per row
+  pub fn to_ansi(&self) -> String {
+    let mut text = String::with_capacity(self.cells.len() * 8);
+    for row in 0..self.rows {
+      let mut fg: Option<Rgb> = None;
+      let mut bg: Option<Rgb> = None;
+      for col in 0..self.cols {
+        let cell = self.cell(col, row);
+        if fg != Some(cell.fg) {
+          text.push_str(&format!("\x1b[38;2;{};{};{}m", cell.fg.0, cell.fg.1, cell.fg.2));
+          fg = Some(cell.fg);
+        }
+        if bg != cell.bg {
+          match cell.bg {
+            Some(color) => text.push_str(&format!("\x1b[48;2;{};{};{}m", color.0, color.1, color.2)),
+            None => text.push_str("\x1b[49m"),
+          }
+          bg = cell.bg;
+        }
+        text.push(cell.ch);
+      }
+      text.push_str("\x1b[0m");
+      if row + 1 < self.rows {
+        text.push_str("\r\n");
+      }
+    }
+    text
+  }
+
+  // The frame as html, one line per row and a `<span>` per run of cells sharing their colors
+  pub fn to_html(&self) -> String {
+    let mut html = String::with_capacity(self.cells.len() * 4);
+    for row in 0..self.rows {
+      let mut col = 0;
+      while col < self.cols {
+        let cell = *self.cell(col, row);
+        match cell.bg {
+          Some(bg) => html.push_str(&format!("<span style=\"color:{};background:{}\">", cell.fg.to_hex(), bg.to_hex())),
+          None => html.push_str(&format!("<span style=\"color:{}\">", cell.fg.to_hex())),
+        }
+        while col < self.cols && (self.cell(col, row).fg, self.cell(col, row).bg) == (cell.fg, cell.bg) {
+          match self.cell(col, row).ch {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            ch => html.push(ch),
+          }
+          col += 1;
+        }
+        html.push_str("</span>");
+      }
+      html.push('\n');
+    }
+    html
+  }
+}
+
+impl Renderer {
+  pub fn new(mode: RenderMode, cols: u16, rows: u16) -> Renderer {
+    Renderer { mode, cols, rows }
+  }
+
+  pub fn mode(&self) -> RenderMode {
+    self.mode
+  }
+
+  pub fn size(&self) -> (u16, u16) {
+    (self.cols, self.rows)
+  }
+
+  pub fn resize(&mut self, cols: u16, rows: u16) {
+    self.cols = cols;
+    self.rows = rows;
+  }
+
+  // The largest pixel box, in square pixels, a frame is shown in
+  pub fn frame_box(&self) -> (u32, u32) {
+    (self.cols as u32, self.rows as u32 * 2)
+  }
+
+  // Convert a frame to cells, keeping its aspect ratio and centering it in the grid.
+  // A cell is considered twice as tall as it is wide.
+  pub fn render(&self, frame: &Frame) -> AsciiFrame {
+    let mut ascii = AsciiFrame::new(self.cols, self.rows);
+    if frame.width == 0 || frame.height == 0 || self.cols == 0 || self.rows == 0 {
+      return ascii;
+    }
+
+    let (sx, sy) = self.mode.samples_per_cell();
+    let (box_w, box_h) = self.frame_box();
+    let scale = (box_w as f32 / frame.width as f32).min(box_h as f32 / frame.height as f32);
+
+    // Size of the image in samples
+    let samples_w = ((frame.width as f32 * scale * sx as f32).round() as u32).clamp(1, self.cols as u32 * sx);
+    let samples_h = ((frame.height as f32 * scale * sy as f32 / 2.0).round() as u32).clamp(1, self.rows as u32 * sy);
+    let offset_x = (self.cols as u32 * sx - samples_w) / 2;
+    let offset_y = (self.rows as u32 * sy - samples_h) / 2;
+
+    let sample = |x: u32, y: u32| -> Option<Rgb> {
+      if x < offset_x || y < offset_y || x >= offset_x + samples_w || y >= offset_y + samples_h {
+        return None;
+      }
+      let (x, y) = (x - offset_x, y - offset_y);
+      let x0 = x * frame.width / samples_w;
+      let x1 = (x + 1) * frame.width / samples_w;
+      let y0 = y * frame.height / samples_h;
+      let y1 = (y + 1) * frame.height / samples_h;
+      Some(frame.average(x0, y0, x1, y1))
+    };
+
+    for row in 0..self.rows {
+      for col in 0..self.cols {
+        let (x, y) = (col as u32 * sx, row as u32 * sy);
+        let cell = match self.mode {
+          RenderMode::Ascii | RenderMode::Color => match sample(x, y) {
+            Some(color) => {
+              let luma = color.luma() as usize;
+              let ch = ASCII_RAMP[luma * (ASCII_RAMP.len() - 1) / 255];
+              let fg = if self.mode == RenderMode::Color { color } else { Rgb(luma as u8, luma as u8, luma as u8) };
+              Cell { ch, fg, bg: None }
+            },
+            None => Cell::blank(),
+          },
+          RenderMode::Blocks => match (sample(x, y), sample(x, y + 1)) {
+            (None, None) => Cell::blank(),
+            (top, bottom) => Cell {
+              ch: HALF_BLOCK,
+              fg: top.unwrap_or(Rgb(0, 0, 0)),
+              bg: Some(bottom.unwrap_or(Rgb(0, 0, 0))),
+            },
+          },
+        };
+        ascii.set(col, row, cell);
+      }
+    }
+
+    ascii
+  }
+}
@@ -0,0 +1,110 @@
+use std::io::Write;
+
+use api::fetcher::SearchParams;
+use api::models::SearchItem;
+
+use crate::renderer::{AsciiFrame, RenderMode};
+use crate::thumbnails::{fitting, ThumbnailCache};
+use crate::utils::{format_count, format_duration};
+use crate::{api_client, get_logger};
+
+// Size of the thumbnail next to a result, 16:9 with cells twice as tall as wide
+pub static THUMBNAIL_COLS: u16 = 20;
+pub static THUMBNAIL_ROWS: u16 = 5;
+
+// Pixels wide a thumbnail is fetched at, the smaller ones are blurry once drawn
+static THUMBNAIL_WIDTH: u32 = 120;
+
+// What a result shows: its lines of text, and the url of its thumbnail
+fn describe(item: &SearchItem) -> Option<(Vec<String>, Option<String>)> {
+  match item {
+    SearchItem::Video(video) => {
+      let mut details = vec![video.author.clone(), format!("{} views", format_count(video.viewCount))];
+      if video.lengthSeconds > 0 {
+        details.push(format_duration(std::time::Duration::from_secs(video.lengthSeconds as u64)));
+      }
+      if !video.publishedText.is_empty() {
+        details.push(video.publishedText.clone());
+      }
+      let lines = vec![format!("\x1b[1m{}\x1b[0m", video.title), details.join(" · "), video.videoId.clone()];
+      Some((lines, fitting(&video.videoThumbnails, THUMBNAIL_WIDTH).map(|thumbnail| thumbnail.url.clone())))
+    },
+    SearchItem::Channel(channel) => {
+      let details = format!("channel · {} subscribers · {} videos", format_count(channel.subCount), channel.videoCount);
+      let lines = vec![format!("\x1b[1m{}\x1b[0m", channel.author), details, channel.authorId.clone()];
+      Some((lines, fitting(&channel.authorThumbnails, THUMBNAIL_WIDTH).map(|thumbnail| thumbnail.url.clone())))
+    },
+    SearchItem::Playlist(playlist) => {
+      let details = format!("playlist · {} · {} videos", playlist.author, playlist.videoCount);
+      let lines = vec![format!("\x1b[1m{}\x1b[0m", playlist.title), details, playlist.playlistId.clone()];
+      Some((lines, Some(playlist.playlistThumbnail.clone()).filter(|url| !url.is_empty())))
+    },
+    SearchItem::Other => None,
+  }
+}
+
+// Draw the thumbnails of the results, in parallel as each one is a request
+fn thumbnails(urls: &[Option<String>], mode: RenderMode) -> Vec<Option<AsciiFrame>> {
+  let cache = ThumbnailCache::new(api_client());
+  // The images are fetched with reqwest, which needs the tokio runtime of the caller
+  let runtime = tokio::runtime::Handle::try_current().ok();
+  std::thread::scope(|scope| {
+    let handles: Vec<_> = urls.iter().map(|url| {
+      let (cache, runtime) = (&cache, &runtime);
+      scope.spawn(move || {
+        let _guard = runtime.as_ref().map(|runtime| runtime.enter());
+        let url = url.as_ref()?;
+        cache.render(url, mode, THUMBNAIL_COLS, THUMBNAIL_ROWS)
+          .map_err(|e| get_logger().warn(format!("Failed to draw the thumbnail {}: {}", url, e)))
+          .ok()
+      })
+    }).collect();
+    handles.into_iter().map(|handle| handle.join().ok().flatten()).collect()
+  })
+}
+
+// Print the results of a search, each next to its thumbnail
+pub fn search(query: String, page: u32, mode: RenderMode, no_thumbnails: bool) {
+  let client = api_client();
+  let params = SearchParams {
+    q: query.clone(),
+    page: Some(page),
+    sort_by: None,
+    date: None,
+    duration: None,
+    ctype: None,
+    features: None,
+    region: None,
+  };
+  let items = match client.search(params) {
+    Ok(items) => items,
+    Err(e) => {
+      get_logger().error(format!("Search failed: {}", e));
+      std::process::exit(1);
+    },
+  };
+
+  let results: Vec<(Vec<String>, Option<String>)> = items.iter().filter_map(describe).collect();
+  if results.is_empty() {
+    println!("No results for \"{}\"", query);
+    return;
+  }
+  let urls: Vec<Option<String>> = results.iter().map(|(_, url)| url.clone().filter(|_| !no_thumbnails)).collect();
+  let frames = thumbnails(&urls, mode);
+
+  let mut stdout = std::io::stdout().lock();
+  for ((lines, _), frame) in results.iter().zip(frames) {
+    let frame = frame.map(|frame| frame.to_ansi());
+    let thumbnail: Vec<&str> = match &frame {
+      Some(frame) => frame.split("\r\n").collect(),
+      None => vec![],
+    };
+    let width = if no_thumbnails { 0 } else { THUMBNAIL_COLS as usize };
+    for row in 0..thumbnail.len().max(lines.len()) {
+      // Without a thumbnail the text stays aligned with the others
+      let left = thumbnail.get(row).map(|line| line.to_string()).unwrap_or_else(|| " ".repeat(width));
+      let _ = writeln!(stdout, "{}  {}", left, lines.get(row).map(String::as_str).unwrap_or(""));
+    }
+    let _ = writeln!(stdout);
+  }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use api::client::APIClient;
+use api::models::StoryboardObject;
+
+use crate::get_logger;
+use crate::renderer::Frame;
+use crate::thumbnails::decode;
+
+// A sheet of thumbnails, fetched in the background
+enum Sheet {
+  Loading,
+  Ready(Frame),
+  Failed,
+}
+
+// Thumbnails of a video at regular intervals, a preview of where a seek lands without
+// decoding the video. The thumbnails come in sheets, fetched the first time one is asked.
+#[derive(Clone)]
+pub struct Storyboard {
+  board: StoryboardObject,
+  sheets: Arc<Mutex<HashMap<u32, Sheet>>>,
+  // Indexes of the sheets to fetch
+  requests: Sender<u32>,
+}
+
+// The `width` x `height` pixels of `frame` at `x`, `y`, cut at the frame borders
+fn crop(frame: &Frame, x: u32, y: u32, width: u32, height: u32) -> Frame {
+  let width = width.min(frame.width.saturating_sub(x));
+  let height = height.min(frame.height.saturating_sub(y));
+  let mut data = Vec::with_capacity((width * height * 3) as usize);
+  for row in y..y + height {
+    let start = ((row * frame.width + x) * 3) as usize;
+    data.extend_from_slice(&frame.data[start..start + width as usize * 3]);
+  }
+  Frame::new(width, height, data)
+}
+
+impl Storyboard {
+  // Use the storyboard with the largest thumbnails of a video
+  pub fn fetch(client: &APIClient, video_id: &str) -> std::io::Result<Storyboard> {
+    let board = client.get_storyboards(video_id.to_string())?
+      .into_iter()
+      .filter(|board| board.interval > 0 && board.count > 0 && board.width > 0 && board.height > 0 && !board.templateUrl.is_empty())
+      .max_by_key(|board| board.width * board.height)
+      .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, format!("{} has no storyboard", video_id)))?;
+    Ok(Storyboard::new(client.clone(), board))
+  }
+
+  pub fn new(client: APIClient, board: StoryboardObject) -> Storyboard {
+    let sheets = Arc::new(Mutex::new(HashMap::new()));
+    let (requests, receiver) = mpsc::channel::<u32>();
+
+    // The sheets are fetched with reqwest, which needs the tokio runtime of the caller
+    let runtime = tokio::runtime::Handle::try_current().ok();
+    let template = board.clone();
+    let fetched = sheets.clone();
+    std::thread::spawn(move || {
+      let _guard = runtime.as_ref().map(|runtime| runtime.enter());
+      for index in receiver {
+        let sheet = match client.get_file(&template.sheet_url(index)).and_then(|bytes| decode(&bytes)) {
+          Ok(frame) => Sheet::Ready(frame),
+          Err(e) => {
+            get_logger().warn(format!("Failed to fetch the storyboard sheet {}: {}", index, e));
+            Sheet::Failed
+          },
+        };
+        fetched.lock().expect("Failed to lock the storyboard").insert(index, sheet);
+      }
+    });
+
+    Storyboard { board, sheets, requests }
+  }
+
+  // The thumbnail of `position`, `None` while its sheet is fetched
+  pub fn thumbnail(&self, position: Duration) -> Option<Frame> {
+    let (index, x, y) = self.board.locate(position)?;
+    let mut sheets = self.sheets.lock().expect("Failed to lock the storyboard");
+    match sheets.get(&index) {
+      Some(Sheet::Ready(sheet)) => Some(crop(sheet, x, y, self.board.width, self.board.height)),
+      Some(Sheet::Loading) | Some(Sheet::Failed) => None,
+      None => {
+        sheets.insert(index, Sheet::Loading);
+        let _ = self.requests.send(index);
+        None
+      },
+    }
+  }
+}
@@ -0,0 +1,467 @@
+use std::collections::VecDeque;
+use std::io::{Error, ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::defaults::{STREAM_BUFFER_SIZE, STREAM_CHUNK_SIZE};
+use crate::get_logger;
+
+// Failed range requests in a row before the stream is given up
+static MAX_RETRIES: u32 = 5;
+
+// Bytes fetched ahead of the decoder. It is bounded so a long video is never
+// held in memory, the fetch waits for the decoder when it is full.
+struct RingBuffer {
+  state: Mutex<RingState>,
+  // Signaled when bytes are written or the stream is over
+  readable: Condvar,
+  // Signaled when bytes are read or the reader is gone
+  writable: Condvar,
+}
+
+struct RingState {
+  data: VecDeque<u8>,
+  capacity: usize,
+  // Bytes to wait for before reading, after the start and after running dry
+  prebuffer: usize,
+  // Whether the prebuffer was reached since the buffer last ran dry
+  filled: bool,
+  // Set once the whole stream was written
+  ended: bool,
+  // Why the fetch was given up
+  error: Option<String>,
+  // Set once the reader is gone, the fetch stops
+  closed: bool,
+  // The reader waits for bytes the network did not bring yet
+  starving: bool,
+}
+
+struct Inner {
+  name: String,
+  url: String,
+  // The media duration, as the video metadata tells it
+  duration: Option<Duration>,
+  buffer: Arc<RingBuffer>,
+  taken: AtomicBool,
+}
+
+// A media fetched over HTTP with range requests, read once by a decoder. The decoders
+// seek by opening its url again at another position.
+#[derive(Clone)]
+pub struct RemoteStream {
+  inner: Arc<Inner>,
+}
+
+// The reading end of a `RemoteStream`, blocking until the network brings the bytes
+pub struct StreamReader {
+  inner: Arc<Inner>,
+}
+
+impl RingBuffer {
+  fn new(capacity: usize, prebuffer: usize) -> RingBuffer {
+    RingBuffer {
+      state: Mutex::new(RingState {
+        data: VecDeque::new(),
+        capacity,
+        prebuffer,
+        filled: false,
+        ended: false,
+        error: None,
+        closed: false,
+        starving: false,
+      }),
+      readable: Condvar::new(),
+      writable: Condvar::new(),
+    }
+  }
+
+  fn state(&self) -> MutexGuard<'_, RingState> {
+    self.state.lock().expect("Failed to lock RingBuffer")
+  }
+
+  // Append `data`, waiting for room. Returns false once the reader is gone.
+  fn write(&self, mut data: &[u8]) -> bool {
+    let mut state = self.state();
+    while !data.is_empty() {
+      while state.data.len() >= state.capacity && !state.closed {
+        state = self.writable.wait(state).expect("Failed to lock RingBuffer");
+      }
+      if state.closed {
+        return false;
+      }
+      let count = (state.capacity - state.data.len()).min(data.len());
+      state.data.extend(&data[..count]);
+      data = &data[count..];
+      self.readable.notify_all();
+    }
+    true
+  }
+
+  fn finish(&self, error: Option<String>) {
+    let mut state = self.state();
+    state.ended = true;
+    state.error = error;
+    self.readable.notify_all();
+  }
+
+  fn close(&self) {
+    let mut state = self.state();
+    state.closed = true;
+    state.data.clear();
+    self.writable.notify_all();
+  }
+
+  fn is_closed(&self) -> bool {
+    self.state().closed
+  }
+}
+
+// Fetch `url` chunk by chunk into the buffer, retrying from where a request stopped
+fn fetch(url: &str, length: Option<u64>, buffer: &RingBuffer) -> Result<(), String> {
+  let mut offset = 0;
+  let mut failures = 0;
+
+  loop {
+    if length.is_some_and(|length| offset >= length) {
+      return Ok(());
+    }
+    let end = match length {
+      Some(length) => (offset + STREAM_CHUNK_SIZE).min(length) - 1,
+      None => offset + STREAM_CHUNK_SIZE - 1,
+    };
+
+    let result = youtube::http::get_range(url, offset, end, |data| {
+      if !buffer.write(data) {
+        return false;
+      }
+      offset += data.len() as u64;
+      true
+    });
+    if buffer.is_closed() {
+      return Ok(());
+    }
+
+    let error = match result {
+      // Without a known length, a short chunk is the end of the stream
+      Ok(()) if length.is_none() && offset <= end => return Ok(()),
+      Ok(()) if offset > end => {
+        failures = 0;
+        continue;
+      },
+      Ok(()) => "the server sent a short response".to_string(),
+      Err(e) => e.to_string(),
+    };
+
+    failures += 1;
+    if failures > MAX_RETRIES {
+      return Err(error);
+    }
+    get_logger().warn(format!("Range request failed at byte {}, retrying: {}", offset, error));
+    std::thread::sleep(Duration::from_secs(failures as u64));
+  }
+}
+
+impl RemoteStream {
+  // Start fetching `url` on a thread. `length` and `duration` describe the media when they
+  // are known, and `prebuffer` is the bytes to wait for before the decoder gets any.
+  pub fn open(name: String, url: String, length: Option<u64>, duration: Option<Duration>, prebuffer: usize) -> RemoteStream {
+    let buffer = Arc::new(RingBuffer::new(STREAM_BUFFER_SIZE.max(prebuffer), prebuffer));
+
+    let thread_buffer = buffer.clone();
+    let thread_name = name.clone();
+    let thread_url = url.clone();
+    std::thread::spawn(move || {
+      let result = fetch(&thread_url, length, &thread_buffer);
+      if let Err(e) = &result {
+        get_logger().error(format!("Failed to stream {}: {}", thread_name, e));
+      }
+      thread_buffer.finish(result.err());
+    });
+
+    RemoteStream { inner: Arc::new(Inner { name, url, duration, buffer, taken: AtomicBool::new(false) }) }
+  }
+
+  pub fn url(&self) -> &str {
+    &self.inner.url
+  }
+
+  pub fn duration(&self) -> Option<Duration> {
+    self.inner.duration
+  }
+
+  // Whether the reading end was taken, the stream cannot be read from the start again
+  pub fn is_read(&self) -> bool {
+    self.inner.taken.load(Ordering::Relaxed)
+  }
+
+  // The reading end, it can only be taken once
+  pub fn reader(&self) -> std::io::Result<StreamReader> {
+    if self.inner.taken.swap(true, Ordering::Relaxed) {
+      return Err(Error::new(ErrorKind::Unsupported, format!("{} can only be read once", self.inner.name)));
+    }
+    Ok(StreamReader { inner: self.inner.clone() })
+  }
+
+  // Copy the stream to `writer` on a thread, e.g. the stdin of a decoder process
+  pub fn pipe_to<W: Write + Send + 'static>(&self, mut writer: W) -> std::io::Result<()> {
+    let mut reader = self.reader()?;
+    std::thread::spawn(move || {
+      if let Err(e) = std::io::copy(&mut reader, &mut writer) {
+        // The decoder stopped reading, there is no one left to warn
+        if e.kind() != ErrorKind::BrokenPipe {
+          get_logger().warn(format!("Streaming {} stopped: {}", reader.inner.name, e));
+        }
+      }
+    });
+    Ok(())
+  }
+
+  // Whether the decoder waits for the network
+  pub fn is_buffering(&self) -> bool {
+    let state = self.inner.buffer.state();
+    state.starving && !state.ended
+  }
+}
+
+impl Read for StreamReader {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    let buffer = &self.inner.buffer;
+    let mut state = buffer.state();
+    loop {
+      if state.data.len() >= state.prebuffer {
+        state.filled = true;
+      }
+      if (state.filled && !state.data.is_empty()) || state.ended {
+        break;
+      }
+      state.starving = true;
+      state = buffer.readable.wait(state).expect("Failed to lock RingBuffer");
+    }
+    state.starving = false;
+
+    if state.data.is_empty() {
+      return match &state.error {
+        Some(error) => Err(Error::other(error.clone())),
+        None => Ok(0),
+      };
+    }
+
+    let count = buf.len().min(state.data.len());
+    for (byte, value) in buf.iter_mut().zip(state.data.drain(..count)) {
+      *byte = value;
+    }
+    // Ran dry, wait for the prebuffer again rather than stuttering on every chunk
+    if state.data.is_empty() && !state.ended {
+      state.filled = false;
+    }
+    buffer.writable.notify_all();
+    Ok(count)
+  }
+}
+
+impl Drop for StreamReader {
+  fn drop(&mut self) {
+    self.inner.buffer.close();
+  }
+}
+
+impl Drop for Inner {
+  fn drop(&mut self) {
+    // Nobody can read the stream anymore
+    self.buffer.close();
+  }
+}
+
+impl PartialEq for RemoteStream {
+  fn eq(&self, other: &RemoteStream) -> bool {
+    Arc::ptr_eq(&self.inner, &other.inner)
+  }
+}
+
+impl std::fmt::Debug for RemoteStream {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("RemoteStream").field("name", &self.inner.name).finish()
+  }
+}
+
+impl std::fmt::Display for RemoteStream {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.inner.name)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::{BufRead, BufReader};
+  use std::net::TcpListener;
+  use std::sync::mpsc::{self, Receiver};
+
+  static BODY: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+  // A stream fed by the test rather than the network
+  fn stream(capacity: usize, prebuffer: usize) -> RemoteStream {
+    let buffer = Arc::new(RingBuffer::new(capacity, prebuffer));
+    RemoteStream {
+      inner: Arc::new(Inner { name: "test".to_string(), url: String::new(), duration: None, buffer, taken: AtomicBool::new(false) }),
+    }
+  }
+
+  // Read `stream` on a thread, sending what each read returned
+  fn read_on_thread(stream: &RemoteStream) -> Receiver<std::io::Result<Vec<u8>>> {
+    let mut reader = stream.reader().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || loop {
+      let mut buf = [0; 64];
+      let read = reader.read(&mut buf).map(|count| buf[..count].to_vec());
+      let over = !matches!(&read, Ok(bytes) if !bytes.is_empty());
+      if sender.send(read).is_err() || over {
+        return;
+      }
+    });
+    receiver
+  }
+
+  fn wait_until(condition: impl Fn() -> bool) {
+    for _ in 0..500 {
+      if condition() {
+        return;
+      }
+      std::thread::sleep(Duration::from_millis(2));
+    }
+    panic!("The condition was never met");
+  }
+
+  #[test]
+  fn waits_for_the_prebuffer() {
+    let stream = stream(16, 4);
+    let reads = read_on_thread(&stream);
+    stream.inner.buffer.write(b"ab");
+    wait_until(|| stream.is_buffering());
+    assert!(reads.recv_timeout(Duration::from_millis(50)).is_err());
+
+    stream.inner.buffer.write(b"cd");
+    assert_eq!(reads.recv_timeout(Duration::from_secs(1)).unwrap().unwrap(), b"abcd");
+  }
+
+  #[test]
+  fn waits_for_the_prebuffer_again_once_dry() {
+    let stream = stream(16, 4);
+    stream.inner.buffer.write(b"abcd");
+    let reads = read_on_thread(&stream);
+    assert_eq!(reads.recv_timeout(Duration::from_secs(1)).unwrap().unwrap(), b"abcd");
+
+    // Less than the prebuffer is held back until the stream ends
+    stream.inner.buffer.write(b"e");
+    wait_until(|| stream.is_buffering());
+    assert!(reads.recv_timeout(Duration::from_millis(50)).is_err());
+    stream.inner.buffer.finish(None);
+    assert_eq!(reads.recv_timeout(Duration::from_secs(1)).unwrap().unwrap(), b"e");
+    assert!(reads.recv_timeout(Duration::from_secs(1)).unwrap().unwrap().is_empty());
+    assert!(!stream.is_buffering());
+  }
+
+  #[test]
+  fn reports_the_fetch_error_after_the_bytes_fetched() {
+    let stream = stream(16, 1);
+    stream.inner.buffer.write(b"ab");
+    stream.inner.buffer.finish(Some("Connection reset".to_string()));
+    let reads = read_on_thread(&stream);
+    assert_eq!(reads.recv_timeout(Duration::from_secs(1)).unwrap().unwrap(), b"ab");
+    let error = reads.recv_timeout(Duration::from_secs(1)).unwrap().unwrap_err();
+    assert_eq!(error.to_string(), "Connection reset");
+  }
+
+  #[test]
+  fn holds_the_fetch_while_full_and_stops_it_once_closed() {
+    let stream = stream(4, 1);
+    let buffer = stream.inner.buffer.clone();
+    let (sender, written) = mpsc::channel();
+    std::thread::spawn(move || {
+      let _ = sender.send(buffer.write(b"abcdefgh"));
+      let _ = sender.send(buffer.write(b"ijklmnop"));
+    });
+    assert!(written.recv_timeout(Duration::from_millis(50)).is_err());
+
+    let mut reader = stream.reader().unwrap();
+    let (mut first, mut second) = ([0; 8], [0; 8]);
+    assert_eq!(reader.read(&mut first).unwrap(), 4);
+    assert!(written.recv_timeout(Duration::from_secs(1)).unwrap());
+    assert_eq!(reader.read(&mut second).unwrap(), 4);
+    assert_eq!((&first[..4], &second[..4]), (&b"abcd"[..], &b"efgh"[..]));
+
+    // The reader is gone, nothing can be written anymore
+    wait_until(|| stream.inner.buffer.state().data.len() == 4);
+    drop(reader);
+    assert!(!written.recv_timeout(Duration::from_secs(1)).unwrap());
+    assert!(!stream.inner.buffer.write(b"m"));
+  }
+
+  #[test]
+  fn is_read_once_then_opened_again_from_its_url() {
+    let stream = RemoteStream::open("test".to_string(), "http://127.0.0.1:1/stream".to_string(), None, Some(Duration::from_secs(90)), 1);
+    assert!(!stream.is_read());
+    let reader = stream.reader().unwrap();
+    assert!(stream.is_read());
+    assert_eq!(stream.reader().err().unwrap().kind(), ErrorKind::Unsupported);
+    // What a decoder needs to open the stream again at another position
+    assert_eq!((stream.url(), stream.duration()), ("http://127.0.0.1:1/stream", Some(Duration::from_secs(90))));
+    drop(reader);
+  }
+
+  // Serve `BODY` over range requests, cutting the first response halfway when asked.
+  // The ranges asked are sent on the receiver.
+  fn serve(cut_first: bool) -> (String, Receiver<(usize, usize)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/stream", listener.local_addr().unwrap());
+    let (sender, ranges) = mpsc::channel();
+    std::thread::spawn(move || {
+      for (index, stream) in listener.incoming().enumerate() {
+        let mut stream = stream.unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut range = (0, BODY.len() - 1);
+        loop {
+          let mut line = String::new();
+          reader.read_line(&mut line).unwrap();
+          if line.trim().is_empty() {
+            break;
+          }
+          if let Some((start, end)) = line.trim().strip_prefix("Range: bytes=").and_then(|value| value.split_once('-')) {
+            range = (start.parse().unwrap(), end.parse().unwrap());
+          }
+        }
+        let _ = sender.send(range);
+        let (start, end) = range;
+        let end = end.min(BODY.len() - 1);
+        let sent = if cut_first && index == 0 { start + (end + 1 - start) / 2 } else { end + 1 };
+        let header = format!("HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\n\r\n", end + 1 - start);
+        let _ = stream.write_all(&[header.as_bytes(), &BODY[start..sent]].concat());
+      }
+    });
+    (url, ranges)
+  }
+
+  fn read_all(stream: &RemoteStream) -> Vec<u8> {
+    let mut bytes = vec![];
+    stream.reader().unwrap().read_to_end(&mut bytes).unwrap();
+    bytes
+  }
+
+  #[test]
+  fn streams_over_http() {
+    let (url, ranges) = serve(false);
+    let stream = RemoteStream::open("test".to_string(), url, Some(BODY.len() as u64), None, 4);
+    assert_eq!(read_all(&stream), BODY);
+    assert!(!stream.is_buffering());
+    assert_eq!(ranges.try_iter().collect::<Vec<_>>(), [(0, BODY.len() - 1)]);
+  }
+
+  #[test]
+  fn resumes_a_cut_request_where_it_stopped() {
+    let (url, ranges) = serve(true);
+    let stream = RemoteStream::open("test".to_string(), url, Some(BODY.len() as u64), None, 4);
+    assert_eq!(read_all(&stream), BODY);
+    assert_eq!(ranges.try_iter().collect::<Vec<_>>(), [(0, BODY.len() - 1), (BODY.len() / 2, BODY.len() - 1)]);
+  }
+}
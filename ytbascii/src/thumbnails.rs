@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+
+use api::client::APIClient;
+use api::models::ThumbnailObject;
+
+use crate::defaults::DEFAULT_THUMBNAILS_PATH;
+use crate::get_logger;
+use crate::renderer::{AsciiFrame, Frame, RenderMode, Renderer};
+
+// Thumbnails drawn with the renderer, the images being kept on disk by url so a list
+// shown again does not fetch them twice
+#[derive(Debug, Clone)]
+pub struct ThumbnailCache {
+  client: APIClient,
+  dir: PathBuf,
+}
+
+// FNV-1a, a stable name for the file of a url
+fn hash(url: &str) -> u64 {
+  url.bytes().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+// Decode a jpeg or webp image to RGB24
+pub fn decode(bytes: &[u8]) -> std::io::Result<Frame> {
+  let image = image::load_from_memory(bytes).map_err(std::io::Error::other)?.to_rgb8();
+  Ok(Frame::new(image.width(), image.height(), image.into_raw()))
+}
+
+// The smallest thumbnail at least `width` pixels wide, or the largest one. The few
+// columns of a preview do not need more.
+pub fn fitting(thumbnails: &[ThumbnailObject], width: u32) -> Option<&ThumbnailObject> {
+  let thumbnails = thumbnails.iter().filter(|thumbnail| !thumbnail.url.is_empty());
+  let (large, small): (Vec<&ThumbnailObject>, Vec<&ThumbnailObject>) = thumbnails.partition(|thumbnail| thumbnail.width as u32 >= width);
+  large.into_iter().min_by_key(|thumbnail| thumbnail.width)
+    .or_else(|| small.into_iter().max_by_key(|thumbnail| thumbnail.width))
+}
+
+impl ThumbnailCache {
+  pub fn new(client: APIClient) -> ThumbnailCache {
+    ThumbnailCache { client, dir: PathBuf::from(DEFAULT_THUMBNAILS_PATH) }
+  }
+
+  // Where the image of `url` is kept
+  fn path(&self, url: &str) -> PathBuf {
+    self.dir.join(format!("{:016x}", hash(url)))
+  }
+
+  // The bytes of the image at `url`, fetched when they are not on disk yet
+  pub fn fetch(&self, url: &str) -> std::io::Result<Vec<u8>> {
+    let path = self.path(url);
+    if let Ok(bytes) = std::fs::read(&path) {
+      return Ok(bytes);
+    }
+    let bytes = self.client.get_file(url)?;
+    // A failed write only costs a fetch next time
+    if let Err(e) = std::fs::create_dir_all(&self.dir).and_then(|_| std::fs::write(&path, &bytes)) {
+      get_logger().warn(format!("Failed to cache the thumbnail {}: {}", url, e));
+    }
+    Ok(bytes)
+  }
+
+  // The image at `url` drawn in `cols` x `rows` cells
+  pub fn render(&self, url: &str, mode: RenderMode, cols: u16, rows: u16) -> std::io::Result<AsciiFrame> {
+    let frame = decode(&self.fetch(url)?)?;
+    Ok(Renderer::new(mode, cols, rows).render(&frame))
+  }
+}
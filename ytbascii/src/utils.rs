@@ -5,3 +5,28 @@ pub fn get_shell_dim() -> (u16, u16) {
     (cols, rows)
 }
 
+// Format a duration as `m:ss`, or `h:mm:ss` past an hour
+pub fn format_duration(duration: std::time::Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+// Format a count the way YouTube does, e.g. `950`, `12K` or `1.2M`
+pub fn format_count(count: i64) -> String {
+    let count = count.max(0) as f64;
+    let (value, suffix) = match count {
+        count if count >= 1e9 => (count / 1e9, "B"),
+        count if count >= 1e6 => (count / 1e6, "M"),
+        count if count >= 1e3 => (count / 1e3, "K"),
+        count => return format!("{}", count),
+    };
+    // One decimal under 10, dropped when it is 0
+    match value < 10.0 {
+        true => format!("{}{}", (value * 10.0).floor() / 10.0, suffix),
+        false => format!("{}{}", value.floor(), suffix),
+    }
+}